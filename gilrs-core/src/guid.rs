@@ -0,0 +1,225 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+/// `SDL_HARDWARE_BUS_USB`
+#[allow(dead_code)]
+pub(crate) const BUS_USB: u16 = 0x03;
+/// `SDL_HARDWARE_BUS_BLUETOOTH`
+pub(crate) const BUS_BLUETOOTH: u16 = 0x05;
+/// `SDL_HARDWARE_BUS_VIRTUAL`
+pub(crate) const BUS_VIRTUAL: u16 = 0x06;
+
+/// Gamepad GUID with the same byte layout as SDL joystick GUID.
+///
+/// GUIDs created from vendor and product IDs consist of following little-endian 16-bit values:
+/// bus type, CRC16 of device name (or 0), vendor ID, 0, product ID, 0, version, followed by
+/// two bytes of driver data. `Display` and `FromStr` use the same 32 hex digits format as SDL and
+/// SDL_GameControllerDB.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Guid([u8; 16]);
+
+impl Guid {
+    /// Creates GUID from raw bytes.
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Guid(bytes)
+    }
+
+    /// Creates GUID from bus type, vendor ID, product ID and version, like SDL does for most
+    /// devices. CRC is set to 0.
+    pub fn from_ids(bus: u16, vendor: u16, product: u16, version: u16) -> Self {
+        let mut bytes = [0; 16];
+        bytes[0..2].copy_from_slice(&bus.to_le_bytes());
+        bytes[4..6].copy_from_slice(&vendor.to_le_bytes());
+        bytes[8..10].copy_from_slice(&product.to_le_bytes());
+        bytes[12..14].copy_from_slice(&version.to_le_bytes());
+
+        Guid(bytes)
+    }
+
+    /// Returns raw bytes of GUID.
+    pub const fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Returns `true` if all bytes are zero.
+    pub fn is_nil(&self) -> bool {
+        self.0 == [0; 16]
+    }
+
+    fn word(&self, idx: usize) -> u16 {
+        u16::from_le_bytes([self.0[idx * 2], self.0[idx * 2 + 1]])
+    }
+
+    /// Returns `true` if GUID was created from vendor and product IDs, so `vendor_id()`,
+    /// `product_id()` and `version()` return meaningful values.
+    pub fn has_ids(&self) -> bool {
+        self.word(3) == 0 && self.word(5) == 0 && self.word(2) != 0
+    }
+
+    /// Returns bus type, for example `0x03` for USB and `0x05` for Bluetooth.
+    pub fn bus(&self) -> u16 {
+        self.word(0)
+    }
+
+    /// Returns CRC16 of device name stored in GUID or 0 if GUID doesn't contain CRC.
+    pub fn crc(&self) -> u16 {
+        self.word(1)
+    }
+
+    /// Returns vendor ID if GUID was created from vendor and product IDs.
+    pub fn vendor_id(&self) -> Option<u16> {
+        self.has_ids().then(|| self.word(2))
+    }
+
+    /// Returns product ID if GUID was created from vendor and product IDs.
+    pub fn product_id(&self) -> Option<u16> {
+        self.has_ids().then(|| self.word(4))
+    }
+
+    /// Returns version if GUID was created from vendor and product IDs.
+    pub fn version(&self) -> Option<u16> {
+        self.has_ids().then(|| self.word(6))
+    }
+
+    /// Returns GUID with CRC set to `crc`. SDL 2.26 and newer store CRC of device name in GUID.
+    pub fn with_crc(mut self, crc: u16) -> Self {
+        self.0[2..4].copy_from_slice(&crc.to_le_bytes());
+        self
+    }
+
+    /// Returns GUID with CRC of `name` computed the same way as SDL does.
+    pub fn with_name_crc(self, name: &str) -> Self {
+        self.with_crc(crc16(0, name.as_bytes()))
+    }
+
+    /// Returns GUID with CRC set to 0, which is the format used by older SDL versions and most
+    /// SDL mappings.
+    pub fn without_crc(self) -> Self {
+        self.with_crc(0)
+    }
+}
+
+impl From<[u8; 16]> for Guid {
+    fn from(bytes: [u8; 16]) -> Self {
+        Guid(bytes)
+    }
+}
+
+impl From<Guid> for [u8; 16] {
+    fn from(guid: Guid) -> Self {
+        guid.0
+    }
+}
+
+impl From<Uuid> for Guid {
+    fn from(uuid: Uuid) -> Self {
+        Guid(*uuid.as_bytes())
+    }
+}
+
+impl From<Guid> for Uuid {
+    fn from(guid: Guid) -> Self {
+        Uuid::from_bytes(guid.0)
+    }
+}
+
+impl Display for Guid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Guid {
+    type Err = ParseGuidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 32 || !s.is_ascii() {
+            return Err(ParseGuidError);
+        }
+
+        let mut bytes = [0; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|_| ParseGuidError)?;
+        }
+
+        Ok(Guid(bytes))
+    }
+}
+
+/// Error returned when string is not valid SDL GUID.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseGuidError;
+
+impl Display for ParseGuidError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("GUID has to be 32 hexadecimal digits")
+    }
+}
+
+impl error::Error for ParseGuidError {}
+
+/// CRC16 implementation used by SDL (`SDL_crc16()`).
+pub(crate) fn crc16(mut crc: u16, data: &[u8]) -> u16 {
+    fn crc16_for_byte(mut r: u8) -> u16 {
+        let mut crc = 0u16;
+        for _ in 0..8 {
+            crc = if (crc ^ r as u16) & 1 != 0 { 0xA001 } else { 0 } ^ (crc >> 1);
+            r >>= 1;
+        }
+
+        crc
+    }
+
+    for &byte in data {
+        crc = crc16_for_byte(crc as u8 ^ byte) ^ (crc >> 8);
+    }
+
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc16, Guid, BUS_USB};
+
+    #[test]
+    fn layout() {
+        // Xbox 360 controller
+        let guid = Guid::from_ids(BUS_USB, 0x045e, 0x028e, 0x0114);
+        assert_eq!(guid.to_string(), "030000005e0400008e02000014010000");
+        assert_eq!(guid.vendor_id(), Some(0x045e));
+        assert_eq!(guid.product_id(), Some(0x028e));
+        assert_eq!(guid.version(), Some(0x0114));
+        assert_eq!(guid.bus(), BUS_USB);
+        assert_eq!(guid.crc(), 0);
+
+        let parsed: Guid = "030000005e0400008e02000014010000".parse().unwrap();
+        assert_eq!(parsed, guid);
+        assert!("030000005e0400008e0200001401000".parse::<Guid>().is_err());
+        assert!("030000005e0400008e0200001401000x".parse::<Guid>().is_err());
+
+        let with_crc = guid.with_crc(0xabcd);
+        assert_eq!(with_crc.to_string(), "0300cdab5e0400008e02000014010000");
+        assert_eq!(with_crc.without_crc(), guid);
+    }
+
+    #[test]
+    fn sdl_crc16() {
+        // CRC-16/ARC
+        assert_eq!(crc16(0, b"123456789"), 0xbb3d);
+        assert_eq!(crc16(0, b""), 0);
+    }
+}
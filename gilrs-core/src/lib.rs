@@ -5,13 +5,17 @@ use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
 
+use std::collections::VecDeque;
 use std::error;
 use std::time::Duration;
 use std::time::SystemTime;
 
+mod guid;
 mod platform;
 pub mod utils;
 
+pub use crate::guid::{Guid, ParseGuidError};
+
 /// True, if Y axis of sticks commonly points downwards.
 pub const IS_Y_AXIS_REVERSED: bool = platform::IS_Y_AXIS_REVERSED;
 
@@ -26,6 +30,62 @@ impl FfDevice {
     pub fn set_ff_state(&mut self, strong: u16, weak: u16, min_duration: Duration) {
         self.inner.set_ff_state(strong, weak, min_duration)
     }
+
+    /// Returns number of ff motors. See [`Gamepad::motor_count()`].
+    pub fn motor_count(&self) -> usize {
+        self.inner.motor_count()
+    }
+
+    /// Sets magnitude for every ff motor, in the same order as described in
+    /// [`Gamepad::motor_count()`]. Missing values are treated as 0 and extra values are ignored.
+    pub fn set_motors(&mut self, magnitudes: &[u16], min_duration: Duration) {
+        self.inner.set_motors(magnitudes, min_duration)
+    }
+}
+
+/// Virtual input device created with Linux uinput. Events written to it are seen by other
+/// applications as if they were sent by real device.
+///
+/// Requires write access to `/dev/uinput`.
+#[cfg(all(target_os = "linux", feature = "uinput"))]
+#[derive(Debug)]
+pub struct VirtualDevice {
+    inner: platform::VirtualDevice,
+}
+
+#[cfg(all(target_os = "linux", feature = "uinput"))]
+impl VirtualDevice {
+    /// Creates device with given name, vendor and product ID, buttons and axes. Device is removed
+    /// when `VirtualDevice` is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `/dev/uinput` can't be opened, kernel rejects the device or one of
+    /// `buttons` is not a button or one of `axes` is not an axis.
+    pub fn new(
+        name: &str,
+        vendor_id: u16,
+        product_id: u16,
+        buttons: &[EvCode],
+        axes: &[(EvCode, AxisInfo)],
+    ) -> std::io::Result<Self> {
+        let buttons: Vec<_> = buttons.iter().map(|b| b.0).collect();
+        let axes: Vec<_> = axes.iter().map(|(a, info)| (a.0, *info)).collect();
+
+        platform::VirtualDevice::new(name, vendor_id, product_id, &buttons, &axes)
+            .map(|inner| VirtualDevice { inner })
+    }
+
+    /// Changes value of button (0 or 1) or axis. Changes are not visible to other applications
+    /// until [`sync()`](Self::sync) is called.
+    pub fn emit(&mut self, code: EvCode, value: i32) -> std::io::Result<()> {
+        self.inner.emit(code.0, value)
+    }
+
+    /// Sends all changes made since last call as one report.
+    pub fn sync(&mut self) -> std::io::Result<()> {
+        self.inner.sync()
+    }
 }
 
 /// Holds information about gamepad event.
@@ -57,6 +117,8 @@ pub enum EventType {
     AxisValueChanged(i32, EvCode),
     Connected,
     Disconnected,
+    /// State of headset connected to gamepad's audio jack has changed.
+    HeadsetChanged(HeadsetState),
 }
 
 /// Holds information about expected axis range and deadzone.
@@ -97,41 +159,199 @@ pub enum PowerInfo {
     Charged,
 }
 
+/// State of headset (or other audio device) connected to gamepad's audio jack.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum HeadsetState {
+    /// Platform or device doesn't report headset state. The gamepad may still have an audio jack.
+    Unknown,
+    /// Gamepad reports headset state, but nothing is connected to it.
+    Disconnected,
+    /// Headset is connected to the gamepad.
+    Connected,
+}
+
+/// How gamepad is connected to the computer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum ConnectionType {
+    /// Platform doesn't report connection type.
+    Unknown,
+    /// Gamepad is connected with USB cable. Some wireless receivers, like Xbox Wireless Adapter,
+    /// are USB devices too.
+    Usb,
+    /// Gamepad is connected over Bluetooth.
+    Bluetooth,
+    /// Gamepad is connected wirelessly, but platform doesn't tell which protocol is used.
+    Wireless,
+    /// Gamepad is virtual device created by other application or driver.
+    Virtual,
+}
+
+impl ConnectionType {
+    #[allow(dead_code)]
+    pub(crate) fn from_bus(bus: u16) -> Self {
+        match bus {
+            guid::BUS_USB => ConnectionType::Usb,
+            guid::BUS_BLUETOOTH => ConnectionType::Bluetooth,
+            guid::BUS_VIRTUAL => ConnectionType::Virtual,
+            _ => ConnectionType::Unknown,
+        }
+    }
+}
+
+/// Device that looks like a gamepad, but couldn't be opened because of missing permissions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InaccessibleDevice {
+    /// Path to device node, for example `/dev/input/event5`.
+    pub path: String,
+    /// Name of the device, if it could be read without opening the device.
+    pub name: Option<String>,
+    /// Group that owns the device node. Usually, user has to be member of this group or udev
+    /// rule that grants access to the device has to be installed.
+    pub group: Option<String>,
+}
+
 /// Struct used to manage gamepads and retrieve events.
 #[derive(Debug)]
 pub struct Gilrs {
-    inner: platform::Gilrs,
+    // `None` until devices are scanned, see `new_deferred()`. Boxed to keep `Error` small.
+    inner: Option<Box<platform::Gilrs>>,
+    // Events that were already read from backend and sorted by `sort_events()`.
+    events: VecDeque<Event>,
+    // Poll interval set before devices were scanned.
+    poll_interval: Option<Duration>,
 }
 
+/// Maximum number of events that are read from backend and sorted at once.
+const MAX_BATCH: usize = 1024;
+
 impl Gilrs {
     pub fn new() -> Result<Self, Error> {
         let inner = platform::Gilrs::new().map_err(|e| match e {
-            PlatformError::NotImplemented(inner) => Error::NotImplemented(Gilrs { inner }),
+            PlatformError::NotImplemented(inner) => Error::NotImplemented(Gilrs {
+                inner: Some(inner),
+                events: VecDeque::new(),
+                poll_interval: None,
+            }),
             PlatformError::Other(e) => Error::Other(e),
         })?;
 
-        Ok(Gilrs { inner })
+        Ok(Gilrs {
+            inner: Some(Box::new(inner)),
+            events: VecDeque::new(),
+            poll_interval: None,
+        })
+    }
+
+    /// Creates `Gilrs` without initializing backend, so it doesn't look for gamepads. Until
+    /// [`scan_devices()`](Self::scan_devices) is called, there are no gamepads and no events.
+    pub fn new_deferred() -> Self {
+        Gilrs {
+            inner: None,
+            events: VecDeque::new(),
+            poll_interval: None,
+        }
+    }
+
+    /// Initializes backend of `Gilrs` created with [`new_deferred()`](Self::new_deferred), which
+    /// looks for connected gamepads. Does nothing if backend is already initialized.
+    ///
+    /// On platforms that are not supported, this function succeeds, but no gamepad is found.
+    pub fn scan_devices(&mut self) -> Result<(), Box<dyn error::Error + Send + Sync + 'static>> {
+        if self.inner.is_some() {
+            return Ok(());
+        }
+
+        let mut inner = match platform::Gilrs::new() {
+            Ok(inner) => Box::new(inner),
+            Err(PlatformError::NotImplemented(inner)) => inner,
+            Err(PlatformError::Other(e)) => return Err(e),
+        };
+        if let Some(interval) = self.poll_interval.take() {
+            inner.set_poll_interval(interval);
+        }
+        self.inner = Some(inner);
+
+        Ok(())
+    }
+
+    /// Returns `true` if backend is initialized, that is `Gilrs` wasn't created with
+    /// [`new_deferred()`](Self::new_deferred) or [`scan_devices()`](Self::scan_devices) was
+    /// called.
+    pub fn is_scanned(&self) -> bool {
+        self.inner.is_some()
     }
 
     /// Returns oldest event or `None` if all events were processed.
+    ///
+    /// All events that are available are read at once and returned in non-decreasing order of
+    /// their timestamps, even if they come from different gamepads. `Connected` and
+    /// `Disconnected` events are never reordered.
     pub fn next_event(&mut self) -> Option<Event> {
-        self.inner.next_event()
+        if self.events.is_empty() {
+            let first = self.inner.as_mut()?.next_event()?;
+            self.read_events(first);
+        }
+
+        self.events.pop_front()
     }
 
     /// Returns oldest event, waiting for new event if necessary.
+    ///
+    /// Events are ordered the same way as in `next_event()`. If devices weren't scanned yet,
+    /// `None` is returned immediately.
     pub fn next_event_blocking(&mut self, timeout: Option<Duration>) -> Option<Event> {
-        self.inner.next_event_blocking(timeout)
+        if self.events.is_empty() {
+            let first = self.inner.as_mut()?.next_event_blocking(timeout)?;
+            self.read_events(first);
+        }
+
+        self.events.pop_front()
+    }
+
+    fn read_events(&mut self, first: Event) {
+        let inner = match self.inner {
+            Some(ref mut inner) => inner,
+            None => return,
+        };
+
+        let mut batch = vec![first];
+        while batch.len() < MAX_BATCH {
+            match inner.next_event() {
+                Some(event) => batch.push(event),
+                None => break,
+            }
+        }
+
+        sort_events(&mut batch);
+        self.events.extend(batch);
     }
 
     /// Borrows `Gamepad` or return `None` if index is invalid. Returned gamepad may be disconnected.
     pub fn gamepad(&self, id: usize) -> Option<&Gamepad> {
         unsafe {
-            let gp: Option<&platform::Gamepad> = self.inner.gamepad(id);
+            let gp: Option<&platform::Gamepad> = self.inner.as_ref()?.gamepad(id);
 
             gp.map(|gp| &*(gp as *const _ as *const Gamepad))
         }
     }
 
+    /// Returns devices that look like gamepads, but couldn't be opened because of missing
+    /// permissions. Devices are removed from this list when they are disconnected or opened
+    /// successfully.
+    ///
+    /// Currently, this is only reported on Linux when udev is used to discover devices.
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        match self.inner {
+            Some(ref inner) => inner.inaccessible_devices(),
+            None => &[],
+        }
+    }
+
     /// Returns id greater than id of last connected gamepad. The returned value is only hint
     /// and may be much larger than number of observed gamepads. For example, it may return maximum
     /// number of connected gamepads on platforms when this limit is small.
@@ -139,7 +359,45 @@ impl Gilrs {
     /// `gamepad(id)` should return `Some` if using id that is smaller than value returned from this
     /// function.
     pub fn last_gamepad_hint(&self) -> usize {
-        self.inner.last_gamepad_hint()
+        self.inner
+            .as_ref()
+            .map_or(0, |inner| inner.last_gamepad_hint())
+    }
+
+    /// Returns how often backend reads state of gamepads or `None` if backend doesn't poll
+    /// gamepads, but is notified about new events by the OS.
+    ///
+    /// Currently, XInput and Windows Gaming Input backends poll gamepads in separate thread,
+    /// 10 ms and 8 ms by default. Wasm backend polls when `next_event()` is called, by default on
+    /// every call.
+    ///
+    /// Before devices are scanned, returns interval set with `set_poll_interval()`, if any.
+    pub fn poll_interval(&self) -> Option<Duration> {
+        match self.inner {
+            Some(ref inner) => inner.poll_interval(),
+            None => self.poll_interval,
+        }
+    }
+
+    /// Sets how often backend reads state of gamepads. Shorter interval reduces latency at the
+    /// cost of CPU usage. Does nothing if backend doesn't poll gamepads.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        match self.inner {
+            Some(ref mut inner) => inner.set_poll_interval(interval),
+            None => self.poll_interval = Some(interval),
+        }
+    }
+}
+
+/// Sorts events by time without moving any event across `Connected` or `Disconnected` event.
+///
+/// Sort is stable, so events of one gamepad keep their order even if backend reported them with
+/// the same or decreasing timestamps.
+fn sort_events(events: &mut [Event]) {
+    for segment in
+        events.split_mut(|ev| matches!(ev.event, EventType::Connected | EventType::Disconnected))
+    {
+        segment.sort_by_key(|ev| ev.time);
     }
 }
 
@@ -163,8 +421,9 @@ impl Gamepad {
 
     /// Returns UUID that represents gamepad model.
     ///
-    /// Returned UUID should be the same as SLD2 uses. If platform does not provide any method to
-    /// distinguish between gamepad models, nil UUID is returned.
+    /// Returned UUID has the same byte layout as SDL2 joystick GUID (without CRC), see [`Guid`].
+    /// If platform does not provide any method to distinguish between gamepad models, nil UUID is
+    /// returned.
     ///
     /// It is recommended to process with the [UUID crate](https://crates.io/crates/uuid).
     /// Use `Uuid::from_bytes` method to create a `Uuid` from the returned bytes.
@@ -182,16 +441,84 @@ impl Gamepad {
         self.inner.product_id()
     }
 
+    /// Returns XInput user index (0–3) of the gamepad, if it's connected and driven by XInput.
+    ///
+    /// This is only available with Windows `xinput` backend, on other platforms and backends
+    /// `None` is returned.
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        self.inner.xinput_user_index()
+    }
+
     /// Returns device's power supply state.
     pub fn power_info(&self) -> PowerInfo {
         self.inner.power_info()
     }
 
+    /// Returns state of headset connected to gamepad's audio jack.
+    pub fn headset_state(&self) -> HeadsetState {
+        self.inner.headset_state()
+    }
+
+    /// Returns how gamepad is connected to the computer.
+    pub fn connection_type(&self) -> ConnectionType {
+        self.inner.connection_type()
+    }
+
+    /// Returns path of the OS device node that backs this gamepad, if platform uses them and
+    /// gamepad is connected.
+    ///
+    /// On Linux this is evdev node (`/dev/input/event*`). If device creates multiple event nodes
+    /// (for example separate touchpad or motion sensors node), only the one with most gamepad
+    /// buttons, then most axes is used. Nodes that are gamepads on their own, like ports of
+    /// multi-port adapters, are reported separately. Legacy joydev (`/dev/input/js*`) nodes are
+    /// never used.
+    pub fn device_path(&self) -> Option<&str> {
+        self.inner.device_path()
+    }
+
+    /// Sends HID output report to device. First byte of `data` is report ID, or 0 if device
+    /// doesn't use numbered reports.
+    ///
+    /// This and other functions that access raw HID reports are escape hatch for using vendor
+    /// features that aren't supported by gilrs. They are only available for devices that are
+    /// driven through HID: on Linux (using `hidraw` node, which usually requires additional
+    /// permissions) and macOS. Other platforms return `HidError::NotSupported`.
+    pub fn send_output_report(&self, data: &[u8]) -> Result<(), HidError> {
+        self.inner.send_output_report(data)
+    }
+
+    /// Sends HID feature report to device. First byte of `data` is report ID, or 0 if device
+    /// doesn't use numbered reports.
+    pub fn send_feature_report(&self, data: &[u8]) -> Result<(), HidError> {
+        self.inner.send_feature_report(data)
+    }
+
+    /// Reads HID feature report with ID set in first byte of `buf` (0 if device doesn't use
+    /// numbered reports). Returns number of bytes written to `buf`, including report ID.
+    pub fn get_feature_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        self.inner.get_feature_report(buf)
+    }
+
+    /// Reads current HID input report with ID set in first byte of `buf` (0 if device doesn't use
+    /// numbered reports). Returns number of bytes written to `buf`, including report ID.
+    pub fn get_input_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        self.inner.get_input_report(buf)
+    }
+
     /// Returns true if force feedback is supported by device,
     pub fn is_ff_supported(&self) -> bool {
         self.inner.is_ff_supported()
     }
 
+    /// Returns number of force feedback motors, 0 if force feedback is not supported.
+    ///
+    /// Motors are ordered as follows: strong (usually left, low frequency), weak (usually right,
+    /// high frequency), left trigger, right trigger. Currently, only Windows `wgi` backend reports
+    /// trigger motors.
+    pub fn motor_count(&self) -> usize {
+        self.inner.motor_count()
+    }
+
     /// Creates `FfDevice` corresponding to this gamepad.
     pub fn ff_device(&self) -> Option<FfDevice> {
         self.inner.ff_device().map(|inner| FfDevice { inner })
@@ -220,6 +547,20 @@ impl Gamepad {
     pub fn axis_info(&self, nec: EvCode) -> Option<&AxisInfo> {
         self.inner.axis_info(nec.0)
     }
+
+    /// Reads current value of button (0 or 1) or axis directly from device, bypassing event
+    /// queue. Returns `None` if backend can't read it (it's not supported on macOS and web) or
+    /// gamepad is disconnected.
+    pub fn query_value(&self, nec: EvCode) -> Option<i32> {
+        self.inner.query_value(nec.0)
+    }
+
+    /// Returns human-readable name of button or axis, for example "Paddle P3". Names come from
+    /// known layouts of some devices and, if device isn't one of them, from the platform (evdev
+    /// code names on Linux, HID usage names on macOS). Other platforms return `None`.
+    pub fn code_name(&self, nec: EvCode) -> Option<String> {
+        self.inner.code_name(nec.0)
+    }
 }
 
 #[cfg(feature = "serde-serialize")]
@@ -235,6 +576,34 @@ impl EvCode {
     pub fn into_u32(self) -> u32 {
         self.0.into_u32()
     }
+
+    /// Returns `true` if this is a key of keyboard that is part of gamepad or attached to it (for
+    /// example Xbox 360 chatpad) rather than gamepad button. Such keys are only reported on Linux.
+    pub fn is_key(self) -> bool {
+        #[cfg(target_os = "linux")]
+        return self.0.is_key();
+        #[cfg(not(target_os = "linux"))]
+        return false;
+    }
+
+    /// Returns `true` if this is left or right Shift key.
+    pub fn is_shift_key(self) -> bool {
+        #[cfg(target_os = "linux")]
+        return self.0.is_shift_key();
+        #[cfg(not(target_os = "linux"))]
+        return false;
+    }
+
+    /// Returns character produced by the key with US layout, if any.
+    pub fn key_char(self, shift: bool) -> Option<char> {
+        #[cfg(target_os = "linux")]
+        return self.0.key_char(shift);
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = shift;
+            None
+        }
+    }
 }
 
 impl Display for EvCode {
@@ -251,7 +620,7 @@ enum PlatformError {
     /// Gilrs does not support current platform, but you can use dummy context from this error if
     /// gamepad input is not essential.
     #[allow(dead_code)]
-    NotImplemented(platform::Gilrs),
+    NotImplemented(Box<platform::Gilrs>),
     /// Platform specific error.
     #[allow(dead_code)]
     Other(Box<dyn error::Error + Send + Sync>),
@@ -306,6 +675,44 @@ impl error::Error for Error {
     }
 }
 
+/// Error returned by functions that access raw HID reports, like
+/// [`Gamepad::send_output_report()`].
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum HidError {
+    /// Platform or device doesn't allow access to raw HID reports.
+    NotSupported,
+    /// Gamepad is disconnected.
+    Disconnected,
+    /// Error returned by OS.
+    Io(std::io::Error),
+}
+
+impl Display for HidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HidError::NotSupported => f.write_str("Raw HID reports are not supported by device."),
+            HidError::Disconnected => f.write_str("Gamepad is disconnected."),
+            HidError::Io(e) => write!(f, "HID report failed: {}", e),
+        }
+    }
+}
+
+impl error::Error for HidError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            HidError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for HidError {
+    fn from(e: std::io::Error) -> Self {
+        HidError::Io(e)
+    }
+}
+
 /// Provides the most common mappings of physical location of gamepad elements to their EvCodes.
 /// Some (or most) gamepads may use different mappings.
 pub mod native_ev_codes {
@@ -346,3 +753,63 @@ pub mod native_ev_codes {
     pub const BTN_DPAD_LEFT: EvCode = EvCode(nec::BTN_DPAD_LEFT);
     pub const BTN_DPAD_RIGHT: EvCode = EvCode(nec::BTN_DPAD_RIGHT);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{guid, native_ev_codes, sort_events, ConnectionType, Event, EventType};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn ev(id: usize, event: EventType, ms: u64) -> Event {
+        Event {
+            id,
+            event,
+            time: UNIX_EPOCH + Duration::from_millis(ms),
+        }
+    }
+
+    #[test]
+    fn events_are_merged_by_time() {
+        let press = EventType::ButtonPressed(native_ev_codes::BTN_SOUTH);
+        let release = EventType::ButtonReleased(native_ev_codes::BTN_SOUTH);
+        let mut events = vec![
+            ev(0, press, 10),
+            ev(0, release, 30),
+            ev(1, press, 5),
+            ev(1, release, 20),
+            ev(2, EventType::Connected, 0),
+            ev(2, press, 50),
+            ev(0, press, 40),
+            ev(0, release, 40),
+        ];
+        sort_events(&mut events);
+
+        let order: Vec<_> = events.iter().map(|e| (e.id, e.event)).collect();
+        assert_eq!(
+            order,
+            vec![
+                (1, press),
+                (0, press),
+                (1, release),
+                (0, release),
+                (2, EventType::Connected),
+                (0, press),
+                (0, release),
+                (2, press),
+            ]
+        );
+    }
+
+    #[test]
+    fn connection_type_from_bus() {
+        assert_eq!(ConnectionType::from_bus(guid::BUS_USB), ConnectionType::Usb);
+        assert_eq!(
+            ConnectionType::from_bus(guid::BUS_BLUETOOTH),
+            ConnectionType::Bluetooth
+        );
+        assert_eq!(
+            ConnectionType::from_bus(guid::BUS_VIRTUAL),
+            ConnectionType::Virtual
+        );
+        assert_eq!(ConnectionType::from_bus(0x19), ConnectionType::Unknown);
+    }
+}
@@ -7,7 +7,10 @@
 #![allow(unused_variables)]
 
 use super::FfDevice;
-use crate::{AxisInfo, Event, PlatformError, PowerInfo};
+use crate::{
+    AxisInfo, ConnectionType, Event, HeadsetState, HidError, InaccessibleDevice, PlatformError,
+    PowerInfo,
+};
 use uuid::Uuid;
 
 use std::fmt::{Display, Formatter, Result as FmtResult};
@@ -18,7 +21,7 @@ pub struct Gilrs {}
 
 impl Gilrs {
     pub(crate) fn new() -> Result<Self, PlatformError> {
-        Err(PlatformError::NotImplemented(Gilrs {}))
+        Err(PlatformError::NotImplemented(Box::new(Gilrs {})))
     }
 
     pub(crate) fn next_event(&mut self) -> Option<Event> {
@@ -37,6 +40,16 @@ impl Gilrs {
     pub fn last_gamepad_hint(&self) -> usize {
         0
     }
+
+    pub(crate) fn poll_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    pub(crate) fn set_poll_interval(&mut self, _interval: Duration) {}
+
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        &[]
+    }
 }
 
 #[derive(Debug)]
@@ -61,6 +74,38 @@ impl Gamepad {
         None
     }
 
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn headset_state(&self) -> HeadsetState {
+        HeadsetState::Unknown
+    }
+
+    pub fn connection_type(&self) -> ConnectionType {
+        ConnectionType::Unknown
+    }
+
+    pub fn device_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn send_output_report(&self, data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn send_feature_report(&self, data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_feature_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_input_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
     pub fn power_info(&self) -> PowerInfo {
         PowerInfo::Unknown
     }
@@ -69,6 +114,10 @@ impl Gamepad {
         false
     }
 
+    pub fn motor_count(&self) -> usize {
+        0
+    }
+
     /// Creates Ffdevice corresponding to this gamepad.
     pub fn ff_device(&self) -> Option<FfDevice> {
         Some(FfDevice)
@@ -86,6 +135,14 @@ impl Gamepad {
         None
     }
 
+    pub fn query_value(&self, _nec: EvCode) -> Option<i32> {
+        None
+    }
+
+    pub fn code_name(&self, _nec: EvCode) -> Option<String> {
+        None
+    }
+
     pub fn is_connected(&self) -> bool {
         false
     }
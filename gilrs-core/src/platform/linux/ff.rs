@@ -6,57 +6,50 @@
 // copied, modified, or distributed except according to those terms.
 
 use std::fs::File;
-use std::io::{Error as IoError, ErrorKind, Result as IoResult, Write};
+use std::io::{Result as IoResult, Write};
 use std::os::unix::io::AsRawFd;
-use std::u16::MAX as U16_MAX;
 use std::{mem, slice};
 
 use super::ioctl::{self, ff_effect, ff_replay, ff_rumble_effect, input_event};
 use nix::errno::Errno;
 use std::time::Duration;
 
+/// Force feedback handle of one device.
+///
+/// Some devices support only one or two simultaneous effects, which are shared by all handles and
+/// processes that use the device. To not hold a slot while gamepad is not rumbling, effect is
+/// uploaded to the kernel only when non-zero magnitude is set and removed when magnitudes drop to
+/// zero. If device has no free slot, the change is skipped and upload is retried with the next one.
 #[derive(Debug)]
 pub struct Device {
-    effect: i16,
+    // Id of uploaded kernel effect, `None` if nothing is playing.
+    effect: Option<i16>,
     file: File,
 }
 
 impl Device {
     pub(crate) fn new(path: &str) -> IoResult<Self> {
         let file = File::create(path)?;
-        let mut effect = ff_effect {
-            type_: FF_RUMBLE,
-            id: -1,
-            direction: 0,
-            trigger: Default::default(),
-            replay: Default::default(),
-            u: Default::default(),
-        };
-
-        #[allow(clippy::unnecessary_mut_passed)]
-        let res = unsafe { ioctl::eviocsff(file.as_raw_fd(), &mut effect) };
 
-        if res.is_err() {
-            Err(IoError::new(ErrorKind::Other, "Failed to create effect"))
-        } else {
-            Ok(Device {
-                effect: effect.id,
-                file,
-            })
-        }
+        Ok(Device { effect: None, file })
     }
 
     pub fn set_ff_state(&mut self, strong: u16, weak: u16, min_duration: Duration) {
+        if strong == 0 && weak == 0 {
+            self.remove_effect();
+            return;
+        }
+
         let duration = min_duration.as_secs() * 1000 + u64::from(min_duration.subsec_millis());
-        let duration = if duration > u64::from(U16_MAX) {
-            U16_MAX
+        let duration = if duration > u64::from(u16::MAX) {
+            u16::MAX
         } else {
             duration as u16
         };
 
         let mut effect = ff_effect {
             type_: FF_RUMBLE,
-            id: self.effect,
+            id: self.effect.unwrap_or(-1),
             direction: 0,
             trigger: Default::default(),
             replay: ff_replay {
@@ -71,13 +64,27 @@ impl Device {
             (*rumble).strong_magnitude = strong;
             (*rumble).weak_magnitude = weak;
 
-            if let Err(err) = ioctl::eviocsff(self.file.as_raw_fd(), &effect) {
-                error!(
-                    "Failed to modify effect of gamepad {:?}, error: {}",
-                    self.file, err
-                );
-
-                return;
+            // Kernel sets id of newly uploaded effect.
+            #[allow(clippy::unnecessary_mut_passed)]
+            let res = ioctl::eviocsff(self.file.as_raw_fd(), &mut effect);
+            match res {
+                Ok(_) => self.effect = Some(effect.id),
+                Err(Errno::ENOSPC) => {
+                    debug!(
+                        "Device {:?} has no free effect slot, skipping force feedback update",
+                        self.file
+                    );
+
+                    return;
+                }
+                Err(err) => {
+                    error!(
+                        "Failed to modify effect of gamepad {:?}, error: {}",
+                        self.file, err
+                    );
+
+                    return;
+                }
             }
         };
 
@@ -87,7 +94,7 @@ impl Device {
         };
         let ev = input_event {
             type_: EV_FF,
-            code: self.effect as u16,
+            code: effect.id as u16,
             value: 1,
             time,
         };
@@ -101,14 +108,28 @@ impl Device {
             Err(e) => error!("Failed to set ff state: {}", e),
         }
     }
-}
 
-impl Drop for Device {
-    fn drop(&mut self) {
+    pub fn motor_count(&self) -> usize {
+        2
+    }
+
+    pub fn set_motors(&mut self, magnitudes: &[u16], min_duration: Duration) {
+        let strong = magnitudes.first().copied().unwrap_or(0);
+        let weak = magnitudes.get(1).copied().unwrap_or(0);
+        self.set_ff_state(strong, weak, min_duration);
+    }
+
+    /// Removes uploaded effect, which also stops it, and frees its slot.
+    fn remove_effect(&mut self) {
+        let effect = match self.effect.take() {
+            Some(effect) => effect,
+            None => return,
+        };
+
         #[cfg(target_os = "linux")]
-        let effect = self.effect as ::libc::c_ulong;
+        let effect = effect as ::libc::c_ulong;
         #[cfg(not(target_os = "linux"))]
-        let effect = self.effect as ::libc::c_int;
+        let effect = effect as ::libc::c_int;
 
         if let Err(err) = unsafe { ioctl::eviocrmff(self.file.as_raw_fd(), effect) } {
             if err != Errno::ENODEV {
@@ -121,5 +142,11 @@ impl Drop for Device {
     }
 }
 
+impl Drop for Device {
+    fn drop(&mut self) {
+        self.remove_effect();
+    }
+}
+
 const EV_FF: u16 = 0x15;
 const FF_RUMBLE: u16 = 0x50;
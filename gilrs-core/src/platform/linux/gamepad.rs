@@ -9,9 +9,10 @@ use super::ff::Device as FfDevice;
 use super::ioctl;
 use super::ioctl::{input_absinfo, input_event};
 use super::udev::*;
+use crate::guid::Guid;
 use crate::utils;
 use crate::{AxisInfo, Event, EventType};
-use crate::{PlatformError, PowerInfo};
+use crate::{ConnectionType, HeadsetState, HidError, InaccessibleDevice, PlatformError, PowerInfo};
 
 use libc as c;
 use uuid::Uuid;
@@ -21,17 +22,19 @@ use inotify::{EventMask, Inotify, WatchMask};
 use nix::errno::Errno;
 use nix::sys::epoll::{Epoll, EpollCreateFlags, EpollEvent, EpollFlags, EpollTimeout};
 use nix::sys::eventfd::{EfdFlags, EventFd};
+use std::cmp::Reverse;
 use std::collections::VecDeque;
 use std::error;
 use std::ffi::OsStr;
 use std::ffi::{CStr, CString};
 use std::fmt::{Display, Formatter, Result as FmtResult};
-use std::fs::File;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
 use std::mem::{self, MaybeUninit};
 use std::ops::Index;
 use std::os::raw::c_char;
-use std::os::unix::ffi::OsStrExt;
-use std::os::unix::io::{BorrowedFd, RawFd};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::io::{AsRawFd, BorrowedFd, RawFd};
 use std::path::{Path, PathBuf};
 use std::str;
 use std::sync::mpsc;
@@ -46,6 +49,9 @@ pub struct Gilrs {
     epoll: Epoll,
     hotplug_rx: Receiver<HotplugEvent>,
     to_check: VecDeque<usize>,
+    // Gamepads waiting for `Connected` event after they replaced other node of the same device.
+    pending_gamepads: VecDeque<Gamepad>,
+    inaccessible_devices: Vec<InaccessibleDevice>,
     discovery_backend: DiscoveryBackend,
 }
 
@@ -101,13 +107,10 @@ impl Gilrs {
                 let devpath = CString::new(gamepad_path.to_str().unwrap()).unwrap();
                 if let Some(gamepad) = Gamepad::open(&devpath, &syspath, DiscoveryBackend::Inotify)
                 {
-                    let idx = gamepads.len();
-                    gamepad
-                        .register_fd(&epoll, idx as u64)
-                        .map_err(|e| errno_to_platform_error(e, "registering gamepad in epoll"))?;
                     gamepads.push(gamepad);
                 }
             }
+            register_gamepads(&mut gamepads, &epoll)?;
 
             std::thread::Builder::new()
                 .name("gilrs".to_owned())
@@ -135,6 +138,8 @@ impl Gilrs {
                 epoll,
                 hotplug_rx,
                 to_check: VecDeque::new(),
+                pending_gamepads: VecDeque::new(),
+                inaccessible_devices: Vec::new(),
                 discovery_backend: DiscoveryBackend::Inotify,
             });
         }
@@ -151,6 +156,7 @@ impl Gilrs {
             }
         };
 
+        let mut inaccessible_devices = Vec::new();
         unsafe { en.add_match_property(cstr_new(b"ID_INPUT_JOYSTICK\0"), cstr_new(b"1\0")) }
         unsafe { en.add_match_subsystem(cstr_new(b"input\0")) }
         en.scan_devices();
@@ -163,14 +169,13 @@ impl Gilrs {
                 };
                 let syspath = Path::new(OsStr::from_bytes(dev.syspath().to_bytes()));
                 if let Some(gamepad) = Gamepad::open(devpath, syspath, DiscoveryBackend::Udev) {
-                    let idx = gamepads.len();
-                    gamepad
-                        .register_fd(&epoll, idx as u64)
-                        .map_err(|e| errno_to_platform_error(e, "registering gamepad in epoll"))?;
                     gamepads.push(gamepad);
+                } else if let Some(device) = InaccessibleDevice::check(devpath, syspath) {
+                    inaccessible_devices.push(device);
                 }
             }
         }
+        register_gamepads(&mut gamepads, &epoll)?;
 
         let (hotplug_tx, hotplug_rx) = mpsc::channel();
         std::thread::Builder::new()
@@ -201,6 +206,8 @@ impl Gilrs {
             epoll,
             hotplug_rx,
             to_check: VecDeque::new(),
+            pending_gamepads: VecDeque::new(),
+            inaccessible_devices,
             discovery_backend: DiscoveryBackend::Udev,
         })
     }
@@ -214,6 +221,10 @@ impl Gilrs {
     }
 
     fn next_event_impl(&mut self, timeout: Option<Duration>) -> Option<Event> {
+        if let Some(gamepad) = self.pending_gamepads.pop_front() {
+            return Some(self.add_gamepad(gamepad));
+        }
+
         let mut check_hotplug = false;
 
         if self.to_check.is_empty() {
@@ -294,6 +305,16 @@ impl Gilrs {
         self.gamepads.len()
     }
 
+    pub(crate) fn poll_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    pub(crate) fn set_poll_interval(&mut self, _interval: Duration) {}
+
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        &self.inaccessible_devices
+    }
+
     fn handle_hotplug(&mut self) -> Option<Event> {
         while let Ok(event) = self.hotplug_rx.try_recv() {
             match event {
@@ -307,42 +328,56 @@ impl Gilrs {
                     {
                         continue;
                     }
-                    if let Some(gamepad) = Gamepad::open(&devpath, &syspath, self.discovery_backend)
+                    let gamepad = Gamepad::open(&devpath, &syspath, self.discovery_backend);
+                    let inaccessible = if gamepad.is_none()
+                        && matches!(self.discovery_backend, DiscoveryBackend::Udev)
                     {
-                        return if let Some(id) = self
-                            .gamepads
-                            .iter()
-                            .position(|gp| gp.uuid() == gamepad.uuid && !gp.is_connected)
-                        {
-                            if let Err(e) = gamepad.register_fd(&self.epoll, id as u64) {
-                                error!("Failed to add gamepad to epoll: {}", e);
-                            }
-                            self.gamepads[id] = gamepad;
-                            Some(Event::new(id, EventType::Connected))
-                        } else {
-                            if let Err(e) =
-                                gamepad.register_fd(&self.epoll, self.gamepads.len() as u64)
+                        InaccessibleDevice::check(&devpath, &syspath)
+                    } else {
+                        None
+                    };
+                    replace_inaccessible(
+                        &mut self.inaccessible_devices,
+                        &gamepad_path_str,
+                        inaccessible,
+                    );
+
+                    if let Some(gamepad) = gamepad {
+                        let same_device = self.gamepads.iter().position(|gp| {
+                            gp.is_connected
+                                && is_same_gamepad(&gp.node_info(), &gamepad.node_info())
+                        });
+
+                        match same_device {
+                            Some(id)
+                                if self.gamepads[id].node_info().priority
+                                    >= gamepad.node_info().priority =>
                             {
-                                error!("Failed to add gamepad to epoll: {}", e);
+                                debug!(
+                                    "Ignoring {}, {} is preferred node of the same device",
+                                    gamepad.devpath, self.gamepads[id].devpath
+                                );
                             }
-                            self.gamepads.push(gamepad);
-                            Some(Event::new(self.gamepads.len() - 1, EventType::Connected))
-                        };
+                            Some(id) => {
+                                debug!(
+                                    "Replacing {} with preferred node {} of the same device",
+                                    self.gamepads[id].devpath, gamepad.devpath
+                                );
+                                self.pending_gamepads.push_back(gamepad);
+                                return Some(self.remove_gamepad(id));
+                            }
+                            None => return Some(self.add_gamepad(gamepad)),
+                        }
                     }
                 }
                 HotplugEvent::Removed(devpath) => {
+                    replace_inaccessible(&mut self.inaccessible_devices, &devpath, None);
                     if let Some(id) = self
                         .gamepads
                         .iter()
                         .position(|gp| devpath == gp.devpath && gp.is_connected)
                     {
-                        let gamepad_fd = unsafe { BorrowedFd::borrow_raw(self.gamepads[id].fd) };
-                        if let Err(e) = self.epoll.delete(gamepad_fd) {
-                            error!("Failed to remove disconnected gamepad from epoll: {}", e);
-                        }
-
-                        self.gamepads[id].disconnect();
-                        return Some(Event::new(id, EventType::Disconnected));
+                        return Some(self.remove_gamepad(id));
                     } else {
                         debug!("Could not find disconnected gamepad {devpath:?}");
                     }
@@ -352,6 +387,233 @@ impl Gilrs {
 
         None
     }
+
+    /// Registers gamepad in epoll and returns `Connected` event. Gamepad reuses ID of
+    /// disconnected gamepad with the same UUID, if there is one.
+    fn add_gamepad(&mut self, gamepad: Gamepad) -> Event {
+        let disconnected: Vec<_> = self
+            .gamepads
+            .iter()
+            .map(|gp| (!gp.is_connected).then(|| gp.device_key()))
+            .collect();
+        if let Some(id) = reconnected_gamepad(&disconnected, &gamepad.device_key()) {
+            if let Err(e) = gamepad.register_fd(&self.epoll, id as u64) {
+                error!("Failed to add gamepad to epoll: {}", e);
+            }
+            self.gamepads[id] = gamepad;
+            Event::new(id, EventType::Connected)
+        } else {
+            if let Err(e) = gamepad.register_fd(&self.epoll, self.gamepads.len() as u64) {
+                error!("Failed to add gamepad to epoll: {}", e);
+            }
+            self.gamepads.push(gamepad);
+            Event::new(self.gamepads.len() - 1, EventType::Connected)
+        }
+    }
+
+    fn remove_gamepad(&mut self, id: usize) -> Event {
+        if let Err(e) = self.gamepads[id].unregister_fd(&self.epoll) {
+            error!("Failed to remove disconnected gamepad from epoll: {}", e);
+        }
+
+        self.gamepads[id].disconnect();
+        Event::new(id, EventType::Disconnected)
+    }
+}
+
+/// Drops gamepads that are other event nodes of the same physical device as a preferred one and
+/// registers the rest in epoll.
+///
+/// Preferred node is the one with most gamepad buttons, then most axes, then the one found first.
+/// Nodes that are gamepads on their own are always kept, because adapters with multiple ports
+/// create one such node per port under the same device. Legacy joydev (`js*`) nodes are never
+/// used.
+fn register_gamepads(gamepads: &mut Vec<Gamepad>, epoll: &Epoll) -> Result<(), PlatformError> {
+    let nodes: Vec<_> = gamepads.iter().map(Gamepad::node_info).collect();
+    let preferred: Vec<_> = (0..nodes.len())
+        .map(|i| preferred_node(&nodes, i))
+        .collect();
+
+    for (i, other) in preferred.iter().enumerate() {
+        if let Some(other) = *other {
+            debug!(
+                "Ignoring {}, {} is preferred node of the same device",
+                gamepads[i].devpath, gamepads[other].devpath
+            );
+        }
+    }
+
+    let mut i = 0;
+    gamepads.retain(|_| {
+        i += 1;
+        preferred[i - 1].is_none()
+    });
+
+    for (idx, gamepad) in gamepads.iter().enumerate() {
+        gamepad
+            .register_fd(epoll, idx as u64)
+            .map_err(|e| errno_to_platform_error(e, "registering gamepad in epoll"))?;
+    }
+
+    Ok(())
+}
+
+/// Event node described by properties used to merge nodes of the same gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NodeInfo<'a> {
+    // Physical device that created the node, see `Gamepad::physical_device`.
+    device: Option<&'a Path>,
+    // Number of gamepad buttons, then number of axes.
+    priority: (usize, usize),
+    // Node has gamepad buttons and stick axes, so it's a gamepad on its own.
+    is_gamepad: bool,
+}
+
+impl<'a> NodeInfo<'a> {
+    fn new(device: Option<&'a Path>, buttons: &[EvCode], axes: &[EvCode]) -> Self {
+        let gamepad_buttons = buttons
+            .iter()
+            .filter(|btn| {
+                (BTN_JOYSTICK..BTN_DIGI).contains(&btn.code)
+                    || (BTN_TRIGGER_HAPPY1..=BTN_TRIGGER_HAPPY40).contains(&btn.code)
+            })
+            .count();
+        let has_stick = axes
+            .iter()
+            .any(|axis| (ABS_X..=ABS_RZ).contains(&axis.code));
+
+        NodeInfo {
+            device,
+            priority: (gamepad_buttons, axes.len()),
+            is_gamepad: gamepad_buttons > 0 && has_stick,
+        }
+    }
+}
+
+/// Returns `true` if nodes belong to the same gamepad: both were created by the same physical
+/// device and at most one of them is a gamepad on its own (others are, for example, touchpad or
+/// motion sensors). Nodes without physical device are never merged.
+fn is_same_gamepad(a: &NodeInfo, b: &NodeInfo) -> bool {
+    a.device.is_some() && a.device == b.device && !(a.is_gamepad && b.is_gamepad)
+}
+
+/// Returns index of node that should be used instead of `nodes[i]`, if any. It is the node of the
+/// same gamepad with highest priority, or the first one found if there is more than one.
+fn preferred_node(nodes: &[NodeInfo], i: usize) -> Option<usize> {
+    let node = &nodes[i];
+
+    // Highest priority wins, on tie the node that was found first.
+    let best = nodes
+        .iter()
+        .enumerate()
+        .filter(|&(j, other)| j == i || is_same_gamepad(node, other))
+        .max_by_key(|&(j, other)| (other.priority, Reverse(j)))
+        .map(|(j, _)| j)?;
+
+    (best != i).then_some(best)
+}
+
+/// Properties used to recognize gamepad that was connected again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DeviceKey<'a> {
+    uuid: Uuid,
+    vendor_id: u16,
+    product_id: u16,
+    // Unique identifier of device (usually serial number or Bluetooth address), can be empty.
+    uniq: &'a str,
+}
+
+/// Returns index of disconnected gamepad (`None` entries are connected gamepads) that is the same
+/// device as `key`. Gamepad with the same UUID is preferred. UUID includes bus type, so gamepad
+/// that reconnected over different bus is matched by vendor, product and unique identifier, if
+/// driver reports one.
+fn reconnected_gamepad(disconnected: &[Option<DeviceKey>], key: &DeviceKey) -> Option<usize> {
+    disconnected
+        .iter()
+        .position(|old| old.is_some_and(|old| old.uuid == key.uuid))
+        .or_else(|| {
+            disconnected.iter().position(|old| {
+                old.is_some_and(|old| {
+                    !key.uniq.is_empty()
+                        && old.uniq == key.uniq
+                        && old.vendor_id == key.vendor_id
+                        && old.product_id == key.product_id
+                })
+            })
+        })
+}
+
+impl InaccessibleDevice {
+    /// Returns `Some` if device node exists, but current user doesn't have read and write access
+    /// to it.
+    fn check(path: &CStr, syspath: &Path) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        if unsafe { c::access(path.as_ptr(), c::R_OK | c::W_OK) } == 0 {
+            return None;
+        }
+
+        match Errno::last() {
+            Errno::EACCES | Errno::EPERM => (),
+            _ => return None,
+        }
+
+        let path = path.to_string_lossy().into_owned();
+        let name = std::fs::read_to_string(syspath.join("device/name"))
+            .ok()
+            .map(|name| name.trim().to_owned());
+        let group = std::fs::metadata(&path)
+            .ok()
+            .and_then(|meta| group_name(meta.gid()));
+
+        warn!(
+            "Permission denied when opening {} ({}), owned by group {}. Check udev rules or add \
+             user to the group.",
+            path,
+            name.as_deref().unwrap_or("unknown device"),
+            group.as_deref().unwrap_or("unknown")
+        );
+
+        Some(InaccessibleDevice { path, name, group })
+    }
+}
+
+/// Removes device with `path` from `devices` and adds `device` in its place, if any.
+fn replace_inaccessible(
+    devices: &mut Vec<InaccessibleDevice>,
+    path: &str,
+    device: Option<InaccessibleDevice>,
+) {
+    devices.retain(|device| device.path != path);
+    devices.extend(device);
+}
+
+fn group_name(gid: c::gid_t) -> Option<String> {
+    let mut group = MaybeUninit::<c::group>::uninit();
+    let mut result = std::ptr::null_mut();
+    let mut buf = vec![0 as c_char; 1024];
+
+    loop {
+        let err = unsafe {
+            c::getgrgid_r(
+                gid,
+                group.as_mut_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+
+        match err {
+            0 if result.is_null() => return None,
+            0 => break,
+            c::ERANGE if buf.len() < 65536 => buf.resize(buf.len() * 2, 0),
+            _ => return None,
+        }
+    }
+
+    let name = unsafe { CStr::from_ptr(group.assume_init_ref().gr_name) };
+    Some(name.to_string_lossy().into_owned())
 }
 
 enum HotplugEvent {
@@ -411,10 +673,7 @@ fn handle_inotify(
 }
 
 fn get_gamepad_path(name: &str) -> Option<(PathBuf, PathBuf)> {
-    let event_id = match name.strip_prefix("event") {
-        Some(event_id) => event_id,
-        None => return None,
-    };
+    let event_id = name.strip_prefix("event")?;
     if event_id.is_empty()
         || event_id
             .chars()
@@ -543,12 +802,24 @@ pub struct Gamepad {
     axes_info: AxesInfo,
     ff_supported: bool,
     devpath: String,
+    // Canonical sysfs path of device that created this input node (for example HID device).
+    physical_device: Option<PathBuf>,
+    // hidraw node of the same device, if it's HID device.
+    hidraw: Option<PathBuf>,
     name: String,
     uuid: Uuid,
+    // Unique identifier reported by driver, used to recognize gamepad that reconnected over
+    // different bus.
+    uniq: String,
     vendor_id: u16,
     product_id: u16,
+    bustype: u16,
     bt_capacity_fd: RawFd,
     bt_status_fd: RawFd,
+    // Other event node of the same device that reports audio jack switches (for example "Headset
+    // Jack" node of DualSense), or -1 if there is none or gamepad node reports them itself.
+    headset_fd: RawFd,
+    headset_state: HeadsetState,
     axes_values: VecMap<i32>,
     buttons_values: VecMap<bool>,
     events: Vec<input_event>,
@@ -596,18 +867,35 @@ impl Gamepad {
         let axesi = AxesInfo::new(fd);
         let ff_supported = Self::test_ff(fd);
         let (cap, status) = Self::battery_fd(syspath);
+        let physical_device = syspath.join("device/device").canonicalize().ok();
+        let mut headset_state = Self::get_headset_state(fd);
+        let mut headset_fd = -1;
+        if headset_state == HeadsetState::Unknown {
+            if let Some(ref device) = physical_device {
+                headset_fd = Self::open_headset_node(device, path);
+                if headset_fd >= 0 {
+                    headset_state = Self::get_headset_state(headset_fd);
+                }
+            }
+        }
 
         let mut gamepad = Gamepad {
             fd,
             axes_info: axesi,
             ff_supported,
             devpath: path.to_string_lossy().into_owned(),
+            physical_device,
+            hidraw: Self::hidraw_path(syspath),
             name,
             uuid: create_uuid(input_id),
+            uniq: Self::get_uniq(fd).unwrap_or_default(),
             vendor_id: input_id.vendor,
             product_id: input_id.product,
+            bustype: input_id.bustype,
             bt_capacity_fd: cap,
             bt_status_fd: status,
+            headset_fd,
+            headset_state,
             axes_values: VecMap::new(),
             buttons_values: VecMap::new(),
             events: Vec::new(),
@@ -646,7 +934,24 @@ impl Gamepad {
 
     fn register_fd(&self, epoll: &Epoll, data: u64) -> Result<(), Errno> {
         let fd = unsafe { BorrowedFd::borrow_raw(self.fd) };
-        epoll.add(fd, EpollEvent::new(EpollFlags::EPOLLIN, data))
+        epoll.add(fd, EpollEvent::new(EpollFlags::EPOLLIN, data))?;
+
+        if self.headset_fd >= 0 {
+            let fd = unsafe { BorrowedFd::borrow_raw(self.headset_fd) };
+            epoll.add(fd, EpollEvent::new(EpollFlags::EPOLLIN, data))?;
+        }
+
+        Ok(())
+    }
+
+    fn unregister_fd(&self, epoll: &Epoll) -> Result<(), Errno> {
+        if self.headset_fd >= 0 {
+            let fd = unsafe { BorrowedFd::borrow_raw(self.headset_fd) };
+            epoll.delete(fd)?;
+        }
+
+        let fd = unsafe { BorrowedFd::borrow_raw(self.fd) };
+        epoll.delete(fd)
     }
 
     fn collect_axes_and_buttons(&mut self) {
@@ -687,6 +992,21 @@ impl Gamepad {
         }
     }
 
+    fn get_uniq(fd: i32) -> Option<String> {
+        unsafe {
+            let mut uniqbuff: [MaybeUninit<u8>; 128] = MaybeUninit::uninit().assume_init();
+            if ioctl::eviocguniq(fd, &mut uniqbuff).is_err() {
+                None
+            } else {
+                Some(
+                    CStr::from_ptr(uniqbuff.as_ptr() as *const c_char)
+                        .to_string_lossy()
+                        .into_owned(),
+                )
+            }
+        }
+    }
+
     fn get_input_id(fd: i32) -> Option<ioctl::input_id> {
         unsafe {
             let mut iid = MaybeUninit::<ioctl::input_id>::uninit();
@@ -718,6 +1038,125 @@ impl Gamepad {
         }
     }
 
+    /// Reads state of audio jack switches. Returns `HeadsetState::Unknown` if device doesn't
+    /// report them.
+    fn get_headset_state(fd: i32) -> HeadsetState {
+        let mut sw_bits = [0u8; (SW_MAX / 8) as usize + 1];
+        unsafe {
+            if ioctl::eviocgbit(
+                fd,
+                u32::from(EV_SW),
+                sw_bits.len() as i32,
+                sw_bits.as_mut_ptr(),
+            ) < 0
+            {
+                return HeadsetState::Unknown;
+            }
+        }
+
+        if !HEADSET_SWITCHES
+            .iter()
+            .any(|&sw| utils::test_bit(sw, &sw_bits))
+        {
+            return HeadsetState::Unknown;
+        }
+
+        let mut sw_values = [0u8; (SW_MAX / 8) as usize + 1];
+        unsafe {
+            if ioctl::eviocgsw(fd, &mut sw_values).is_err() {
+                return HeadsetState::Unknown;
+            }
+        }
+
+        headset_state(&sw_bits, &sw_values)
+    }
+
+    /// Opens other event node of `physical_device` that reports audio jack switches. Some drivers
+    /// (for example hid-playstation) create separate node for them. Returns -1 if there is none.
+    fn open_headset_node(physical_device: &Path, gamepad_path: &CStr) -> RawFd {
+        for path in sibling_event_nodes(physical_device) {
+            let path = match CString::new(path.into_os_string().into_vec()) {
+                Ok(path) if path.as_c_str() != gamepad_path => path,
+                _ => continue,
+            };
+
+            let fd = unsafe { c::open(path.as_ptr(), c::O_RDONLY | c::O_NONBLOCK) };
+            if fd < 0 {
+                continue;
+            }
+
+            if Self::get_headset_state(fd) != HeadsetState::Unknown {
+                debug!("Using {:?} for audio jack switches", path);
+                return fd;
+            }
+
+            unsafe {
+                c::close(fd);
+            }
+        }
+
+        -1
+    }
+
+    /// Reads all events from headset node and returns `HeadsetChanged` if state of headset has
+    /// changed.
+    fn headset_event(&mut self) -> Option<(EventType, SystemTime)> {
+        if self.headset_fd < 0 {
+            return None;
+        }
+
+        let mut time = None;
+        loop {
+            let mut event_buf = [input_event::default(); 12];
+            let size = mem::size_of::<input_event>();
+            let n = unsafe {
+                c::read(
+                    self.headset_fd,
+                    event_buf.as_mut_ptr() as *mut c::c_void,
+                    size * event_buf.len(),
+                )
+            };
+            if n <= 0 {
+                break;
+            }
+
+            for event in &event_buf[..n as usize / size] {
+                // After SYN_DROPPED state has to be checked anyway.
+                if event.type_ == EV_SW || (event.type_ == EV_SYN && event.code == SYN_DROPPED) {
+                    let dur =
+                        Duration::new(event.time.tv_sec as u64, event.time.tv_usec as u32 * 1000);
+                    time = Some(UNIX_EPOCH + dur);
+                }
+            }
+        }
+
+        let state = Self::get_headset_state(self.headset_fd);
+        if state == self.headset_state {
+            return None;
+        }
+        self.headset_state = state;
+
+        Some((
+            EventType::HeadsetChanged(state),
+            time.unwrap_or_else(SystemTime::now),
+        ))
+    }
+
+    /// Used to select one event node if device creates multiple of them (for example DualShock 4
+    /// has separate nodes for gamepad, touchpad and motion sensors).
+    fn node_info(&self) -> NodeInfo<'_> {
+        NodeInfo::new(self.physical_device.as_deref(), &self.buttons, &self.axes)
+    }
+
+    fn device_key(&self) -> DeviceKey<'_> {
+        DeviceKey {
+            uuid: self.uuid,
+            vendor_id: self.vendor_id,
+            product_id: self.product_id,
+            uniq: &self.uniq,
+        }
+    }
+
     fn is_gamepad(&self) -> bool {
         // TODO: improve it (for example check for buttons in range)
         !self.buttons.is_empty() && self.axes.len() >= 2
@@ -784,14 +1223,33 @@ impl Gamepad {
         (-1, -1)
     }
 
+    fn hidraw_path(syspath: &Path) -> Option<PathBuf> {
+        // HID devices have "hidraw" directory next to "input" directory in device root.
+        let entry = std::fs::read_dir(syspath.join("device/device/hidraw"))
+            .ok()?
+            .next()?
+            .ok()?;
+
+        Some(Path::new("/dev").join(entry.file_name()))
+    }
+
+    fn open_hidraw(&self) -> Result<File, HidError> {
+        if !self.is_connected {
+            return Err(HidError::Disconnected);
+        }
+
+        let path = self.hidraw.as_ref().ok_or(HidError::NotSupported)?;
+        Ok(OpenOptions::new().read(true).write(true).open(path)?)
+    }
+
     fn event(&mut self) -> Option<(EventType, SystemTime)> {
         let mut skip = false;
         // Skip all unknown events and return Option on first know event or when there is no more
         // events to read. Returning None on unknown event breaks iterators.
         loop {
             let event = match self.next_event() {
-                Some(e) => e,
-                None => return None,
+                Some(event) => event,
+                None => return self.headset_event(),
             };
 
             if skip {
@@ -820,6 +1278,16 @@ impl Gamepad {
                     self.axes_values.insert(event.code as usize, event.value);
                     Some(EventType::AxisValueChanged(event.value, event.into()))
                 }
+                EV_SW if HEADSET_SWITCHES.contains(&event.code) => {
+                    // Headphone and microphone switches can change independently, so check both.
+                    let state = Self::get_headset_state(self.fd);
+                    if state != self.headset_state {
+                        self.headset_state = state;
+                        Some(EventType::HeadsetChanged(state))
+                    } else {
+                        None
+                    }
+                }
                 _ => {
                     trace!("Skipping event {:?}", event);
                     None
@@ -913,6 +1381,14 @@ impl Gamepad {
                 });
             }
         }
+
+        if self.headset_fd < 0 && Self::get_headset_state(self.fd) != self.headset_state {
+            self.events.push(input_event {
+                type_: EV_SW,
+                code: SW_HEADPHONE_INSERT,
+                ..Default::default()
+            });
+        }
     }
 
     fn disconnect(&mut self) {
@@ -920,8 +1396,12 @@ impl Gamepad {
             if self.fd >= 0 {
                 c::close(self.fd);
             }
+            if self.headset_fd >= 0 {
+                c::close(self.headset_fd);
+            }
         }
         self.fd = -2;
+        self.headset_fd = -1;
         self.devpath.clear();
         self.is_connected = false;
     }
@@ -988,6 +1468,14 @@ impl Gamepad {
         self.ff_supported
     }
 
+    pub fn motor_count(&self) -> usize {
+        if self.ff_supported {
+            2
+        } else {
+            0
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -1004,6 +1492,69 @@ impl Gamepad {
         Some(self.product_id)
     }
 
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn headset_state(&self) -> HeadsetState {
+        if self.is_connected {
+            self.headset_state
+        } else {
+            HeadsetState::Unknown
+        }
+    }
+
+    pub fn connection_type(&self) -> ConnectionType {
+        ConnectionType::from_bus(self.bustype)
+    }
+
+    pub fn device_path(&self) -> Option<&str> {
+        if self.is_connected {
+            Some(&self.devpath)
+        } else {
+            None
+        }
+    }
+
+    pub fn send_output_report(&self, data: &[u8]) -> Result<(), HidError> {
+        let n = self.open_hidraw()?.write(data)?;
+        if n != data.len() {
+            return Err(io::Error::from(io::ErrorKind::WriteZero).into());
+        }
+
+        Ok(())
+    }
+
+    pub fn send_feature_report(&self, data: &[u8]) -> Result<(), HidError> {
+        let file = self.open_hidraw()?;
+        let n = unsafe { ioctl::hidiocsfeature(file.as_raw_fd(), data.as_ptr(), data.len()) };
+        if n < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        Ok(())
+    }
+
+    pub fn get_feature_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        let file = self.open_hidraw()?;
+        let n = unsafe { ioctl::hidiocgfeature(file.as_raw_fd(), buf.as_mut_ptr(), buf.len()) };
+        if n < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        Ok(n as usize)
+    }
+
+    pub fn get_input_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        let file = self.open_hidraw()?;
+        let n = unsafe { ioctl::hidiocginput(file.as_raw_fd(), buf.as_mut_ptr(), buf.len()) };
+        if n < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        Ok(n as usize)
+    }
+
     pub fn ff_device(&self) -> Option<FfDevice> {
         if self.is_ff_supported() {
             FfDevice::new(&self.devpath).ok()
@@ -1020,6 +1571,36 @@ impl Gamepad {
         &self.axes
     }
 
+    pub fn query_value(&self, nec: EvCode) -> Option<i32> {
+        if !self.is_connected {
+            return None;
+        }
+
+        match nec.kind {
+            EV_KEY => {
+                let mut buf = [0u8; KEY_MAX as usize / 8 + 1];
+                unsafe { ioctl::eviocgkey(self.fd, &mut buf) }.ok()?;
+                Some(utils::test_bit(nec.code, &buf) as i32)
+            }
+            EV_ABS => {
+                let mut absinfo = input_absinfo::default();
+                let res = unsafe { ioctl::eviocgabs(self.fd, u32::from(nec.code), &mut absinfo) };
+                if res < 0 {
+                    None
+                } else {
+                    Some(absinfo.value)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn code_name(&self, nec: EvCode) -> Option<String> {
+        vendor_code_name(self.vendor_id, self.product_id, nec)
+            .map(str::to_owned)
+            .or_else(|| nec.name())
+    }
+
     pub(crate) fn axis_info(&self, nec: EvCode) -> Option<&AxisInfo> {
         if nec.kind != EV_ABS {
             None
@@ -1041,6 +1622,9 @@ impl Drop for Gamepad {
             if self.bt_status_fd >= 0 {
                 c::close(self.bt_status_fd);
             }
+            if self.headset_fd >= 0 {
+                c::close(self.headset_fd);
+            }
         }
     }
 }
@@ -1052,25 +1636,7 @@ impl PartialEq for Gamepad {
 }
 
 fn create_uuid(iid: ioctl::input_id) -> Uuid {
-    let bus = (u32::from(iid.bustype)).to_be();
-    let vendor = iid.vendor.to_be();
-    let product = iid.product.to_be();
-    let version = iid.version.to_be();
-    Uuid::from_fields(
-        bus,
-        vendor,
-        0,
-        &[
-            (product >> 8) as u8,
-            product as u8,
-            0,
-            0,
-            (version >> 8) as u8,
-            version as u8,
-            0,
-            0,
-        ],
-    )
+    Guid::from_ids(iid.bustype, iid.vendor, iid.product, iid.version).into()
 }
 
 unsafe fn cstr_new(bytes: &[u8]) -> &CStr {
@@ -1083,8 +1649,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct EvCode {
-    kind: u16,
-    code: u16,
+    pub(super) kind: u16,
+    pub(super) code: u16,
 }
 
 impl EvCode {
@@ -1095,6 +1661,139 @@ impl EvCode {
     pub fn into_u32(self) -> u32 {
         u32::from(self.kind) << 16 | u32::from(self.code)
     }
+
+    pub fn is_key(self) -> bool {
+        self.kind == EV_KEY && self.code < BTN_MISC
+    }
+
+    pub fn is_shift_key(self) -> bool {
+        self.kind == EV_KEY && (self.code == KEY_LEFTSHIFT || self.code == KEY_RIGHTSHIFT)
+    }
+
+    pub fn key_char(self, shift: bool) -> Option<char> {
+        // First key code of each row of US layout, followed by characters without and with shift.
+        const ROWS: [(u16, &str, &str); 5] = [
+            (2, "1234567890-=", "!@#$%^&*()_+"),
+            (16, "qwertyuiop[]", "QWERTYUIOP{}"),
+            (30, "asdfghjkl;'`", "ASDFGHJKL:\"~"),
+            (43, "\\zxcvbnm,./", "|ZXCVBNM<>?"),
+            (KEY_SPACE, " ", " "),
+        ];
+
+        if self.kind != EV_KEY {
+            return None;
+        }
+
+        ROWS.iter().find_map(|&(first, lower, upper)| {
+            let idx = self.code.checked_sub(first)?;
+            let row = if shift { upper } else { lower };
+            row.chars().nth(idx.into())
+        })
+    }
+
+    /// Generic name of evdev code, used when device isn't in `VENDOR_CODE_NAMES`.
+    fn name(self) -> Option<String> {
+        const JOYSTICK: [&str; 16] = [
+            "Trigger", "Thumb", "Thumb 2", "Top", "Top 2", "Pinkie", "Base", "Base 2", "Base 3",
+            "Base 4", "Base 5", "Base 6", "", "", "", "Dead",
+        ];
+        const GAMEPAD: [&str; 15] = [
+            "South",
+            "East",
+            "C",
+            "North",
+            "West",
+            "Z",
+            "Left shoulder",
+            "Right shoulder",
+            "Left trigger",
+            "Right trigger",
+            "Select",
+            "Start",
+            "Mode",
+            "Left stick",
+            "Right stick",
+        ];
+        const EXTRA: [&str; 8] = [
+            "D-pad Up",
+            "D-pad Down",
+            "D-pad Left",
+            "D-pad Right",
+            "Left grip",
+            "Right grip",
+            "Left grip 2",
+            "Right grip 2",
+        ];
+        const ABS: [&str; 11] = [
+            "X", "Y", "Z", "Rx", "Ry", "Rz", "Throttle", "Rudder", "Wheel", "Gas", "Brake",
+        ];
+
+        let code = usize::from(self.code);
+        let name = match (self.kind, self.code) {
+            (EV_KEY, BTN_JOYSTICK..=0x12f) => JOYSTICK[code - usize::from(BTN_JOYSTICK)],
+            (EV_KEY, BTN_SOUTH..=BTN_THUMBR) => GAMEPAD[code - usize::from(BTN_SOUTH)],
+            (EV_KEY, BTN_DPAD_UP..=BTN_GRIPR2) => EXTRA[code - usize::from(BTN_DPAD_UP)],
+            (EV_KEY, BTN_TRIGGER_HAPPY1..=BTN_TRIGGER_HAPPY40) => {
+                return Some(format!(
+                    "Extra button {}",
+                    self.code - BTN_TRIGGER_HAPPY1 + 1
+                ));
+            }
+            (EV_ABS, ABS_X..=0x0a) => ABS[code],
+            (EV_ABS, ABS_HAT0X..=0x17) => {
+                let hat = (self.code - ABS_HAT0X) / 2;
+                let axis = if self.code % 2 == 0 { 'X' } else { 'Y' };
+                return Some(format!("Hat {} {}", hat, axis));
+            }
+            _ => return None,
+        };
+
+        if name.is_empty() {
+            None
+        } else {
+            Some(name.to_owned())
+        }
+    }
+}
+
+type CodeNames = &'static [(u16, &'static str)];
+
+/// Names of elements of devices with known layouts, as (vendor ID, product IDs, names).
+const VENDOR_CODE_NAMES: &[(u16, &[u16], CodeNames)] = &[
+    // Xbox Elite Wireless Controller and Series 2, paddles as reported by xpad.
+    (
+        0x045e,
+        &[0x02e3, 0x0b00],
+        &[
+            (BTN_TRIGGER_HAPPY1 + 4, "Paddle P1"),
+            (BTN_TRIGGER_HAPPY1 + 5, "Paddle P2"),
+            (BTN_TRIGGER_HAPPY1 + 6, "Paddle P3"),
+            (BTN_TRIGGER_HAPPY1 + 7, "Paddle P4"),
+        ],
+    ),
+    // Steam Deck, back grips as reported by hid-steam.
+    (
+        0x28de,
+        &[0x1205],
+        &[
+            (BTN_GRIPL, "L4"),
+            (BTN_GRIPR, "R4"),
+            (BTN_GRIPL2, "L5"),
+            (BTN_GRIPR2, "R5"),
+        ],
+    ),
+];
+
+fn vendor_code_name(vendor: u16, product: u16, nec: EvCode) -> Option<&'static str> {
+    if nec.kind != EV_KEY {
+        return None;
+    }
+
+    VENDOR_CODE_NAMES
+        .iter()
+        .find(|&&(v, products, _)| v == vendor && products.contains(&product))
+        .and_then(|&(_, _, names)| names.iter().find(|&&(code, _)| code == nec.code))
+        .map(|&(_, name)| name)
 }
 
 impl From<input_event> for crate::EvCode {
@@ -1142,6 +1841,43 @@ impl Display for Error {
 
 impl error::Error for Error {}
 
+/// Returns paths of all event nodes created by `physical_device`, sorted by path.
+fn sibling_event_nodes(physical_device: &Path) -> Vec<PathBuf> {
+    // Each input device of physical device has its own directory in "input" directory, with event
+    // node directory inside, for example "input/input23/event20".
+    let mut nodes: Vec<_> = std::fs::read_dir(physical_device.join("input"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|input| std::fs::read_dir(input.path()).ok())
+        .flatten()
+        .flatten()
+        .map(|entry| entry.file_name())
+        .filter(|name| name.as_bytes().starts_with(b"event"))
+        .map(|name| Path::new(INPUT_DIR_PATH).join(name))
+        .collect();
+    nodes.sort();
+
+    nodes
+}
+
+/// Returns state of headset from bitmasks of switches that device supports and of switches that
+/// are on.
+fn headset_state(supported: &[u8], values: &[u8]) -> HeadsetState {
+    let mut switches = HEADSET_SWITCHES
+        .iter()
+        .filter(|&&sw| utils::test_bit(sw, supported))
+        .peekable();
+
+    if switches.peek().is_none() {
+        HeadsetState::Unknown
+    } else if switches.any(|&sw| utils::test_bit(sw, values)) {
+        HeadsetState::Connected
+    } else {
+        HeadsetState::Disconnected
+    }
+}
+
 fn errno_to_platform_error(errno: Errno, ctx: &'static str) -> PlatformError {
     PlatformError::Other(Box::new(Error::Errno(errno, ctx)))
 }
@@ -1149,21 +1885,33 @@ fn errno_to_platform_error(errno: Errno, ctx: &'static str) -> PlatformError {
 const KEY_MAX: u16 = 0x2ff;
 #[allow(dead_code)]
 const EV_MAX: u16 = 0x1f;
-const EV_SYN: u16 = 0x00;
-const EV_KEY: u16 = 0x01;
+pub(super) const EV_SYN: u16 = 0x00;
+pub(super) const EV_KEY: u16 = 0x01;
 const EV_REL: u16 = 0x02;
-const EV_ABS: u16 = 0x03;
+pub(super) const EV_ABS: u16 = 0x03;
 const EV_MSC: u16 = 0x04;
 const EV_SW: u16 = 0x05;
 const ABS_MAX: u16 = 0x3f;
 const EV_FF: u16 = 0x15;
 
-const SYN_REPORT: u16 = 0x00;
+pub(super) const SYN_REPORT: u16 = 0x00;
 const SYN_DROPPED: u16 = 0x03;
 
+const SW_MAX: u16 = 0x10;
+const SW_HEADPHONE_INSERT: u16 = 0x02;
+const SW_MICROPHONE_INSERT: u16 = 0x04;
+const SW_LINEOUT_INSERT: u16 = 0x06;
+const HEADSET_SWITCHES: [u16; 3] = [SW_HEADPHONE_INSERT, SW_MICROPHONE_INSERT, SW_LINEOUT_INSERT];
+
+const KEY_LEFTSHIFT: u16 = 42;
+const KEY_RIGHTSHIFT: u16 = 54;
+const KEY_SPACE: u16 = 57;
 const BTN_MISC: u16 = 0x100;
 const BTN_MOUSE: u16 = 0x110;
 const BTN_JOYSTICK: u16 = 0x120;
+const BTN_DIGI: u16 = 0x140;
+const BTN_TRIGGER_HAPPY1: u16 = 0x2c0;
+const BTN_TRIGGER_HAPPY40: u16 = 0x2e7;
 const BTN_SOUTH: u16 = 0x130;
 const BTN_EAST: u16 = 0x131;
 #[allow(dead_code)]
@@ -1186,6 +1934,10 @@ const BTN_DPAD_UP: u16 = 0x220;
 const BTN_DPAD_DOWN: u16 = 0x221;
 const BTN_DPAD_LEFT: u16 = 0x222;
 const BTN_DPAD_RIGHT: u16 = 0x223;
+const BTN_GRIPL: u16 = 0x224;
+const BTN_GRIPR: u16 = 0x225;
+const BTN_GRIPL2: u16 = 0x226;
+const BTN_GRIPR2: u16 = 0x227;
 
 const ABS_X: u16 = 0x00;
 const ABS_Y: u16 = 0x01;
@@ -1339,9 +2091,171 @@ pub mod native_ev_codes {
 #[cfg(test)]
 mod tests {
     use super::super::ioctl;
-    use super::create_uuid;
+    use super::{
+        create_uuid, group_name, headset_state, is_same_gamepad, preferred_node,
+        reconnected_gamepad, replace_inaccessible, sibling_event_nodes, vendor_code_name,
+        DeviceKey, EvCode, Gamepad, NodeInfo, ABS_X, ABS_Y, BTN_EAST, BTN_GRIPL, BTN_SOUTH,
+        BTN_TRIGGER_HAPPY1, EV_ABS, EV_KEY, KEY_LEFTSHIFT, KEY_SPACE, SW_HEADPHONE_INSERT, SW_MAX,
+        SW_MICROPHONE_INSERT,
+    };
+    use crate::{HeadsetState, InaccessibleDevice};
+    use std::ffi::CString;
+    use std::fs;
+    use std::path::Path;
     use uuid::Uuid;
 
+    #[test]
+    fn inaccessible_devices() {
+        let device = |path: &str, group: &str| InaccessibleDevice {
+            path: path.to_owned(),
+            name: None,
+            group: Some(group.to_owned()),
+        };
+        let mut devices = Vec::new();
+
+        replace_inaccessible(
+            &mut devices,
+            "/dev/input/event3",
+            Some(device("/dev/input/event3", "input")),
+        );
+        replace_inaccessible(
+            &mut devices,
+            "/dev/input/event4",
+            Some(device("/dev/input/event4", "input")),
+        );
+        // Device that appears again is reported only once, with current group.
+        replace_inaccessible(
+            &mut devices,
+            "/dev/input/event3",
+            Some(device("/dev/input/event3", "plugdev")),
+        );
+        assert_eq!(
+            devices,
+            [
+                device("/dev/input/event4", "input"),
+                device("/dev/input/event3", "plugdev")
+            ]
+        );
+        // Device that was removed or opened successfully is forgotten.
+        replace_inaccessible(&mut devices, "/dev/input/event4", None);
+        assert_eq!(devices, [device("/dev/input/event3", "plugdev")]);
+
+        // Missing nodes are not reported as inaccessible.
+        let missing = CString::new("/dev/input/gilrs-missing").unwrap();
+        assert_eq!(
+            InaccessibleDevice::check(&missing, Path::new("/sys/nonexistent")),
+            None
+        );
+        assert_eq!(group_name(0).as_deref(), Some("root"));
+    }
+
+    #[test]
+    fn hidraw_path() {
+        let root = std::env::temp_dir().join(format!("gilrs-hidraw-{}", std::process::id()));
+        let hid = root.join("usb/input5");
+        fs::create_dir_all(hid.join("device/device/hidraw/hidraw3")).unwrap();
+        let evdev = root.join("platform/input6");
+        fs::create_dir_all(evdev.join("device/device")).unwrap();
+
+        let hidraw = Gamepad::hidraw_path(&hid);
+        let no_hidraw = Gamepad::hidraw_path(&evdev);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(hidraw.as_deref(), Some(Path::new("/dev/hidraw3")));
+        assert_eq!(no_hidraw, None);
+    }
+
+    #[test]
+    fn sibling_nodes() {
+        let root = std::env::temp_dir().join(format!("gilrs-siblings-{}", std::process::id()));
+        let device = root.join("0005:054C:0CE6.0001");
+        fs::create_dir_all(device.join("input/input23/event21")).unwrap();
+        fs::create_dir_all(device.join("input/input22/event20")).unwrap();
+        fs::create_dir_all(device.join("input/input22/mouse3")).unwrap();
+        fs::create_dir_all(device.join("input/input24/js0")).unwrap();
+        fs::create_dir_all(root.join("no-input")).unwrap();
+
+        let nodes = sibling_event_nodes(&device);
+        let no_nodes = sibling_event_nodes(&root.join("no-input"));
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            nodes,
+            [
+                Path::new("/dev/input/event20"),
+                Path::new("/dev/input/event21")
+            ]
+        );
+        assert!(no_nodes.is_empty());
+    }
+
+    #[test]
+    fn headset_switches() {
+        let bits = |switches: &[u16]| {
+            let mut bits = [0u8; (SW_MAX / 8) as usize + 1];
+            for &sw in switches {
+                bits[(sw / 8) as usize] |= 1 << (sw % 8);
+            }
+            bits
+        };
+        let jack = bits(&[SW_HEADPHONE_INSERT, SW_MICROPHONE_INSERT]);
+
+        assert_eq!(
+            headset_state(&bits(&[]), &bits(&[SW_HEADPHONE_INSERT])),
+            HeadsetState::Unknown
+        );
+        assert_eq!(headset_state(&jack, &bits(&[])), HeadsetState::Disconnected);
+        assert_eq!(
+            headset_state(&jack, &bits(&[SW_MICROPHONE_INSERT])),
+            HeadsetState::Connected
+        );
+        // Other switches, like lid or tablet mode, are ignored.
+        assert_eq!(
+            headset_state(&jack, &bits(&[0x00, 0x01])),
+            HeadsetState::Disconnected
+        );
+    }
+
+    #[test]
+    fn code_names() {
+        let key = |code| EvCode::new(EV_KEY, code);
+        assert_eq!(key(BTN_SOUTH).name().as_deref(), Some("South"));
+        assert_eq!(key(0x12f).name().as_deref(), Some("Dead"));
+        assert_eq!(key(0x12c).name(), None);
+        assert_eq!(
+            key(BTN_TRIGGER_HAPPY1 + 16).name().as_deref(),
+            Some("Extra button 17")
+        );
+        assert_eq!(EvCode::new(EV_ABS, 0x13).name().as_deref(), Some("Hat 1 Y"));
+        assert_eq!(key(30).name(), None);
+
+        let paddle = key(BTN_TRIGGER_HAPPY1 + 6);
+        assert_eq!(vendor_code_name(0x045e, 0x0b00, paddle), Some("Paddle P3"));
+        assert_eq!(vendor_code_name(0x045e, 0x028e, paddle), None);
+        assert_eq!(vendor_code_name(0x28de, 0x1205, key(BTN_GRIPL)), Some("L4"));
+    }
+
+    #[test]
+    fn keyboard_keys() {
+        let key = |code| EvCode::new(EV_KEY, code);
+        // KEY_A
+        assert_eq!(key(30).key_char(false), Some('a'));
+        assert_eq!(key(30).key_char(true), Some('A'));
+        // KEY_BACKSLASH and KEY_APOSTROPHE
+        assert_eq!(key(43).key_char(false), Some('\\'));
+        assert_eq!(key(40).key_char(true), Some('"'));
+        assert_eq!(key(KEY_SPACE).key_char(true), Some(' '));
+        // KEY_ENTER
+        assert_eq!(key(28).key_char(false), None);
+        assert_eq!(key(KEY_LEFTSHIFT).key_char(false), None);
+        assert!(key(KEY_LEFTSHIFT).is_shift_key());
+
+        assert!(key(30).is_key());
+        assert!(!key(BTN_SOUTH).is_key());
+        assert!(!EvCode::new(EV_ABS, 30).is_key());
+        assert_eq!(EvCode::new(EV_ABS, 30).key_char(false), None);
+    }
+
     #[test]
     fn sdl_uuid() {
         let x = Uuid::parse_str("030000005e0400008e02000020200000").unwrap();
@@ -1353,4 +2267,115 @@ mod tests {
         });
         assert_eq!(x, y);
     }
+
+    #[test]
+    fn node_infos() {
+        let key = |code| EvCode::new(EV_KEY, code);
+        let abs = |code| EvCode::new(EV_ABS, code);
+
+        let gamepad = NodeInfo::new(
+            None,
+            &[key(BTN_SOUTH), key(BTN_EAST), key(BTN_TRIGGER_HAPPY1)],
+            &[abs(ABS_X), abs(ABS_Y)],
+        );
+        let keyboard = NodeInfo::new(None, &[key(KEY_SPACE), key(KEY_LEFTSHIFT)], &[]);
+        let motion = NodeInfo::new(None, &[], &[abs(ABS_X), abs(ABS_Y), abs(0x02)]);
+        // Only d-pad on hat axes.
+        let dpad = NodeInfo::new(None, &[key(BTN_SOUTH)], &[abs(0x10), abs(0x11)]);
+
+        assert_eq!(gamepad.priority, (3, 2));
+        assert_eq!(keyboard.priority, (0, 0));
+        assert_eq!(motion.priority, (0, 3));
+        assert!(gamepad.priority > motion.priority);
+        assert!(gamepad.is_gamepad);
+        assert!(!keyboard.is_gamepad);
+        assert!(!motion.is_gamepad);
+        assert!(!dpad.is_gamepad);
+    }
+
+    #[test]
+    fn preferred_nodes() {
+        let node = |device, priority, is_gamepad| NodeInfo {
+            device,
+            priority,
+            is_gamepad,
+        };
+        let ds4 = Some(Path::new("/sys/devices/ds4"));
+        let adapter = Some(Path::new("/sys/devices/adapter"));
+        let other = Some(Path::new("/sys/devices/other"));
+        let nodes = [
+            // DualShock 4 motion sensors, touchpad and gamepad.
+            node(ds4, (0, 6), false),
+            node(ds4, (0, 2), false),
+            node(ds4, (13, 8), true),
+            // Adapter with two ports, each one is a gamepad.
+            node(adapter, (12, 6), true),
+            node(adapter, (12, 6), true),
+            // Two nodes with equal priority that are not gamepads, first one is used.
+            node(other, (1, 2), false),
+            node(other, (1, 2), false),
+            // Unknown physical device is never merged.
+            node(None, (0, 2), false),
+            node(None, (11, 6), true),
+        ];
+
+        let preferred: Vec<_> = (0..nodes.len())
+            .map(|i| preferred_node(&nodes, i))
+            .collect();
+        assert_eq!(
+            preferred,
+            [
+                Some(2),
+                Some(2),
+                None,
+                None,
+                None,
+                None,
+                Some(5),
+                None,
+                None
+            ]
+        );
+
+        assert!(is_same_gamepad(&nodes[1], &nodes[2]));
+        assert!(!is_same_gamepad(&nodes[3], &nodes[4]));
+        assert!(!is_same_gamepad(&nodes[7], &nodes[7]));
+    }
+
+    #[test]
+    fn reconnected_gamepads() {
+        let key = |bustype, uniq| DeviceKey {
+            uuid: create_uuid(ioctl::input_id {
+                bustype,
+                vendor: 0x054c,
+                product: 0x0ce6,
+                version: 0x8111,
+            }),
+            vendor_id: 0x054c,
+            product_id: 0x0ce6,
+            uniq,
+        };
+        let usb = key(0x03, "a0:5a:5c:00:00:01");
+        let bluetooth = key(0x05, "a0:5a:5c:00:00:01");
+        let other = key(0x05, "a0:5a:5c:00:00:02");
+
+        // Same gamepad over different bus.
+        assert_eq!(reconnected_gamepad(&[None, Some(usb)], &bluetooth), Some(1));
+        // Gamepad with the same UUID is preferred.
+        assert_eq!(
+            reconnected_gamepad(&[Some(usb), Some(bluetooth)], &bluetooth),
+            Some(1)
+        );
+        // Connected gamepads and other devices are not matched.
+        assert_eq!(reconnected_gamepad(&[None, Some(other)], &usb), None);
+        // Without unique identifier only UUID can be used.
+        assert_eq!(
+            reconnected_gamepad(&[Some(key(0x03, ""))], &key(0x05, "")),
+            None
+        );
+        assert_eq!(
+            reconnected_gamepad(&[Some(key(0x03, ""))], &key(0x03, "")),
+            Some(0)
+        );
+    }
 }
@@ -10,7 +10,10 @@
 // difference, so it looks like conditional compilation is not needed.
 #![allow(dead_code)]
 
-use nix::{ioctl_read, ioctl_read_buf, ioctl_write_int, ioctl_write_ptr, request_code_read};
+use nix::{
+    ioctl_none, ioctl_read, ioctl_read_buf, ioctl_write_int, ioctl_write_ptr, request_code_read,
+    request_code_readwrite,
+};
 use std::mem::MaybeUninit;
 
 #[cfg(target_env = "musl")]
@@ -22,7 +25,17 @@ ioctl_read!(eviocgid, b'E', 0x02, /*struct*/ input_id);
 ioctl_write_int!(eviocrmff, b'E', 0x81);
 ioctl_write_ptr!(eviocsff, b'E', 0x80, ff_effect);
 ioctl_read_buf!(eviocgname, b'E', 0x06, MaybeUninit<u8>);
+ioctl_read_buf!(eviocguniq, b'E', 0x08, MaybeUninit<u8>);
 ioctl_read_buf!(eviocgkey, b'E', 0x18, u8);
+ioctl_read_buf!(eviocgsw, b'E', 0x1b, u8);
+
+ioctl_none!(ui_dev_create, b'U', 1);
+ioctl_none!(ui_dev_destroy, b'U', 2);
+ioctl_write_ptr!(ui_dev_setup, b'U', 3, uinput_setup);
+ioctl_write_ptr!(ui_abs_setup, b'U', 4, uinput_abs_setup);
+ioctl_write_int!(ui_set_evbit, b'U', 100);
+ioctl_write_int!(ui_set_keybit, b'U', 101);
+ioctl_write_int!(ui_set_absbit, b'U', 103);
 
 pub unsafe fn eviocgbit(fd: libc::c_int, ev: u32, len: libc::c_int, buf: *mut u8) -> libc::c_int {
     ::nix::libc::ioctl(
@@ -41,6 +54,30 @@ pub unsafe fn eviocgabs(fd: ::libc::c_int, abs: u32, buf: *mut input_absinfo) ->
     )
 }
 
+pub unsafe fn hidiocsfeature(fd: libc::c_int, buf: *const u8, len: usize) -> libc::c_int {
+    ::nix::libc::ioctl(
+        fd,
+        request_code_readwrite!(b'H', 0x06, len) as IoctlRequest,
+        buf,
+    )
+}
+
+pub unsafe fn hidiocgfeature(fd: libc::c_int, buf: *mut u8, len: usize) -> libc::c_int {
+    ::nix::libc::ioctl(
+        fd,
+        request_code_readwrite!(b'H', 0x07, len) as IoctlRequest,
+        buf,
+    )
+}
+
+pub unsafe fn hidiocginput(fd: libc::c_int, buf: *mut u8, len: usize) -> libc::c_int {
+    ::nix::libc::ioctl(
+        fd,
+        request_code_readwrite!(b'H', 0x0A, len) as IoctlRequest,
+        buf,
+    )
+}
+
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub struct input_event {
@@ -86,6 +123,21 @@ pub struct input_absinfo {
     pub resolution: i32,
 }
 
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct uinput_setup {
+    pub id: input_id,
+    pub name: [libc::c_char; 80],
+    pub ff_effects_max: u32,
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct uinput_abs_setup {
+    pub code: u16,
+    pub absinfo: input_absinfo,
+}
+
 #[derive(Copy, Clone, Default)]
 #[repr(C)]
 pub struct ff_replay {
@@ -9,8 +9,12 @@ mod ff;
 mod gamepad;
 mod ioctl;
 mod udev;
+#[cfg(feature = "uinput")]
+mod uinput;
 
 pub use self::ff::Device as FfDevice;
 pub use self::gamepad::{native_ev_codes, EvCode, Gamepad, Gilrs};
+#[cfg(feature = "uinput")]
+pub use self::uinput::VirtualDevice;
 
 pub const IS_Y_AXIS_REVERSED: bool = true;
@@ -0,0 +1,135 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::{mem, slice};
+
+use super::gamepad::{EvCode, EV_ABS, EV_KEY, EV_SYN, SYN_REPORT};
+use super::ioctl::{self, input_absinfo, input_event, input_id, uinput_abs_setup, uinput_setup};
+use crate::AxisInfo;
+
+const BUS_VIRTUAL: u16 = 0x06;
+
+#[derive(Debug)]
+pub struct VirtualDevice {
+    file: File,
+}
+
+impl VirtualDevice {
+    pub fn new(
+        name: &str,
+        vendor: u16,
+        product: u16,
+        buttons: &[EvCode],
+        axes: &[(EvCode, AxisInfo)],
+    ) -> IoResult<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")?;
+        let fd = file.as_raw_fd();
+
+        let mut setup = uinput_setup {
+            id: input_id {
+                bustype: BUS_VIRTUAL,
+                vendor,
+                product,
+                version: 1,
+            },
+            name: [0; 80],
+            ff_effects_max: 0,
+        };
+        // Keep at least one NUL byte at the end.
+        for (dst, src) in setup.name.iter_mut().zip(name.bytes().take(79)) {
+            *dst = src as libc::c_char;
+        }
+
+        unsafe {
+            ioctl::ui_set_evbit(fd, EV_SYN.into())?;
+            if !buttons.is_empty() {
+                ioctl::ui_set_evbit(fd, EV_KEY.into())?;
+            }
+            if !axes.is_empty() {
+                ioctl::ui_set_evbit(fd, EV_ABS.into())?;
+            }
+
+            for btn in buttons {
+                check_kind(*btn, EV_KEY)?;
+                ioctl::ui_set_keybit(fd, btn.code.into())?;
+            }
+
+            for (axis, info) in axes {
+                check_kind(*axis, EV_ABS)?;
+                ioctl::ui_set_absbit(fd, axis.code.into())?;
+
+                let abs_setup = uinput_abs_setup {
+                    code: axis.code,
+                    absinfo: input_absinfo {
+                        minimum: info.min,
+                        maximum: info.max,
+                        flat: info.deadzone.map_or(0, |d| d as i32),
+                        ..Default::default()
+                    },
+                };
+                ioctl::ui_abs_setup(fd, &abs_setup)?;
+            }
+
+            ioctl::ui_dev_setup(fd, &setup)?;
+            ioctl::ui_dev_create(fd)?;
+        }
+
+        Ok(VirtualDevice { file })
+    }
+
+    pub fn emit(&mut self, code: EvCode, value: i32) -> IoResult<()> {
+        self.write(code.kind, code.code, value)
+    }
+
+    pub fn sync(&mut self) -> IoResult<()> {
+        self.write(EV_SYN, SYN_REPORT, 0)
+    }
+
+    fn write(&mut self, type_: u16, code: u16, value: i32) -> IoResult<()> {
+        // Kernel sets the time of events written to uinput.
+        let ev = input_event {
+            time: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            type_,
+            code,
+            value,
+        };
+
+        let size = mem::size_of::<input_event>();
+        let s = unsafe { slice::from_raw_parts(&ev as *const _ as *const u8, size) };
+
+        self.file.write_all(s)
+    }
+}
+
+impl Drop for VirtualDevice {
+    fn drop(&mut self) {
+        if let Err(e) = unsafe { ioctl::ui_dev_destroy(self.file.as_raw_fd()) } {
+            error!("Failed to destroy virtual device: {}", e);
+        }
+    }
+}
+
+fn check_kind(code: EvCode, kind: u16) -> IoResult<()> {
+    if code.kind == kind {
+        Ok(())
+    } else {
+        Err(IoError::new(
+            ErrorKind::InvalidInput,
+            format!("{} can not be used as this type of element", code),
+        ))
+    }
+}
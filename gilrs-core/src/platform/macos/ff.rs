@@ -14,4 +14,12 @@ pub struct Device;
 impl Device {
     /// Sets magnitude for strong and weak ff motors.
     pub fn set_ff_state(&mut self, _strong: u16, _weak: u16, _min_duration: Duration) {}
+
+    /// Returns number of ff motors.
+    pub fn motor_count(&self) -> usize {
+        0
+    }
+
+    /// Sets magnitude for every ff motor.
+    pub fn set_motors(&mut self, _magnitudes: &[u16], _min_duration: Duration) {}
 }
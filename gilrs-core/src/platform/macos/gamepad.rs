@@ -7,16 +7,24 @@
 
 use super::io_kit::*;
 use super::FfDevice;
-use crate::{AxisInfo, Event, EventType, PlatformError, PowerInfo};
+use crate::guid::{Guid, BUS_USB};
+use crate::{
+    AxisInfo, ConnectionType, Event, EventType, HeadsetState, HidError, InaccessibleDevice,
+    PlatformError, PowerInfo,
+};
 use uuid::Uuid;
 
+use core_foundation::base::CFIndex;
 use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop};
 use io_kit_sys::hid::base::{IOHIDDeviceRef, IOHIDValueRef};
+use io_kit_sys::hid::keys::{
+    kIOHIDReportTypeFeature, kIOHIDReportTypeInput, kIOHIDReportTypeOutput, IOHIDReportType,
+};
 use io_kit_sys::hid::usage_tables::{
     kHIDPage_GenericDesktop, kHIDUsage_GD_GamePad, kHIDUsage_GD_Joystick,
     kHIDUsage_GD_MultiAxisController,
 };
-use io_kit_sys::ret::IOReturn;
+use io_kit_sys::ret::{kIOReturnSuccess, IOReturn};
 use vec_map::VecMap;
 
 use std::fmt::{Display, Formatter, Result as FmtResult};
@@ -102,6 +110,7 @@ impl Gilrs {
                 if event.event == EventType::Connected {
                     if self.gamepads.get(event.id).is_some() {
                         self.gamepads[event.id].is_connected = true;
+                        self.gamepads[event.id].device = device.clone();
                     } else {
                         match Gamepad::open(device) {
                             Some(gamepad) => {
@@ -149,6 +158,27 @@ impl Gilrs {
     pub fn last_gamepad_hint(&self) -> usize {
         self.gamepads.len()
     }
+
+    pub(crate) fn poll_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    pub(crate) fn set_poll_interval(&mut self, _interval: Duration) {}
+
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        &[]
+    }
+}
+
+fn empty_report_error() -> HidError {
+    HidError::Io(std::io::ErrorKind::InvalidInput.into())
+}
+
+fn io_return_error(ret: IOReturn) -> HidError {
+    HidError::Io(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        format!("IOKit error {:#x}", ret),
+    ))
 }
 
 #[derive(Debug)]
@@ -157,6 +187,7 @@ pub struct Gamepad {
     name: String,
     vendor: Option<u16>,
     product: Option<u16>,
+    bustype: Option<u16>,
     uuid: Uuid,
     entry_id: u64,
     location_id: u32,
@@ -167,6 +198,7 @@ pub struct Gamepad {
     hats: Vec<EvCode>,
     buttons: Vec<EvCode>,
     is_connected: bool,
+    device: IOHIDDevice,
 }
 
 impl Gamepad {
@@ -242,6 +274,7 @@ impl Gamepad {
             name,
             vendor: device.get_vendor_id(),
             product: device.get_product_id(),
+            bustype: device.get_bustype(),
             uuid,
             entry_id,
             location_id,
@@ -252,6 +285,8 @@ impl Gamepad {
             hats: Vec::with_capacity(4),
             buttons: Vec::with_capacity(16),
             is_connected: true,
+            // Callbacks don't own `device`, so keep our own reference.
+            device: device.clone(),
         };
         gamepad.collect_axes_and_buttons(&device.get_elements());
 
@@ -259,51 +294,26 @@ impl Gamepad {
     }
 
     fn create_uuid(device: &IOHIDDevice) -> Option<Uuid> {
-        // SDL always uses USB bus for UUID
-        let bustype = u32::to_be(0x03);
-
-        let vendor_id = match device.get_vendor_id() {
-            Some(vendor_id) => vendor_id.to_be(),
-            None => {
-                warn!("Failed to get vendor id of device");
-                0
-            }
-        };
+        let vendor_id = device.get_vendor_id().unwrap_or_else(|| {
+            warn!("Failed to get vendor id of device");
+            0
+        });
 
-        let product_id = match device.get_product_id() {
-            Some(product_id) => product_id.to_be(),
-            None => {
-                warn!("Failed to get product id of device");
-                0
-            }
-        };
+        let product_id = device.get_product_id().unwrap_or_else(|| {
+            warn!("Failed to get product id of device");
+            0
+        });
 
-        let version = match device.get_version() {
-            Some(version) => version.to_be(),
-            None => {
-                warn!("Failed to get version of device");
-                0
-            }
-        };
+        let version = device.get_version().unwrap_or_else(|| {
+            warn!("Failed to get version of device");
+            0
+        });
 
         if vendor_id == 0 && product_id == 0 && version == 0 {
             None
         } else {
-            Some(Uuid::from_fields(
-                bustype,
-                vendor_id,
-                0,
-                &[
-                    (product_id >> 8) as u8,
-                    product_id as u8,
-                    0,
-                    0,
-                    (version >> 8) as u8,
-                    version as u8,
-                    0,
-                    0,
-                ],
-            ))
+            // SDL always uses USB bus for UUID
+            Some(Guid::from_ids(BUS_USB, vendor_id, product_id, version).into())
         }
     }
 
@@ -319,6 +329,74 @@ impl Gamepad {
         self.product
     }
 
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn headset_state(&self) -> HeadsetState {
+        HeadsetState::Unknown
+    }
+
+    pub fn connection_type(&self) -> ConnectionType {
+        self.bustype
+            .map_or(ConnectionType::Unknown, ConnectionType::from_bus)
+    }
+
+    pub fn device_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn send_output_report(&self, data: &[u8]) -> Result<(), HidError> {
+        self.set_report(kIOHIDReportTypeOutput, data)
+    }
+
+    pub fn send_feature_report(&self, data: &[u8]) -> Result<(), HidError> {
+        self.set_report(kIOHIDReportTypeFeature, data)
+    }
+
+    pub fn get_feature_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        self.get_report(kIOHIDReportTypeFeature, buf)
+    }
+
+    pub fn get_input_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        self.get_report(kIOHIDReportTypeInput, buf)
+    }
+
+    fn set_report(&self, report_type: IOHIDReportType, data: &[u8]) -> Result<(), HidError> {
+        if !self.is_connected {
+            return Err(HidError::Disconnected);
+        }
+
+        let report_id = data.first().copied().ok_or_else(empty_report_error)?;
+        // Report ID is not part of report for devices that don't use numbered reports.
+        let report = if report_id == 0 { &data[1..] } else { data };
+        match self
+            .device
+            .set_report(report_type, report_id as CFIndex, report)
+        {
+            ret if ret == kIOReturnSuccess => Ok(()),
+            ret => Err(io_return_error(ret)),
+        }
+    }
+
+    fn get_report(&self, report_type: IOHIDReportType, buf: &mut [u8]) -> Result<usize, HidError> {
+        if !self.is_connected {
+            return Err(HidError::Disconnected);
+        }
+
+        let report_id = buf.first().copied().ok_or_else(empty_report_error)?;
+        if report_id == 0 {
+            self.device
+                .get_report(report_type, 0, &mut buf[1..])
+                .map(|len| len + 1)
+                .map_err(io_return_error)
+        } else {
+            self.device
+                .get_report(report_type, report_id as CFIndex, buf)
+                .map_err(io_return_error)
+        }
+    }
+
     pub fn uuid(&self) -> Uuid {
         self.uuid
     }
@@ -331,6 +409,10 @@ impl Gamepad {
         false
     }
 
+    pub fn motor_count(&self) -> usize {
+        0
+    }
+
     /// Creates Ffdevice corresponding to this gamepad.
     pub fn ff_device(&self) -> Option<FfDevice> {
         Some(FfDevice)
@@ -348,6 +430,14 @@ impl Gamepad {
         self.axes_info.get(nec.usage as usize)
     }
 
+    pub fn query_value(&self, _nec: EvCode) -> Option<i32> {
+        None
+    }
+
+    pub fn code_name(&self, nec: EvCode) -> Option<String> {
+        nec.usage_name()
+    }
+
     pub fn is_connected(&self) -> bool {
         self.is_connected
     }
@@ -454,6 +544,36 @@ impl EvCode {
     pub fn into_u32(self) -> u32 {
         self.page << 16 | self.usage
     }
+
+    /// Name of usage from HID Usage Tables.
+    fn usage_name(self) -> Option<String> {
+        const PAGE_SIMULATION: u32 = 0x02;
+
+        let name = match (self.page, self.usage) {
+            (PAGE_BUTTON, usage) => return Some(format!("Button {}", usage)),
+            (PAGE_GENERIC_DESKTOP, 0x30) => "X",
+            (PAGE_GENERIC_DESKTOP, 0x31) => "Y",
+            (PAGE_GENERIC_DESKTOP, 0x32) => "Z",
+            (PAGE_GENERIC_DESKTOP, 0x33) => "Rx",
+            (PAGE_GENERIC_DESKTOP, 0x34) => "Ry",
+            (PAGE_GENERIC_DESKTOP, 0x35) => "Rz",
+            (PAGE_GENERIC_DESKTOP, 0x36) => "Slider",
+            (PAGE_GENERIC_DESKTOP, 0x37) => "Dial",
+            (PAGE_GENERIC_DESKTOP, 0x38) => "Wheel",
+            // Hat switch is reported as two axes, see `USAGE_AXIS_DPADY`.
+            (PAGE_GENERIC_DESKTOP, 0x39) => "Hat switch X",
+            (PAGE_GENERIC_DESKTOP, 0x3a) => "Hat switch Y",
+            (PAGE_GENERIC_DESKTOP, 0x90) => "D-pad Up",
+            (PAGE_GENERIC_DESKTOP, 0x91) => "D-pad Down",
+            (PAGE_GENERIC_DESKTOP, 0x92) => "D-pad Right",
+            (PAGE_GENERIC_DESKTOP, 0x93) => "D-pad Left",
+            (PAGE_SIMULATION, 0xc4) => "Accelerator",
+            (PAGE_SIMULATION, 0xc5) => "Brake",
+            _ => return None,
+        };
+
+        Some(name.to_owned())
+    }
 }
 
 impl From<IOHIDElement> for crate::EvCode {
@@ -305,6 +305,48 @@ impl IOHIDDevice {
 
         vec
     }
+
+    pub fn set_report(
+        &self,
+        report_type: IOHIDReportType,
+        report_id: CFIndex,
+        report: &[u8],
+    ) -> IOReturn {
+        unsafe {
+            IOHIDDeviceSetReport(
+                self.0,
+                report_type,
+                report_id,
+                report.as_ptr(),
+                report.len() as CFIndex,
+            )
+        }
+    }
+
+    /// Returns number of bytes written to `report`.
+    pub fn get_report(
+        &self,
+        report_type: IOHIDReportType,
+        report_id: CFIndex,
+        report: &mut [u8],
+    ) -> Result<usize, IOReturn> {
+        let mut len = report.len() as CFIndex;
+        let ret = unsafe {
+            IOHIDDeviceGetReport(
+                self.0,
+                report_type,
+                report_id,
+                report.as_mut_ptr(),
+                &mut len,
+            )
+        };
+
+        if ret == kIOReturnSuccess {
+            Ok(len as usize)
+        } else {
+            Err(ret)
+        }
+    }
 }
 
 impl Properties for IOHIDDevice {
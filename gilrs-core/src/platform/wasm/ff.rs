@@ -12,4 +12,10 @@ pub struct Device;
 
 impl Device {
     pub fn set_ff_state(&mut self, _strong: u16, _weak: u16, _min_duration: Duration) {}
+
+    pub fn motor_count(&self) -> usize {
+        0
+    }
+
+    pub fn set_motors(&mut self, _magnitudes: &[u16], _min_duration: Duration) {}
 }
@@ -16,7 +16,10 @@ use web_sys::{DomException, Gamepad as WebGamepad, GamepadButton, GamepadMapping
 
 use super::FfDevice;
 use crate::platform::native_ev_codes::{BTN_LT2, BTN_RT2};
-use crate::{AxisInfo, Event, EventType, PlatformError, PowerInfo};
+use crate::{
+    AxisInfo, ConnectionType, Event, EventType, HeadsetState, HidError, InaccessibleDevice,
+    PlatformError, PowerInfo,
+};
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
 
@@ -26,6 +29,10 @@ pub struct Gilrs {
     gamepads: Vec<Gamepad>,
     new_web_gamepads: Vec<WebGamepad>,
     next_event_error_logged: bool,
+    // Minimal time between calls to `getGamepads()`. Zero means every `next_event()` call.
+    poll_interval: Duration,
+    // Value of `Date.now()` at last call to `getGamepads()`, in milliseconds.
+    last_poll: f64,
 }
 
 impl Gilrs {
@@ -42,6 +49,8 @@ impl Gilrs {
                 gamepads: Vec::new(),
                 new_web_gamepads: Vec::new(),
                 next_event_error_logged: false,
+                poll_interval: Duration::ZERO,
+                last_poll: f64::NEG_INFINITY,
             }
         })
     }
@@ -53,6 +62,13 @@ impl Gilrs {
             return self.event_cache.pop_front();
         }
 
+        // `Instant` is not available on wasm32-unknown-unknown.
+        let now = js_sys::Date::now();
+        if now - self.last_poll < self.poll_interval.as_secs_f64() * 1000.0 {
+            return None;
+        }
+        self.last_poll = now;
+
         let gamepads = match web_sys::window()
             .expect("no window")
             .navigator()
@@ -190,6 +206,18 @@ impl Gilrs {
     pub fn last_gamepad_hint(&self) -> usize {
         self.gamepads.len()
     }
+
+    pub(crate) fn poll_interval(&self) -> Option<Duration> {
+        Some(self.poll_interval)
+    }
+
+    pub(crate) fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        &[]
+    }
 }
 
 #[derive(Debug)]
@@ -343,6 +371,38 @@ impl Gamepad {
         self.product
     }
 
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn headset_state(&self) -> HeadsetState {
+        HeadsetState::Unknown
+    }
+
+    pub fn connection_type(&self) -> ConnectionType {
+        ConnectionType::Unknown
+    }
+
+    pub fn device_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn send_output_report(&self, _data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn send_feature_report(&self, _data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_feature_report(&self, _buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_input_report(&self, _buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
     pub fn is_connected(&self) -> bool {
         self.gamepad.connected()
     }
@@ -355,6 +415,10 @@ impl Gamepad {
         false
     }
 
+    pub fn motor_count(&self) -> usize {
+        0
+    }
+
     pub fn ff_device(&self) -> Option<FfDevice> {
         None
     }
@@ -381,6 +445,14 @@ impl Gamepad {
             .unwrap_or_else(|| EvCode((index + self.mapping.buttons().len()) as u8 + 31))
     }
 
+    pub fn query_value(&self, _nec: EvCode) -> Option<i32> {
+        None
+    }
+
+    pub fn code_name(&self, _nec: EvCode) -> Option<String> {
+        None
+    }
+
     pub(crate) fn axis_info(&self, _nec: EvCode) -> Option<&AxisInfo> {
         if self.buttons().contains(&_nec) {
             return Some(&AxisInfo {
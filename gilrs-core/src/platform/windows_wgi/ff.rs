@@ -19,13 +19,28 @@ impl Device {
         Device { id, wgi_gamepad }
     }
 
-    pub fn set_ff_state(&mut self, strong: u16, weak: u16, _min_duration: Duration) {
+    pub fn set_ff_state(&mut self, strong: u16, weak: u16, min_duration: Duration) {
+        self.set_motors(&[strong, weak], min_duration);
+    }
+
+    /// Left motor, right motor, left trigger and right trigger.
+    pub fn motor_count(&self) -> usize {
+        if self.wgi_gamepad.is_some() {
+            4
+        } else {
+            0
+        }
+    }
+
+    pub fn set_motors(&mut self, magnitudes: &[u16], _min_duration: Duration) {
+        let motor = |i: usize| magnitudes.get(i).copied().unwrap_or(0) as f64 / u16::MAX as f64;
+
         if let Some(wgi_gamepad) = &self.wgi_gamepad {
             if let Err(err) = wgi_gamepad.SetVibration(GamepadVibration {
-                LeftMotor: (strong as f64) / (u16::MAX as f64),
-                RightMotor: (weak as f64) / (u16::MAX as f64),
-                LeftTrigger: 0.0,
-                RightTrigger: 0.0,
+                LeftMotor: motor(0),
+                RightMotor: motor(1),
+                LeftTrigger: motor(2),
+                RightTrigger: motor(3),
             }) {
                 error!(
                     "Failed to change FF state – unknown error. ID = {}, error = {:?}.",
@@ -6,13 +6,19 @@
 // copied, modified, or distributed except according to those terms.
 
 use super::FfDevice;
+use crate::guid::{Guid, BUS_USB};
 use crate::native_ev_codes as nec;
-use crate::{utils, AxisInfo, Event, EventType, PlatformError, PowerInfo};
+use crate::{
+    utils, AxisInfo, ConnectionType, Event, EventType, HeadsetState, HidError, InaccessibleDevice,
+    PlatformError, PowerInfo,
+};
 
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
 use std::thread::JoinHandle;
 use std::time::{Duration, SystemTime};
 use std::{thread, u32};
@@ -26,13 +32,12 @@ use windows::Gaming::Input::{
 };
 use windows::System::Power::BatteryStatus;
 
-const SDL_HARDWARE_BUS_USB: u32 = 0x03;
-// const SDL_HARDWARE_BUS_BLUETOOTH: u32 = 0x05;
+// const SDL_HARDWARE_BUS_BLUETOOTH: u16 = 0x05;
 
 // The general consensus is that standard xbox controllers poll at ~125 hz which
 // means 8 ms between updates.
 // Seems like a good target for how often we update the background thread.
-const EVENT_THREAD_SLEEP_TIME: u64 = 8;
+const EVENT_THREAD_SLEEP_TIME: Duration = Duration::from_millis(8);
 
 const WGI_TO_GILRS_BUTTON_MAP: [(GamepadButtons, crate::EvCode); 14] = [
     (GamepadButtons::DPadUp, nec::BTN_DPAD_UP),
@@ -77,6 +82,8 @@ pub struct Gilrs {
     rx: Receiver<WgiEvent>,
     join_handle: Option<JoinHandle<()>>,
     stop_tx: Sender<()>,
+    // Sleep time of event thread, in microseconds.
+    poll_interval: Arc<AtomicU64>,
 }
 
 impl Gilrs {
@@ -100,16 +107,22 @@ impl Gilrs {
 
         let (tx, rx) = mpsc::channel();
         let (stop_tx, stop_rx) = mpsc::channel();
-        let join_handle = Some(Self::spawn_thread(tx, stop_rx));
+        let poll_interval = Arc::new(AtomicU64::new(EVENT_THREAD_SLEEP_TIME.as_micros() as u64));
+        let join_handle = Some(Self::spawn_thread(tx, stop_rx, poll_interval.clone()));
         Ok(Gilrs {
             gamepads,
             rx,
             join_handle,
             stop_tx,
+            poll_interval,
         })
     }
 
-    fn spawn_thread(tx: Sender<WgiEvent>, stop_rx: Receiver<()>) -> JoinHandle<()> {
+    fn spawn_thread(
+        tx: Sender<WgiEvent>,
+        stop_rx: Receiver<()>,
+        poll_interval: Arc<AtomicU64>,
+    ) -> JoinHandle<()> {
         let added_tx = tx.clone();
         let added_handler: EventHandler<RawGameController> =
             EventHandler::new(move |_, g: &Option<RawGameController>| {
@@ -143,6 +156,8 @@ impl Gilrs {
                 // To avoid allocating every update, store old and new readings for every controller
                 // and swap their memory
                 let mut readings: Vec<(HSTRING, Reading, Reading)> = Vec::new();
+                // Last known headset state of every controller
+                let mut headsets: Vec<(HSTRING, bool)> = Vec::new();
                 loop {
                     match stop_rx.try_recv() {
                         Ok(_) => break,
@@ -167,6 +182,25 @@ impl Gilrs {
 
                     for controller in controllers.iter() {
                         let id: HSTRING = controller.NonRoamableId().unwrap();
+
+                        let has_headset = controller.Headset().is_ok();
+                        match headsets.iter_mut().find(|(other_id, _)| id == *other_id) {
+                            Some((_, old_has_headset)) if *old_has_headset != has_headset => {
+                                *old_has_headset = has_headset;
+                                let state = match has_headset {
+                                    true => HeadsetState::Connected,
+                                    false => HeadsetState::Disconnected,
+                                };
+                                tx.send(WgiEvent::new(
+                                    controller.clone(),
+                                    EventType::HeadsetChanged(state),
+                                ))
+                                .expect("should be able to send to main thread");
+                            }
+                            Some(_) => (),
+                            None => headsets.push((id.clone(), has_headset)),
+                        }
+
                         // Find readings for this controller or insert new ones.
                         let index = match readings.iter().position(|(other_id, ..)| id == *other_id)
                         {
@@ -206,7 +240,8 @@ impl Gilrs {
                             &tx,
                         );
                     }
-                    thread::sleep(Duration::from_millis(EVENT_THREAD_SLEEP_TIME));
+                    let interval = poll_interval.load(Ordering::Relaxed);
+                    thread::sleep(Duration::from_micros(interval));
                 }
 
                 if let Err(e) =
@@ -283,6 +318,21 @@ impl Gilrs {
     pub fn last_gamepad_hint(&self) -> usize {
         self.gamepads.len()
     }
+
+    pub(crate) fn poll_interval(&self) -> Option<Duration> {
+        Some(Duration::from_micros(
+            self.poll_interval.load(Ordering::Relaxed),
+        ))
+    }
+
+    pub(crate) fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval
+            .store(interval.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        &[]
+    }
 }
 
 impl Drop for Gilrs {
@@ -542,8 +592,8 @@ impl Gamepad {
         let uuid = match wgi_gamepad.is_some() {
             true => Uuid::nil(),
             false => {
-                let vendor_id = raw_game_controller.HardwareVendorId().unwrap_or(0).to_be();
-                let product_id = raw_game_controller.HardwareProductId().unwrap_or(0).to_be();
+                let vendor_id = raw_game_controller.HardwareVendorId().unwrap_or(0);
+                let product_id = raw_game_controller.HardwareProductId().unwrap_or(0);
                 let version = 0;
 
                 // SDL uses the SDL_HARDWARE_BUS_BLUETOOTH bustype for IsWireless devices:
@@ -551,23 +601,7 @@ impl Gamepad {
                 // In my testing though, it caused my controllers to not find mappings.
                 // SDL only uses their WGI implementation for UWP apps so I guess it hasn't been
                 // used enough for people to submit mappings with the different bustype.
-                let bustype = SDL_HARDWARE_BUS_USB.to_be();
-
-                Uuid::from_fields(
-                    bustype,
-                    vendor_id,
-                    0,
-                    &[
-                        (product_id >> 8) as u8,
-                        product_id as u8,
-                        0,
-                        0,
-                        (version >> 8) as u8,
-                        version as u8,
-                        0,
-                        0,
-                    ],
-                )
+                Guid::from_ids(BUS_USB, vendor_id, product_id, version).into()
             }
         };
 
@@ -606,10 +640,54 @@ impl Gamepad {
         self.raw_game_controller.HardwareProductId().ok()
     }
 
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        None
+    }
+
     pub fn is_connected(&self) -> bool {
         self.is_connected
     }
 
+    pub fn device_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn send_output_report(&self, _data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn send_feature_report(&self, _data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_feature_report(&self, _buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_input_report(&self, _buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn headset_state(&self) -> HeadsetState {
+        if !self.is_connected {
+            return HeadsetState::Unknown;
+        }
+
+        match self.raw_game_controller.Headset() {
+            Ok(_) => HeadsetState::Connected,
+            Err(_) => HeadsetState::Disconnected,
+        }
+    }
+
+    pub fn connection_type(&self) -> ConnectionType {
+        // WGI doesn't tell which wireless protocol is used, and wired gamepads are USB devices.
+        match self.raw_game_controller.IsWireless() {
+            Ok(true) => ConnectionType::Wireless,
+            Ok(false) => ConnectionType::Usb,
+            Err(_) => ConnectionType::Unknown,
+        }
+    }
+
     pub fn power_info(&self) -> PowerInfo {
         self.power_info_err().unwrap_or(PowerInfo::Unknown)
     }
@@ -651,6 +729,14 @@ impl Gamepad {
                 .is_some()
     }
 
+    pub fn motor_count(&self) -> usize {
+        if self.is_ff_supported() {
+            4
+        } else {
+            0
+        }
+    }
+
     pub fn ff_device(&self) -> Option<FfDevice> {
         Some(FfDevice::new(self.id, self.wgi_gamepad.clone()))
     }
@@ -669,6 +755,52 @@ impl Gamepad {
         }
     }
 
+    pub fn query_value(&self, nec: EvCode) -> Option<i32> {
+        if !self.is_connected {
+            return None;
+        }
+
+        // Values are converted the same way as in `Reading::send_events_for_differences()`.
+        let wgi_gamepad = match &self.wgi_gamepad {
+            Some(wgi_gamepad) => wgi_gamepad,
+            None => {
+                let reading = RawGamepadReading::new(&self.raw_game_controller).ok()?;
+                let index = nec.index as usize;
+                return match nec.kind {
+                    EvCodeKind::Axis => {
+                        let value = *reading.axes.get(index)?;
+                        Some(((value * 65535.0) - 32768.0) as i32)
+                    }
+                    EvCodeKind::Button => reading.buttons.get(index).map(|&pressed| pressed as i32),
+                    EvCodeKind::Switch => {
+                        let (x, y) = direction_from_switch(*reading.switches.get(index / 2)?);
+                        Some(if index % 2 == 0 { x } else { -y })
+                    }
+                };
+            }
+        };
+
+        let reading = wgi_gamepad.GetCurrentReading().ok()?;
+        let axis =
+            |value: f64, multiplier: f64| Some((multiplier * value * i32::MAX as f64) as i32);
+        match nec {
+            native_ev_codes::AXIS_LT2 => axis(reading.LeftTrigger, 1.0),
+            native_ev_codes::AXIS_RT2 => axis(reading.RightTrigger, 1.0),
+            native_ev_codes::AXIS_LSTICKX => axis(reading.LeftThumbstickX, 1.0),
+            native_ev_codes::AXIS_LSTICKY => axis(reading.LeftThumbstickY, -1.0),
+            native_ev_codes::AXIS_RSTICKX => axis(reading.RightThumbstickX, 1.0),
+            native_ev_codes::AXIS_RSTICKY => axis(reading.RightThumbstickY, -1.0),
+            _ => WGI_TO_GILRS_BUTTON_MAP
+                .iter()
+                .find(|(_, code)| code.0 == nec)
+                .map(|&(button, _)| (reading.Buttons & button != GamepadButtons::None) as i32),
+        }
+    }
+
+    pub fn code_name(&self, _nec: EvCode) -> Option<String> {
+        None
+    }
+
     pub(crate) fn axis_info(&self, nec: EvCode) -> Option<&AxisInfo> {
         // If it isn't a Windows "Gamepad" then return what we want SDL mappings to be able to use
         if self.wgi_gamepad.is_none() {
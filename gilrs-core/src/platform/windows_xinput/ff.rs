@@ -37,4 +37,14 @@ impl Device {
             }
         }
     }
+
+    pub fn motor_count(&self) -> usize {
+        2
+    }
+
+    pub fn set_motors(&mut self, magnitudes: &[u16], min_duration: Duration) {
+        let strong = magnitudes.first().copied().unwrap_or(0);
+        let weak = magnitudes.get(1).copied().unwrap_or(0);
+        self.set_ff_state(strong, weak, min_duration);
+    }
 }
@@ -6,11 +6,15 @@
 // copied, modified, or distributed except according to those terms.
 
 use super::FfDevice;
-use crate::{AxisInfo, Event, EventType, PlatformError, PowerInfo};
+use crate::{
+    AxisInfo, ConnectionType, Event, EventType, HeadsetState, HidError, InaccessibleDevice,
+    PlatformError, PowerInfo,
+};
 
 use std::error::Error as StdError;
 use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::sync::{
+    atomic::{AtomicU64, Ordering},
     mpsc::{self, Receiver, Sender},
     Arc,
 };
@@ -30,7 +34,7 @@ use winapi::um::xinput::{
 };
 
 // Chosen by dice roll ;)
-const EVENT_THREAD_SLEEP_TIME: u64 = 10;
+const EVENT_THREAD_SLEEP_TIME: Duration = Duration::from_millis(10);
 const ITERATIONS_TO_CHECK_IF_CONNECTED: u64 = 100;
 
 const MAX_XINPUT_CONTROLLERS: usize = 4;
@@ -39,6 +43,8 @@ const MAX_XINPUT_CONTROLLERS: usize = 4;
 pub struct Gilrs {
     gamepads: [Gamepad; MAX_XINPUT_CONTROLLERS],
     rx: Receiver<Event>,
+    // Sleep time of event thread, in microseconds.
+    poll_interval: Arc<AtomicU64>,
 }
 
 impl Gilrs {
@@ -64,10 +70,15 @@ impl Gilrs {
         }
 
         let (tx, rx) = mpsc::channel();
-        Self::spawn_thread(tx, connected, xinput_handle.clone());
+        let poll_interval = Arc::new(AtomicU64::new(EVENT_THREAD_SLEEP_TIME.as_micros() as u64));
+        Self::spawn_thread(tx, connected, xinput_handle.clone(), poll_interval.clone());
 
         // Coerce gamepads vector to slice
-        Ok(Gilrs { gamepads, rx })
+        Ok(Gilrs {
+            gamepads,
+            rx,
+            poll_interval,
+        })
     }
 
     pub(crate) fn next_event(&mut self) -> Option<Event> {
@@ -107,10 +118,26 @@ impl Gilrs {
         self.gamepads.len()
     }
 
+    pub(crate) fn poll_interval(&self) -> Option<Duration> {
+        Some(Duration::from_micros(
+            self.poll_interval.load(Ordering::Relaxed),
+        ))
+    }
+
+    pub(crate) fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval
+            .store(interval.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        &[]
+    }
+
     fn spawn_thread(
         tx: Sender<Event>,
         connected: [bool; MAX_XINPUT_CONTROLLERS],
         xinput_handle: Arc<XInputHandle>,
+        poll_interval: Arc<AtomicU64>,
     ) {
         std::thread::Builder::new()
             .name("gilrs".to_owned())
@@ -154,7 +181,8 @@ impl Gilrs {
                     }
 
                     counter = counter.wrapping_add(1);
-                    thread::sleep(Duration::from_millis(EVENT_THREAD_SLEEP_TIME));
+                    let interval = poll_interval.load(Ordering::Relaxed);
+                    thread::sleep(Duration::from_micros(interval));
                 }
             })
             .expect("failed to spawn thread");
@@ -430,6 +458,38 @@ impl Gamepad {
         None
     }
 
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        self.is_connected.then_some(self.id)
+    }
+
+    pub fn headset_state(&self) -> HeadsetState {
+        HeadsetState::Unknown
+    }
+
+    pub fn connection_type(&self) -> ConnectionType {
+        ConnectionType::Unknown
+    }
+
+    pub fn device_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn send_output_report(&self, _data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn send_feature_report(&self, _data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_feature_report(&self, _buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_input_report(&self, _buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
     pub fn is_connected(&self) -> bool {
         self.is_connected
     }
@@ -470,6 +530,10 @@ impl Gamepad {
         true
     }
 
+    pub fn motor_count(&self) -> usize {
+        2
+    }
+
     pub fn ff_device(&self) -> Option<FfDevice> {
         Some(FfDevice::new(self.id, self.xinput_handle.clone()))
     }
@@ -487,6 +551,45 @@ impl Gamepad {
             .get(nec.0 as usize)
             .and_then(|o| o.as_ref())
     }
+
+    pub fn query_value(&self, nec: EvCode) -> Option<i32> {
+        use self::native_ev_codes::*;
+
+        if !self.is_connected {
+            return None;
+        }
+
+        let XInputState { raw: state } = self.xinput_handle.get_state(self.id).ok()?;
+        let g = state.Gamepad;
+        let button = |mask: u16| Some((g.wButtons & mask != 0) as i32);
+        match nec {
+            AXIS_LT2 => Some(g.bLeftTrigger as i32),
+            AXIS_RT2 => Some(g.bRightTrigger as i32),
+            AXIS_LSTICKX => Some(g.sThumbLX as i32),
+            AXIS_LSTICKY => Some(g.sThumbLY as i32),
+            AXIS_RSTICKX => Some(g.sThumbRX as i32),
+            AXIS_RSTICKY => Some(g.sThumbRY as i32),
+            BTN_SOUTH => button(XINPUT_GAMEPAD_A),
+            BTN_EAST => button(XINPUT_GAMEPAD_B),
+            BTN_WEST => button(XINPUT_GAMEPAD_X),
+            BTN_NORTH => button(XINPUT_GAMEPAD_Y),
+            BTN_LT => button(XINPUT_GAMEPAD_LEFT_SHOULDER),
+            BTN_RT => button(XINPUT_GAMEPAD_RIGHT_SHOULDER),
+            BTN_SELECT => button(XINPUT_GAMEPAD_BACK),
+            BTN_START => button(XINPUT_GAMEPAD_START),
+            BTN_LTHUMB => button(XINPUT_GAMEPAD_LEFT_THUMB),
+            BTN_RTHUMB => button(XINPUT_GAMEPAD_RIGHT_THUMB),
+            BTN_DPAD_UP => button(XINPUT_GAMEPAD_DPAD_UP),
+            BTN_DPAD_DOWN => button(XINPUT_GAMEPAD_DPAD_DOWN),
+            BTN_DPAD_LEFT => button(XINPUT_GAMEPAD_DPAD_LEFT),
+            BTN_DPAD_RIGHT => button(XINPUT_GAMEPAD_DPAD_RIGHT),
+            _ => None,
+        }
+    }
+
+    pub fn code_name(&self, _nec: EvCode) -> Option<String> {
+        None
+    }
 }
 
 #[inline(always)]
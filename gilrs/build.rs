@@ -18,6 +18,8 @@ const PATH_SEPARATOR: &str = "slash";
 
 fn main() {
     println!("cargo:rustc-check-cfg=cfg(path_separator, values(\"slash\",\"backslash\"))");
+    // Set by `cargo fuzz`, see `fuzz/`.
+    println!("cargo:rustc-check-cfg=cfg(fuzzing)");
     println!(r#"cargo:rustc-cfg=path_separator="{}""#, PATH_SEPARATOR);
 
     let out_dir = env::var("OUT_DIR").unwrap();
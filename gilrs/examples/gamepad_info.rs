@@ -8,9 +8,13 @@ fn main() {
             r#"Gamepad {id} ({name}):
     Map name: {map_name:?}
     Os name: {os_name}
+    Product name: {product_name}
     UUID: {uuid}
     Is connected: {is_connected}
+    Device path: {device_path:?}
     Power info: {power_info:?}
+    XInput user index: {xinput_user_index:?}
+    Headset state: {headset_state:?}
     Mapping source: {mapping_source:?}
     Is ff supported: {ff}
     Deadzone Left X: {dlx:?}
@@ -26,9 +30,13 @@ fn main() {
             name = gamepad.name(),
             map_name = gamepad.map_name(),
             os_name = gamepad.os_name(),
+            product_name = gamepad.product_name(),
             uuid = Uuid::from_bytes(gamepad.uuid()).as_hyphenated(),
             is_connected = gamepad.is_connected(),
+            device_path = gamepad.device_path(),
             power_info = gamepad.power_info(),
+            xinput_user_index = gamepad.xinput_user_index(),
+            headset_state = gamepad.headset_state(),
             mapping_source = gamepad.mapping_source(),
             ff = gamepad.is_ff_supported(),
             dlx = gamepad
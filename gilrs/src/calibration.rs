@@ -0,0 +1,550 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Calibration of gamepad's axes.
+//!
+//! [`Wizard`] guides user through calibration of selected axes: first all axes are moved through
+//! their full range ("move sticks in full circles"), then released, so resting position and its
+//! noise can be measured. Frontend only has to render [`Wizard::instructions()`] and call
+//! [`Wizard::advance()`] when user confirms the step. Result is [`Calibration`], which can be
+//! stored (with `serde-serialize` feature) and applied to events as a filter.
+//!
+//! Values are collected from `AxisChanged` events, so for best results the wizard should get
+//! events before `deadzone` filter is applied (see [`GilrsBuilder::with_default_filters()`]).
+//!
+//! [`GilrsBuilder::with_default_filters()`]: crate::GilrsBuilder::with_default_filters
+//!
+//! # Example
+//!
+//! ```
+//! use gilrs::calibration::{Step, Wizard};
+//! use gilrs::{Axis, Filter, Gilrs};
+//!
+//! let mut gilrs = Gilrs::new().unwrap();
+//! # let id = match gilrs.gamepads().next() { Some((id, _)) => id, None => return };
+//! let mut wizard = Wizard::new(id, &[Axis::LeftStickX, Axis::LeftStickY]);
+//!
+//! while wizard.step() != Step::Done {
+//!     while let Some(event) = gilrs.next_event() {
+//!         wizard.handle_event(&event);
+//!     }
+//!     println!("{} ({:.0}%)", wizard.instructions(), wizard.progress() * 100.0);
+//!     // Call when user confirms the step.
+//!     wizard.advance();
+//!     # break;
+//! }
+//!
+//! // Calibration applies to gamepad with `id`. Use `GamepadMatch::Uuid` to store it.
+//! if let Some(calibration) = wizard.calibration() {
+//!     while let Some(event) = gilrs.next_event().filter_ev(&calibration, &mut gilrs) {
+//!         gilrs.update(&event);
+//!     }
+//! }
+//! ```
+
+use crate::ev::filter::{FilterFn, GamepadMatch};
+use crate::ev::{Axis, Event, EventType};
+use crate::gamepad::{GamepadId, Gilrs};
+
+use std::cell::Cell;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+// Minimal distance from the center that stick has to reach in both directions during range step.
+const MIN_RANGE: f32 = 0.5;
+// Measured noise of released axis is multiplied by this value to get deadzone.
+const DEADZONE_MARGIN: f32 = 1.5;
+const MAX_DEADZONE: f32 = 0.5;
+
+/// Calibration of single axis. All values are in the same units as values of `AxisChanged`
+/// events.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct AxisCalibration {
+    /// Lowest value that axis reports.
+    pub min: f32,
+    /// Highest value that axis reports.
+    pub max: f32,
+    /// Value of released axis.
+    pub center: f32,
+    /// Values closer than this to `center` are reported as 0.0.
+    pub deadzone: f32,
+}
+
+impl AxisCalibration {
+    /// Maps `value` to range from -1.0 to 1.0, so that `center` becomes 0.0 and `min` and `max`
+    /// become -1.0 and 1.0.
+    pub fn apply(&self, value: f32) -> f32 {
+        let offset = value - self.center;
+        if offset.abs() <= self.deadzone {
+            return 0.0;
+        }
+
+        let range = if offset > 0.0 {
+            self.max - self.center
+        } else {
+            self.center - self.min
+        } - self.deadzone;
+
+        if range <= 0.0 {
+            return offset.signum();
+        }
+
+        ((offset.abs() - self.deadzone) / range * offset.signum()).clamp(-1.0, 1.0)
+    }
+}
+
+/// Calibration profile of gamepad's axes, created by [`Wizard`] or manually.
+///
+/// `Calibration` implements [`FilterFn`], which replaces values of `AxisChanged` events of
+/// calibrated axes with calibrated ones. Calibration describes one device, so events of gamepads
+/// that don't match [`gamepad()`](Self::gamepad) are passed unchanged.
+///
+/// When the filter is used for the first time and after calibration changes, it emits
+/// `EventType::MappingChanged` for connected gamepads it applies to.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct Calibration {
+    gamepad: GamepadMatch,
+    axes: Vec<(Axis, AxisCalibration)>,
+    // Whether `MappingChanged` was emitted for current calibration.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    announced: Cell<bool>,
+}
+
+impl Calibration {
+    /// Creates calibration of gamepads selected by `gamepad` that doesn't change any axis.
+    pub fn new(gamepad: GamepadMatch) -> Self {
+        Calibration {
+            gamepad,
+            axes: Vec::new(),
+            announced: Cell::new(false),
+        }
+    }
+
+    /// Returns gamepads to which calibration applies.
+    pub fn gamepad(&self) -> GamepadMatch {
+        self.gamepad
+    }
+
+    /// Sets gamepads to which calibration applies, for example to UUID of gamepad after
+    /// calibration created by [`Wizard`] is loaded in next session.
+    pub fn set_gamepad(&mut self, gamepad: GamepadMatch) {
+        self.gamepad = gamepad;
+        self.announced.set(false);
+    }
+
+    /// Sets calibration of `axis`.
+    pub fn set(&mut self, axis: Axis, calibration: AxisCalibration) {
+        match self.axes.iter_mut().find(|(a, _)| *a == axis) {
+            Some((_, c)) => *c = calibration,
+            None => self.axes.push((axis, calibration)),
+        }
+        self.announced.set(false);
+    }
+
+    /// Returns calibration of `axis`.
+    pub fn get(&self, axis: Axis) -> Option<&AxisCalibration> {
+        self.axes.iter().find(|(a, _)| *a == axis).map(|(_, c)| c)
+    }
+
+    /// Returns calibrated value of `axis`. Values of axes that are not calibrated are returned
+    /// unchanged.
+    pub fn apply(&self, axis: Axis, value: f32) -> f32 {
+        self.get(axis).map_or(value, |c| c.apply(value))
+    }
+
+    /// Returns iterator over calibrated axes.
+    pub fn iter(&self) -> impl Iterator<Item = (Axis, &AxisCalibration)> + '_ {
+        self.axes.iter().map(|(a, c)| (*a, c))
+    }
+}
+
+impl Debug for Calibration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Calibration")
+            .field("gamepad", &self.gamepad)
+            .field("axes", &self.axes)
+            .finish()
+    }
+}
+
+impl PartialEq for Calibration {
+    fn eq(&self, other: &Self) -> bool {
+        self.gamepad == other.gamepad && self.axes == other.axes
+    }
+}
+
+impl FilterFn for Calibration {
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        if !self.announced.replace(true) {
+            let ids: Vec<_> = gilrs
+                .gamepads()
+                .filter(|(id, gamepad)| self.gamepad.matches(*id, gamepad))
+                .map(|(id, _)| id)
+                .collect();
+            for id in ids {
+                gilrs.insert_event(Event::new(id, EventType::MappingChanged));
+            }
+        }
+
+        match ev {
+            Some(Event {
+                id,
+                event: EventType::AxisChanged(axis, val, nec),
+                time,
+            }) if gilrs
+                .connected_gamepad(id)
+                .is_some_and(|gamepad| self.gamepad.matches(id, &gamepad)) =>
+            {
+                Some(Event {
+                    id,
+                    event: EventType::AxisChanged(axis, self.apply(axis, val), nec),
+                    time,
+                })
+            }
+            _ => ev,
+        }
+    }
+}
+
+/// Step of calibration performed by [`Wizard`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Step {
+    /// Axes should be moved through their full range.
+    Range,
+    /// Axes should be released.
+    Center,
+    /// Calibration is finished.
+    Done,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Samples {
+    axis: Axis,
+    min: f32,
+    max: f32,
+    last: f32,
+    // Collected during `Step::Center`.
+    center_sum: f32,
+    center_count: u32,
+    center_min: f32,
+    center_max: f32,
+}
+
+impl Samples {
+    fn new(axis: Axis) -> Self {
+        Samples {
+            axis,
+            min: 0.0,
+            max: 0.0,
+            last: 0.0,
+            center_sum: 0.0,
+            center_count: 0,
+            center_min: f32::INFINITY,
+            center_max: f32::NEG_INFINITY,
+        }
+    }
+
+    fn center(&self) -> f32 {
+        if self.center_count == 0 {
+            self.last
+        } else {
+            self.center_sum / self.center_count as f32
+        }
+    }
+
+    fn range_progress(&self) -> f32 {
+        // Depending on `TriggerRange`, released trigger may be at the bottom of its range.
+        if self.axis.is_trigger() {
+            return ((self.max - self.min) / (2.0 * MIN_RANGE)).min(1.0);
+        }
+
+        let low = (-self.min / MIN_RANGE).min(1.0);
+        let high = (self.max / MIN_RANGE).min(1.0);
+
+        (low + high) / 2.0
+    }
+
+    fn calibration(&self) -> AxisCalibration {
+        let center = self.center();
+        let noise = if self.center_count == 0 {
+            0.0
+        } else {
+            (self.center_max - center).max(center - self.center_min)
+        };
+
+        AxisCalibration {
+            min: self.min,
+            max: self.max,
+            center,
+            deadzone: (noise * DEADZONE_MARGIN).min(MAX_DEADZONE),
+        }
+    }
+}
+
+/// State machine that guides user through calibration of gamepad's axes.
+///
+/// See [module documentation](self) for details.
+#[derive(Clone, Debug)]
+pub struct Wizard {
+    gamepad: GamepadId,
+    step: Step,
+    samples: Vec<Samples>,
+}
+
+impl Wizard {
+    /// Creates wizard that calibrates `axes` of gamepad `gamepad`.
+    pub fn new(gamepad: GamepadId, axes: &[Axis]) -> Self {
+        Wizard {
+            gamepad,
+            step: Step::Range,
+            samples: axes.iter().map(|&axis| Samples::new(axis)).collect(),
+        }
+    }
+
+    /// Returns current step.
+    pub fn step(&self) -> Step {
+        self.step
+    }
+
+    /// Returns instructions for the user for current step.
+    pub fn instructions(&self) -> &'static str {
+        match self.step {
+            Step::Range => "Move sticks in full circles and press triggers fully.",
+            Step::Center => "Release sticks and triggers.",
+            Step::Done => "Calibration is finished.",
+        }
+    }
+
+    /// Collects value from `AxisChanged` event of calibrated gamepad. Other events are ignored.
+    pub fn handle_event(&mut self, event: &Event) {
+        if event.id != self.gamepad {
+            return;
+        }
+
+        let (axis, val) = match event.event {
+            EventType::AxisChanged(axis, val, _) => (axis, val),
+            _ => return,
+        };
+
+        let samples = match self.samples.iter_mut().find(|s| s.axis == axis) {
+            Some(samples) => samples,
+            None => return,
+        };
+
+        samples.last = val;
+        match self.step {
+            Step::Range => {
+                samples.min = samples.min.min(val);
+                samples.max = samples.max.max(val);
+            }
+            Step::Center => {
+                samples.center_sum += val;
+                samples.center_count += 1;
+                samples.center_min = samples.center_min.min(val);
+                samples.center_max = samples.center_max.max(val);
+            }
+            Step::Done => (),
+        }
+    }
+
+    /// Returns progress of current step from 0.0 to 1.0. During `Step::Range` this is how much
+    /// of required range all axes covered, other steps are always complete.
+    pub fn progress(&self) -> f32 {
+        match self.step {
+            Step::Range if !self.samples.is_empty() => {
+                self.samples
+                    .iter()
+                    .map(Samples::range_progress)
+                    .sum::<f32>()
+                    / self.samples.len() as f32
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Moves to the next step if current one is complete (see [`progress()`](Self::progress)).
+    /// Returns `true` if step was changed.
+    pub fn advance(&mut self) -> bool {
+        if self.progress() < 1.0 {
+            return false;
+        }
+
+        self.step = match self.step {
+            Step::Range => Step::Center,
+            Step::Center | Step::Done => Step::Done,
+        };
+
+        true
+    }
+
+    /// Discards collected values and starts from the first step.
+    pub fn restart(&mut self) {
+        self.step = Step::Range;
+        for samples in &mut self.samples {
+            *samples = Samples::new(samples.axis);
+        }
+    }
+
+    /// Returns computed calibration, if wizard is in `Step::Done`. It applies to calibrated
+    /// gamepad, selected by its [`GamepadId`].
+    pub fn calibration(&self) -> Option<Calibration> {
+        if self.step != Step::Done {
+            return None;
+        }
+
+        Some(Calibration {
+            gamepad: GamepadMatch::Id(self.gamepad),
+            axes: self
+                .samples
+                .iter()
+                .map(|s| (s.axis, s.calibration()))
+                .collect(),
+            announced: Cell::new(false),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AxisCalibration, Calibration, Step, Wizard};
+    use crate::ev::filter::{Filter, GamepadMatch};
+    use crate::ev::{Axis, Code, Event, EventType};
+    use crate::gamepad::{GamepadId, GilrsBuilder};
+    use crate::test_backend;
+    use gilrs_core::native_ev_codes as nec;
+
+    fn axis_event(id: GamepadId, val: f32) -> Event {
+        Event::new(
+            id,
+            EventType::AxisChanged(Axis::LeftStickX, val, Code(nec::AXIS_LSTICKX)),
+        )
+    }
+
+    #[test]
+    fn wizard() {
+        let id = GamepadId(0);
+        let mut wizard = Wizard::new(id, &[Axis::LeftStickX]);
+
+        wizard.handle_event(&axis_event(id, -0.8));
+        assert_eq!(wizard.progress(), 0.5);
+        assert!(!wizard.advance());
+        // Other gamepad.
+        wizard.handle_event(&axis_event(GamepadId(1), 0.9));
+        assert_eq!(wizard.progress(), 0.5);
+
+        wizard.handle_event(&axis_event(id, 0.9));
+        assert!(wizard.advance());
+        assert_eq!(wizard.step(), Step::Center);
+        assert_eq!(wizard.calibration(), None);
+
+        for val in [0.08, 0.12, 0.1] {
+            wizard.handle_event(&axis_event(id, val));
+        }
+        assert!(wizard.advance());
+        assert_eq!(wizard.step(), Step::Done);
+
+        let calibration = wizard.calibration().unwrap();
+        let axis = calibration.get(Axis::LeftStickX).unwrap();
+        assert_eq!(axis.min, -0.8);
+        assert_eq!(axis.max, 0.9);
+        assert!((axis.center - 0.1).abs() < 1e-6);
+        assert!((axis.deadzone - 0.03).abs() < 1e-6);
+    }
+
+    #[test]
+    fn apply() {
+        let axis = AxisCalibration {
+            min: -0.8,
+            max: 0.9,
+            center: 0.1,
+            deadzone: 0.1,
+        };
+
+        assert_eq!(axis.apply(0.15), 0.0);
+        assert_eq!(axis.apply(0.9), 1.0);
+        assert_eq!(axis.apply(-0.8), -1.0);
+        assert_eq!(axis.apply(-1.0), -1.0);
+        assert!((axis.apply(0.55) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn filter_selected_gamepad() {
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let first = test_backend::connect(&mut gilrs, "First");
+        let second = test_backend::connect(&mut gilrs, "Second");
+
+        let mut calibration = Calibration::new(GamepadMatch::Id(first));
+        calibration.set(
+            Axis::LeftStickX,
+            AxisCalibration {
+                min: -1.0,
+                max: 1.0,
+                center: 0.1,
+                deadzone: 0.1,
+            },
+        );
+        let value = |ev: Option<Event>| match ev.map(|ev| ev.event) {
+            Some(EventType::AxisChanged(_, val, _)) => val,
+            _ => panic!("expected AxisChanged event"),
+        };
+
+        let ev = Some(axis_event(first, 0.15)).filter_ev(&calibration, &mut gilrs);
+        assert_eq!(value(ev), 0.0);
+        let ev = Some(axis_event(second, 0.15)).filter_ev(&calibration, &mut gilrs);
+        assert_eq!(value(ev), 0.15);
+
+        let uuid = gilrs.gamepad(second).uuid();
+        calibration.set_gamepad(GamepadMatch::Uuid(uuid));
+        let ev = Some(axis_event(first, 0.15)).filter_ev(&calibration, &mut gilrs);
+        assert_eq!(value(ev), 0.15);
+        let ev = Some(axis_event(second, 0.15)).filter_ev(&calibration, &mut gilrs);
+        assert_eq!(value(ev), 0.0);
+    }
+
+    #[test]
+    fn mapping_changed() {
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let first = test_backend::connect(&mut gilrs, "First");
+        test_backend::connect(&mut gilrs, "Second");
+        let mut changed = |calibration: &Calibration| {
+            let mut ids = vec![];
+            // Filter can queue events after `next_event()` returned `None`, so drain queue twice.
+            for _ in 0..2 {
+                while let Some(ev) = gilrs.next_event().filter_ev(calibration, &mut gilrs) {
+                    if ev.event == EventType::MappingChanged {
+                        ids.push(ev.id);
+                    }
+                }
+            }
+
+            ids
+        };
+
+        let mut calibration = Calibration::new(GamepadMatch::Id(first));
+        assert_eq!(changed(&calibration), [first]);
+        assert_eq!(changed(&calibration), []);
+
+        calibration.set(
+            Axis::LeftStickX,
+            AxisCalibration {
+                min: -1.0,
+                max: 1.0,
+                center: 0.0,
+                deadzone: 0.1,
+            },
+        );
+        assert_eq!(changed(&calibration), [first]);
+    }
+}
@@ -72,12 +72,21 @@
 //!
 //! `FilterFn` is also implemented for all `Fn(Option<Event>, &Gilrs) -> Option<Event>`, so above
 //! example could be simplified to passing closure to `filter()` function.
+//!
+//! To make filter chains easier to debug, filters can also override `FilterFn::describe()`. Built-in
+//! filters describe their configuration and [`Profiles`](crate::Profiles) describes filters of all
+//! its profiles.
 
-use crate::ev::{Axis, AxisOrBtn, Button, Code, Event, EventType};
-use crate::gamepad::{Gamepad, Gilrs};
+use crate::ev::{Axis, AxisDirection, AxisOrBtn, Button, Code, Event, EventType};
+use crate::gamepad::{Gamepad, GamepadId, Gilrs, MappingSource, TriggerRange};
 use crate::utils;
 
-use std::time::Duration;
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+use std::cell::RefCell;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::time::{Duration, Instant};
 
 /// Discard axis events that changed less than `threshold`.
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -99,6 +108,10 @@ impl Default for Jitter {
 }
 
 impl FilterFn for Jitter {
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+
     fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
         match ev {
             Some(Event {
@@ -116,6 +129,87 @@ impl FilterFn for Jitter {
     }
 }
 
+/// Generates `AxisPressed` and `AxisReleased` events when axis value crosses thresholds, so
+/// sticks can be treated as digital input.
+///
+/// Axis is pressed when its value reaches `press` in either direction and released when it falls
+/// below `release`. Using `release` lower than `press` prevents axis from toggling when value
+/// oscillates around threshold. `AxisChanged` events are passed unchanged, new events are inserted
+/// after them.
+///
+/// Filter uses gamepad state to remember which axes are pressed, so events have to be passed to
+/// `Gilrs::update()` (which is done automatically unless it was disabled).
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct AxisThreshold {
+    pub press: f32,
+    pub release: f32,
+}
+
+impl AxisThreshold {
+    /// Creates new `AxisThreshold` filter with `press` set to 0.6 and `release` set to 0.4.
+    pub fn new() -> Self {
+        AxisThreshold {
+            press: 0.6,
+            release: 0.4,
+        }
+    }
+
+    /// Returns which direction should be released and which pressed when axis that is currently
+    /// pressed in `pressed` direction changes its value to `val`.
+    fn transition(
+        &self,
+        pressed: Option<AxisDirection>,
+        val: f32,
+    ) -> (Option<AxisDirection>, Option<AxisDirection>) {
+        let release = pressed.filter(|dir| dir.project(val) < self.release);
+        let press = AxisDirection::from_value(val)
+            .filter(|dir| dir.project(val) >= self.press && Some(*dir) != pressed);
+
+        (release, press)
+    }
+}
+
+impl Default for AxisThreshold {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FilterFn for AxisThreshold {
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        if let Some(Event {
+            event: EventType::AxisChanged(axis, val, nec),
+            id,
+            time,
+        }) = ev
+        {
+            let pressed = gilrs.gamepad(id).state().axis_pressed(nec);
+            let (release, press) = self.transition(pressed, val);
+
+            if let Some(dir) = release {
+                gilrs.insert_event(Event {
+                    id,
+                    time,
+                    event: EventType::AxisReleased(axis, dir, nec),
+                });
+            }
+            if let Some(dir) = press {
+                gilrs.insert_event(Event {
+                    id,
+                    time,
+                    event: EventType::AxisPressed(axis, dir, nec),
+                });
+            }
+        }
+
+        ev
+    }
+}
+
 fn apply_deadzone(x: f32, y: f32, threshold: f32) -> (f32, f32) {
     let magnitude = utils::clamp((x * x + y * y).sqrt(), 0.0, 1.0);
     if magnitude <= threshold {
@@ -140,9 +234,71 @@ fn deadzone_nonzero_axis_idx(axis: Axis) -> Option<usize> {
     })
 }
 
+/// Returns value of analog trigger with dead zone applied or `None` if `nec` is not analog.
+fn trigger_value(
+    gilrs: &Gilrs,
+    id: GamepadId,
+    nec: Code,
+    val: f32,
+    range: TriggerRange,
+) -> Option<f32> {
+    let gamepad = gilrs.gamepad(id);
+    // Digital buttons don't have dead zone.
+    gamepad.deadzone(nec)?;
+
+    let val = gilrs
+        .trigger_deadzone()
+        .apply(range.normalize(val), gamepad.trigger_threshold(nec));
+
+    Some(range.denormalize(val))
+}
+
 /// Drops events in dead zone and remaps value to keep it in standard range.
+///
+/// Sticks use radial dead zone reported by the device. Analog triggers use thresholds set with
+/// [`GilrsBuilder::set_trigger_deadzone()`](crate::GilrsBuilder::set_trigger_deadzone).
 pub fn deadzone(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
     match ev {
+        Some(Event {
+            event: EventType::AxisChanged(axis, val, nec),
+            id,
+            time,
+        }) if axis.is_trigger() => {
+            let val = match trigger_value(gilrs, id, nec, val, gilrs.trigger_range()) {
+                Some(val) => val,
+                None => return ev,
+            };
+
+            Some(if gilrs.gamepad(id).state().value(nec) == val {
+                Event::new(id, EventType::Dropped)
+            } else {
+                Event {
+                    id,
+                    time,
+                    event: EventType::AxisChanged(axis, val, nec),
+                }
+            })
+        }
+        Some(Event {
+            event: EventType::ButtonChanged(btn, val, nec),
+            id,
+            time,
+        }) if btn == Button::LeftTrigger2 || btn == Button::RightTrigger2 => {
+            let val = match trigger_value(gilrs, id, nec, val, TriggerRange::ZeroToOne) {
+                Some(val) => val,
+                None => return ev,
+            };
+
+            Some(if gilrs.gamepad(id).state().value(nec) == val {
+                Event::new(id, EventType::Dropped)
+            } else {
+                Event {
+                    id,
+                    time,
+                    event: EventType::ButtonChanged(btn, val, nec),
+                }
+            })
+        }
         Some(Event {
             event: EventType::AxisChanged(axis, val, nec),
             id,
@@ -233,6 +389,176 @@ pub fn deadzone(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
     }
 }
 
+/// Limits length of stick vector to 1.0, so moving diagonally isn't faster than moving along
+/// one axis.
+///
+/// If `square_gate` is `true`, values are also remapped from square to circle, which is useful
+/// for sticks with square gate (or gamepads that report square range) that report 1.0 on both axes
+/// when pushed diagonally. Without it, such diagonals are just clamped and lose precision.
+///
+/// When value of one axis changes, value of the other one may change too. In this case
+/// `AxisChanged` event for the other axis is inserted after the current one. This filter should be
+/// used after `deadzone()`.
+///
+/// Filter remembers unfiltered and clamped values of sticks of each gamepad, so the same instance
+/// should be used for all events. Gamepad state is not used, but with default
+/// [`GilrsBuilder`](crate::GilrsBuilder) settings it's updated by `next_event()` before this filter
+/// runs and keeps unclamped value of the axis from the current event. Use
+/// [`set_update_state(false)`](crate::GilrsBuilder::set_update_state) and call
+/// [`Gilrs::update()`] with filtered events if you read stick values from gamepad state.
+#[derive(Clone, Default)]
+pub struct ClampToCircle {
+    square_gate: bool,
+    // Indexed by GamepadId.
+    sticks: RefCell<Vec<ClampedSticks>>,
+}
+
+// Unfiltered values of stick axes, values returned by `ClampToCircle` and values of events
+// inserted by it, indexed by `stick_axis_idx()`.
+#[derive(Copy, Clone, Debug, Default)]
+struct ClampedSticks {
+    raw: [f32; 4],
+    clamped: [f32; 4],
+    pending: [Option<f32>; 4],
+}
+
+impl ClampToCircle {
+    /// Creates new `ClampToCircle` filter with `square_gate` set to `false`.
+    pub fn new() -> Self {
+        ClampToCircle::default()
+    }
+
+    /// Sets whether values are also remapped from square to circle.
+    pub fn with_square_gate(mut self, square_gate: bool) -> Self {
+        self.square_gate = square_gate;
+        self
+    }
+
+    /// Returns `true` if values are also remapped from square to circle.
+    pub fn square_gate(&self) -> bool {
+        self.square_gate
+    }
+
+    fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = (utils::clamp(x, -1.0, 1.0), utils::clamp(y, -1.0, 1.0));
+        let (x, y) = if self.square_gate {
+            (
+                x * (1.0 - y * y / 2.0).sqrt(),
+                y * (1.0 - x * x / 2.0).sqrt(),
+            )
+        } else {
+            (x, y)
+        };
+
+        let magnitude = (x * x + y * y).sqrt();
+        if magnitude > 1.0 {
+            (x / magnitude, y / magnitude)
+        } else {
+            (x, y)
+        }
+    }
+}
+
+fn stick_axis_idx(axis: Axis) -> Option<usize> {
+    Some(match axis {
+        Axis::LeftStickX => 0,
+        Axis::LeftStickY => 1,
+        Axis::RightStickX => 2,
+        Axis::RightStickY => 3,
+        _ => return None,
+    })
+}
+
+impl Debug for ClampToCircle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ClampToCircle")
+            .field("square_gate", &self.square_gate)
+            .finish()
+    }
+}
+
+impl PartialEq for ClampToCircle {
+    fn eq(&self, other: &Self) -> bool {
+        self.square_gate == other.square_gate
+    }
+}
+
+impl FilterFn for ClampToCircle {
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        let (axis, val, nec, id, time) = match ev {
+            Some(Event {
+                event: EventType::AxisChanged(axis, val, nec),
+                id,
+                time,
+            }) => (axis, val, nec, id, time),
+            // Don't keep values from before gamepad reconnected.
+            Some(Event {
+                event: EventType::Connected | EventType::Disconnected,
+                id,
+                ..
+            }) => {
+                if let Some(sticks) = self.sticks.borrow_mut().get_mut(id.0) {
+                    *sticks = ClampedSticks::default();
+                }
+                return ev;
+            }
+            _ => return ev,
+        };
+        let (idx, other_axis) = match (stick_axis_idx(axis), axis.second_axis()) {
+            (Some(idx), Some(other_axis)) => (idx, other_axis),
+            _ => return ev,
+        };
+        let other_idx = idx ^ 1;
+        let other_code = match gilrs.connected_gamepad(id) {
+            Some(gamepad) => gamepad.axis_code(other_axis),
+            None => return ev,
+        };
+
+        let mut sticks = self.sticks.borrow_mut();
+        if sticks.len() <= id.0 {
+            sticks.resize(id.0 + 1, ClampedSticks::default());
+        }
+        let data = &mut sticks[id.0];
+        // Event for the other axis inserted by this filter.
+        if data.pending[idx] == Some(val) {
+            data.pending[idx] = None;
+            data.clamped[idx] = val;
+            return ev;
+        }
+        data.raw[idx] = val;
+
+        let (x, y) = if idx % 2 == 0 {
+            (val, data.raw[other_idx])
+        } else {
+            (data.raw[other_idx], val)
+        };
+        let (x, y) = self.apply(x, y);
+        let (val, other_val) = if idx % 2 == 0 { (x, y) } else { (y, x) };
+        data.clamped[idx] = val;
+
+        if let Some(other_code) = other_code {
+            if data.clamped[other_idx] != other_val {
+                data.pending[other_idx] = Some(other_val);
+                gilrs.insert_event(Event {
+                    id,
+                    time,
+                    event: EventType::AxisChanged(other_axis, other_val, other_code),
+                });
+            }
+        }
+
+        Some(Event {
+            id,
+            time,
+            event: EventType::AxisChanged(axis, val, nec),
+        })
+    }
+}
+
 /// Maps axis dpad events to button dpad events.
 ///
 /// This filter will do nothing if gamepad has dpad buttons (to prevent double events for same
@@ -430,6 +756,12 @@ pub fn axis_dpad_to_button(ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event
 }
 
 /// Repeats pressed keys.
+///
+/// Repeats are scheduled from timestamps of button events, not from time when filter is called.
+/// If events are not processed for a while (for example after a long frame), repeats that were
+/// missed are emitted at once, so scrolling through menus keeps the same speed regardless of frame
+/// rate. At most [`GilrsBuilder::set_repeat_max_catch_up()`](crate::GilrsBuilder::set_repeat_max_catch_up)
+/// repeats are emitted this way, older ones are skipped.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Repeat {
     pub after: Duration,
@@ -444,6 +776,59 @@ impl Repeat {
             every: Duration::from_millis(30),
         }
     }
+
+    // Returns offset of next repeat from time when button was pressed (or last repeated if
+    // `repeating`) at `last`, or `None` if no repeat is due at `now`. At most `max_catch_up`
+    // missed repeats are emitted.
+    fn next_repeat(
+        &self,
+        repeating: bool,
+        last: Instant,
+        now: Instant,
+        max_catch_up: u32,
+    ) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(last);
+
+        if !repeating {
+            return (elapsed >= self.after).then_some(self.after);
+        }
+
+        if elapsed < self.every {
+            return None;
+        }
+
+        let due = elapsed
+            .as_nanos()
+            .checked_div(self.every.as_nanos())
+            .unwrap_or(1);
+        let skipped = due.saturating_sub(u128::from(max_catch_up.max(1)));
+        let skipped = u32::try_from(skipped).unwrap_or(u32::MAX);
+
+        Some(self.every.saturating_mul(skipped.saturating_add(1)))
+    }
+
+    /// Returns buttons of connected gamepads that are held and will be repeated, together with
+    /// time when their next repeat is due, in no particular order. Due time can be in the past if
+    /// filter wasn't called since then.
+    pub fn pending(&self, gilrs: &Gilrs) -> Vec<(GamepadId, Code, Instant)> {
+        let mut pending = Vec::new();
+        for (id, gamepad) in gilrs.gamepads() {
+            for (code, btn_data) in gamepad.state().buttons() {
+                if !btn_data.is_pressed() {
+                    continue;
+                }
+
+                let delay = if btn_data.is_repeating() {
+                    self.every
+                } else {
+                    self.after
+                };
+                pending.push((id, code, btn_data.instant() + delay));
+            }
+        }
+
+        pending
+    }
 }
 
 impl Default for Repeat {
@@ -453,43 +838,40 @@ impl Default for Repeat {
 }
 
 impl FilterFn for Repeat {
+    fn describe(&self) -> String {
+        format!("{:?}", self)
+    }
+
     fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
         match ev {
             Some(ev) => Some(ev),
             None => {
-                let now = utils::time_now();
+                let now = Instant::now();
+                let max_catch_up = gilrs.repeat_max_catch_up();
                 for (id, gamepad) in gilrs.gamepads() {
                     for (nec, btn_data) in gamepad.state().buttons() {
-                        match (
-                            btn_data.is_pressed(),
+                        if !btn_data.is_pressed() {
+                            continue;
+                        }
+
+                        let offset = self.next_repeat(
                             btn_data.is_repeating(),
-                            now.duration_since(btn_data.timestamp()),
-                        ) {
-                            (true, false, Ok(dur)) if dur >= self.after => {
-                                let btn_name = match gamepad.axis_or_btn_name(nec) {
-                                    Some(AxisOrBtn::Btn(b)) => b,
-                                    _ => Button::Unknown,
-                                };
-
-                                return Some(Event {
-                                    id,
-                                    event: EventType::ButtonRepeated(btn_name, nec),
-                                    time: btn_data.timestamp() + self.after,
-                                });
-                            }
-                            (true, true, Ok(dur)) if dur >= self.every => {
-                                let btn_name = match gamepad.axis_or_btn_name(nec) {
-                                    Some(AxisOrBtn::Btn(b)) => b,
-                                    _ => Button::Unknown,
-                                };
-
-                                return Some(Event {
-                                    id,
-                                    event: EventType::ButtonRepeated(btn_name, nec),
-                                    time: btn_data.timestamp() + self.every,
-                                });
-                            }
-                            _ => (),
+                            btn_data.instant(),
+                            now,
+                            max_catch_up,
+                        );
+
+                        if let Some(offset) = offset {
+                            let btn_name = match gamepad.axis_or_btn_name(nec) {
+                                Some(AxisOrBtn::Btn(b)) => b,
+                                _ => Button::Unknown,
+                            };
+
+                            return Some(Event {
+                                id,
+                                event: EventType::ButtonRepeated(btn_name, nec),
+                                time: btn_data.timestamp() + offset,
+                            });
                         }
                     }
                 }
@@ -499,6 +881,114 @@ impl FilterFn for Repeat {
     }
 }
 
+/// Selects gamepads in allow and deny lists of [`Select`] filter.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum GamepadMatch {
+    /// Gamepad with given id.
+    Id(GamepadId),
+    /// Gamepads with given UUID.
+    Uuid([u8; 16]),
+    /// Gamepads with given vendor and product ID.
+    VendorProduct(u16, u16),
+    /// Gamepads whose mapping comes from given source.
+    MappingSource(MappingSource),
+}
+
+impl GamepadMatch {
+    /// Returns `true` if `gamepad` with `id` is selected by this match.
+    pub fn matches(&self, id: GamepadId, gamepad: &Gamepad<'_>) -> bool {
+        match *self {
+            GamepadMatch::Id(match_id) => id == match_id,
+            GamepadMatch::Uuid(uuid) => gamepad.uuid() == uuid,
+            GamepadMatch::VendorProduct(vendor, product) => {
+                gamepad.vendor_id() == Some(vendor) && gamepad.product_id() == Some(product)
+            }
+            GamepadMatch::MappingSource(source) => gamepad.mapping_source() == source,
+        }
+    }
+}
+
+/// Applies `filter` only to events from selected gamepads. Other events are passed unchanged.
+///
+/// Gamepad is selected if `allow` is empty or any of its entries matches and none of the
+/// entries in `deny` matches. Events of gamepads that are not connected are passed unchanged.
+/// When there is no event (`None`), `filter` is always called, so filters that generate new
+/// events, like [`Repeat`], are not restricted.
+///
+/// ```
+/// use gilrs::{GilrsBuilder, Filter};
+/// use gilrs::ev::filter::{axis_dpad_to_button, GamepadMatch, Select};
+///
+/// let mut gilrs = GilrsBuilder::new().with_default_filters(false).build().unwrap();
+/// // Wheel with hat that is already mapped to buttons.
+/// let dpad = Select::new(axis_dpad_to_button).deny(GamepadMatch::VendorProduct(0x046d, 0xc29b));
+///
+/// while let Some(event) = gilrs.next_event().filter_ev(&dpad, &mut gilrs) {
+///     gilrs.update(&event);
+///     println!("{:?}", event);
+/// }
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Select<F> {
+    pub filter: F,
+    pub allow: Vec<GamepadMatch>,
+    pub deny: Vec<GamepadMatch>,
+}
+
+impl<F> Select<F> {
+    /// Creates new `Select` filter that applies `filter` to all gamepads.
+    pub fn new(filter: F) -> Self {
+        Select {
+            filter,
+            allow: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+
+    /// Adds `m` to allow list.
+    pub fn allow(mut self, m: GamepadMatch) -> Self {
+        self.allow.push(m);
+        self
+    }
+
+    /// Adds `m` to deny list.
+    pub fn deny(mut self, m: GamepadMatch) -> Self {
+        self.deny.push(m);
+        self
+    }
+
+    /// Returns `true` if filter should be applied to events from gamepad with `id`.
+    pub fn is_selected(&self, id: GamepadId, gamepad: &Gamepad<'_>) -> bool {
+        (self.allow.is_empty() || self.allow.iter().any(|m| m.matches(id, gamepad)))
+            && !self.deny.iter().any(|m| m.matches(id, gamepad))
+    }
+}
+
+impl<F: FilterFn> FilterFn for Select<F> {
+    fn describe(&self) -> String {
+        format!(
+            "Select {{ filter: {}, allow: {:?}, deny: {:?} }}",
+            self.filter.describe(),
+            self.allow,
+            self.deny
+        )
+    }
+
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        match ev {
+            Some(e)
+                if !gilrs
+                    .connected_gamepad(e.id)
+                    .is_some_and(|gamepad| self.is_selected(e.id, &gamepad)) =>
+            {
+                ev
+            }
+            _ => self.filter.filter(ev, gilrs),
+        }
+    }
+}
+
 /// Allow filtering events.
 ///
 /// See module level documentation for more info.
@@ -511,6 +1001,13 @@ pub trait Filter {
 /// See module level documentation for more info.
 pub trait FilterFn {
     fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event>;
+
+    /// Returns human readable description of filter and its configuration, for debugging.
+    ///
+    /// Default implementation returns name of the type, which for functions is their path.
+    fn describe(&self) -> String {
+        std::any::type_name::<Self>().to_owned()
+    }
 }
 
 impl<F> FilterFn for F
@@ -522,26 +1019,357 @@ where
     }
 }
 
+/// What happens when filter returns `None` for `Some(event)`.
+///
+/// Set with [`GilrsBuilder::set_filter_check()`](crate::GilrsBuilder::set_filter_check). To fix
+/// single filter regardless of this setting, wrap it in [`Checked`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FilterCheck {
+    /// Panic in debug builds, return `None` in release builds.
+    #[default]
+    DebugPanic,
+    /// Always panic.
+    Panic,
+    /// Log warning and replace `None` with dropped event.
+    Log,
+    /// Return `None`, which ends `while let` loop over events.
+    Ignore,
+}
+
+/// Wraps filter and replaces `None` returned for `Some(event)` with dropped event, so misbehaving
+/// filter can't end event loop.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Checked<F>(pub F);
+
+impl<F: FilterFn> FilterFn for Checked<F> {
+    fn describe(&self) -> String {
+        format!("Checked({})", self.0.describe())
+    }
+
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        match (ev, self.0.filter(ev, gilrs)) {
+            (Some(ev), None) => Some(ev.drop()),
+            (_, filtered) => filtered,
+        }
+    }
+}
+
+// Applies `FilterCheck` of `gilrs` to event returned by `filter` for `ev`.
+fn check_filtered<F: FilterFn>(
+    filter: &F,
+    ev: Option<Event>,
+    filtered: Option<Event>,
+    gilrs: &Gilrs,
+) -> Option<Event> {
+    const MSG: &str =
+        "Filter changed Some(event) into None. See ev::filter documentation for more info.";
+
+    let ev = match (ev, filtered) {
+        (Some(ev), None) => ev,
+        _ => return filtered,
+    };
+
+    match gilrs.filter_check() {
+        FilterCheck::DebugPanic if cfg!(debug_assertions) => panic!("{}", MSG),
+        FilterCheck::Panic => panic!("{}", MSG),
+        FilterCheck::Log => {
+            warn!("{} ({}), event is dropped", MSG, filter.describe());
+            Some(ev.drop())
+        }
+        FilterCheck::DebugPanic | FilterCheck::Ignore => None,
+    }
+}
+
 impl Filter for Option<Event> {
     fn filter_ev<F: FilterFn>(&self, filter: &F, gilrs: &mut Gilrs) -> Option<Event> {
         let e = filter.filter(*self, gilrs);
-        debug_assert!(
-            !(self.is_some() && e.is_none()),
-            "Filter changed Some(event) into None. See ev::filter documentation for more info."
-        );
-
-        e
+        check_filtered(filter, *self, e, gilrs)
     }
 }
 
 impl Filter for Event {
     fn filter_ev<F: FilterFn>(&self, filter: &F, gilrs: &mut Gilrs) -> Option<Event> {
         let e = filter.filter(Some(*self), gilrs);
-        debug_assert!(
-            e.is_some(),
-            "Filter changed Some(event) into None. See ev::filter documentation for more info."
+        check_filtered(filter, Some(*self), e, gilrs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        deadzone, AxisThreshold, Checked, ClampToCircle, Filter, FilterCheck, FilterFn,
+        GamepadMatch, Jitter, Repeat, Select,
+    };
+    use crate::ev::AxisDirection::{Negative, Positive};
+    use crate::ev::{Axis, Event, EventType};
+    use crate::gamepad::{GamepadId, Gilrs, GilrsBuilder};
+    use crate::test_backend;
+    use gilrs_core::{native_ev_codes as nec, EventType as RawEventType};
+    use std::time::{Duration, Instant, SystemTime};
+
+    #[test]
+    fn clamp_to_circle() {
+        let clamp = ClampToCircle::new();
+        assert_eq!(clamp.apply(0.5, -0.5), (0.5, -0.5));
+        assert_eq!(clamp.apply(2.0, 0.0), (1.0, 0.0));
+        let (x, y) = clamp.apply(1.0, 1.0);
+        assert!((x - 0.5f32.sqrt()).abs() < 1e-6 && (x - y).abs() < 1e-6);
+
+        let square = ClampToCircle::new().with_square_gate(true);
+        // Cardinal directions are not changed.
+        assert_eq!(square.apply(0.0, -1.0), (0.0, -1.0));
+        assert_eq!(square.apply(0.5, 0.0), (0.5, 0.0));
+        // Half way to the corner stays inside circle.
+        let (x, y) = square.apply(0.5, 0.5);
+        assert!(x * x + y * y < 0.5 * 0.5 * 2.0);
+    }
+
+    // Passes events moving left stick to (`x`, `y`) through `filter` and updates state. `gilrs`
+    // must not update state or use default filters.
+    fn clamp_stick(filter: &ClampToCircle, gilrs: &mut Gilrs, id: GamepadId, x: f32, y: f32) {
+        let mut events = vec![];
+        for (axis, val) in [(Axis::LeftStickX, x), (Axis::LeftStickY, y)] {
+            let code = gilrs.gamepad(id).axis_code(axis).unwrap();
+            events.push(Event::new(id, EventType::AxisChanged(axis, val, code)));
+        }
+
+        for ev in events {
+            if let Some(ev) = Some(ev).filter_ev(filter, gilrs) {
+                gilrs.update(&ev);
+            }
+            // Events for the other axis inserted by filter.
+            while let Some(ev) = gilrs.next_event() {
+                if let Some(ev) = Some(ev).filter_ev(filter, gilrs) {
+                    gilrs.update(&ev);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clamp_to_circle_square_gate() {
+        let mut gilrs = GilrsBuilder::new()
+            .with_default_filters(false)
+            .set_update_state(false)
+            .build()
+            .unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let diagonal = 0.5f32.sqrt();
+
+        let square = ClampToCircle::new().with_square_gate(true);
+        clamp_stick(&square, &mut gilrs, id, 1.0, 1.0);
+        let gamepad = gilrs.gamepad(id);
+        assert!((gamepad.value(Axis::LeftStickX) - diagonal).abs() < 1e-6);
+        assert!((gamepad.value(Axis::LeftStickY) - diagonal).abs() < 1e-6);
+
+        // Half way to the corner is inside circle, so it's only clamped with square gate.
+        let clamp = ClampToCircle::new();
+        clamp_stick(&clamp, &mut gilrs, id, 0.7, 0.7);
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), 0.7);
+        clamp_stick(&square, &mut gilrs, id, 0.7, 0.7);
+        assert!(gilrs.gamepad(id).value(Axis::LeftStickX) < 0.7);
+    }
+
+    #[test]
+    fn clamp_to_circle_default_builder() {
+        let mut gilrs = GilrsBuilder::new()
+            .with_default_filters(false)
+            .build()
+            .unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let now = SystemTime::now();
+        for nec in [nec::AXIS_LSTICKX, nec::AXIS_LSTICKY] {
+            gilrs
+                .test_backend()
+                .push(id.0, RawEventType::AxisValueChanged(32767, nec), now);
+        }
+
+        let clamp = ClampToCircle::new();
+        let (mut x, mut y) = (0.0, 0.0);
+        while let Some(ev) = gilrs.next_event().filter_ev(&clamp, &mut gilrs) {
+            match ev.event {
+                EventType::AxisChanged(Axis::LeftStickX, val, _) => x = val,
+                EventType::AxisChanged(Axis::LeftStickY, val, _) => y = val,
+                _ => (),
+            }
+        }
+        let diagonal = 0.5f32.sqrt();
+        assert!((x.abs() - diagonal).abs() < 1e-3, "{x}");
+        assert!((y.abs() - diagonal).abs() < 1e-3, "{y}");
+
+        // Gamepad state has unclamped Y, but Y still has to be restored after X is released.
+        gilrs.test_backend().push(
+            id.0,
+            RawEventType::AxisValueChanged(0, nec::AXIS_LSTICKX),
+            now,
+        );
+        while let Some(ev) = gilrs.next_event().filter_ev(&clamp, &mut gilrs) {
+            match ev.event {
+                EventType::AxisChanged(Axis::LeftStickX, val, _) => x = val,
+                EventType::AxisChanged(Axis::LeftStickY, val, _) => y = val,
+                _ => (),
+            }
+        }
+        assert!(x.abs() < 1e-3, "{x}");
+        assert!((y.abs() - 1.0).abs() < 1e-3, "{y}");
+    }
+
+    #[test]
+    fn clamp_to_circle_instances() {
+        let mut gilrs = GilrsBuilder::new()
+            .with_default_filters(false)
+            .set_update_state(false)
+            .build()
+            .unwrap();
+        let first = test_backend::connect(&mut gilrs, "First");
+        let second = test_backend::connect(&mut gilrs, "Second");
+
+        let a = ClampToCircle::new();
+        let b = ClampToCircle::new();
+        clamp_stick(&a, &mut gilrs, first, 1.0, 0.0);
+
+        // Values of other gamepad are not mixed in.
+        clamp_stick(&a, &mut gilrs, second, 0.0, 1.0);
+        assert_eq!(gilrs.gamepad(second).value(Axis::LeftStickY), 1.0);
+        assert_eq!(gilrs.gamepad(first).value(Axis::LeftStickX), 1.0);
+
+        // `b` didn't see X axis of first gamepad, so Y isn't clamped.
+        let code = gilrs.gamepad(first).axis_code(Axis::LeftStickY).unwrap();
+        let ev = Event::new(first, EventType::AxisChanged(Axis::LeftStickY, 1.0, code));
+        assert_eq!(Some(ev).filter_ev(&b, &mut gilrs), Some(ev));
+        let clamped = Some(ev).filter_ev(&a, &mut gilrs).unwrap();
+        assert!(matches!(clamped.event, EventType::AxisChanged(_, val, _) if val < 1.0));
+    }
+
+    #[test]
+    fn select_gamepads() {
+        let mut gilrs = GilrsBuilder::new()
+            .with_default_filters(false)
+            .set_update_state(false)
+            .build()
+            .unwrap();
+        let first = test_backend::connect(&mut gilrs, "First");
+        let second = test_backend::connect(&mut gilrs, "Second");
+        let drop = |ev: Option<Event>, _: &mut Gilrs| {
+            ev.map(|e| Event {
+                event: EventType::Dropped,
+                ..e
+            })
+        };
+        let filtered = |select: &Select<_>, gilrs: &mut Gilrs, id| {
+            let ev = Event::new(id, EventType::Connected);
+            Some(ev).filter_ev(select, gilrs) != Some(ev)
+        };
+
+        let allow = Select::new(drop).allow(GamepadMatch::Id(first));
+        assert!(filtered(&allow, &mut gilrs, first));
+        assert!(!filtered(&allow, &mut gilrs, second));
+
+        let deny = Select::new(drop).deny(GamepadMatch::Id(first));
+        assert!(!filtered(&deny, &mut gilrs, first));
+        assert!(filtered(&deny, &mut gilrs, second));
+
+        // Events of disconnected and unknown gamepads are passed unchanged.
+        gilrs.test_backend().disconnect(second.0);
+        assert!(!filtered(&deny, &mut gilrs, second));
+        assert!(!filtered(&deny, &mut gilrs, GamepadId(10)));
+    }
+
+    #[test]
+    fn axis_threshold_transitions() {
+        let f = AxisThreshold::new();
+
+        assert_eq!(f.transition(None, 0.5), (None, None));
+        assert_eq!(f.transition(None, 0.6), (None, Some(Positive)));
+        assert_eq!(f.transition(None, -0.7), (None, Some(Negative)));
+        // Hysteresis
+        assert_eq!(f.transition(Some(Positive), 0.5), (None, None));
+        assert_eq!(f.transition(Some(Positive), 0.9), (None, None));
+        assert_eq!(f.transition(Some(Positive), 0.3), (Some(Positive), None));
+        // Direct change of direction
+        assert_eq!(
+            f.transition(Some(Positive), -1.0),
+            (Some(Positive), Some(Negative))
         );
+        assert_eq!(f.transition(Some(Negative), 0.0), (Some(Negative), None));
+    }
+
+    #[test]
+    fn repeat_catch_up() {
+        let ms = Duration::from_millis;
+        let repeat = Repeat::new();
+        let pressed = Instant::now();
+
+        assert_eq!(
+            repeat.next_repeat(false, pressed, pressed + ms(499), 3),
+            None
+        );
+        assert_eq!(
+            repeat.next_repeat(false, pressed, pressed + ms(600), 3),
+            Some(ms(500))
+        );
+
+        let last = pressed + ms(500);
+        assert_eq!(repeat.next_repeat(true, last, last + ms(29), 3), None);
+        // Two repeats are due, first one is scheduled where it should have been.
+        assert_eq!(
+            repeat.next_repeat(true, last, last + ms(65), 3),
+            Some(ms(30))
+        );
+        // After long frame only last 3 repeats are emitted.
+        assert_eq!(
+            repeat.next_repeat(true, last, last + ms(300), 3),
+            Some(ms(240))
+        );
+        assert_eq!(
+            repeat.next_repeat(true, last, last + ms(300), 1),
+            Some(ms(300))
+        );
+
+        // Struct literal still works.
+        let literal = Repeat {
+            after: ms(500),
+            every: ms(30),
+        };
+        assert_eq!(literal, repeat);
+        let gilrs = GilrsBuilder::new()
+            .set_repeat_max_catch_up(1)
+            .build()
+            .unwrap();
+        assert_eq!(gilrs.repeat_max_catch_up(), 1);
+    }
+
+    #[test]
+    fn describe() {
+        assert_eq!(Jitter::new().describe(), "Jitter { threshold: 0.01 }");
+        assert_eq!(deadzone.describe(), "gilrs::ev::filter::deadzone");
+
+        let select = Select::new(ClampToCircle::new()).deny(GamepadMatch::Id(GamepadId(1)));
+        assert_eq!(
+            select.describe(),
+            "Select { filter: ClampToCircle { square_gate: false }, allow: [], deny: [Id(GamepadId(1))] }"
+        );
+    }
+
+    #[test]
+    fn filter_check() {
+        let broken = |_: Option<Event>, _: &mut Gilrs| -> Option<Event> { None };
+        let ev = Event::new(GamepadId(0), EventType::Connected);
+        let build = |check| {
+            GilrsBuilder::new()
+                .with_deferred_enumeration()
+                .set_filter_check(check)
+                .build()
+                .unwrap()
+        };
+
+        let mut gilrs = build(FilterCheck::Log);
+        assert_eq!(ev.filter_ev(&broken, &mut gilrs), Some(ev.drop()));
 
-        e
+        let mut gilrs = build(FilterCheck::Ignore);
+        assert_eq!(ev.filter_ev(&broken, &mut gilrs), None);
+        assert_eq!(ev.filter_ev(&Checked(broken), &mut gilrs), Some(ev.drop()));
+        assert_eq!(None.filter_ev(&Checked(broken), &mut gilrs), None);
     }
 }
@@ -8,14 +8,24 @@
 //! Gamepad state and other event related functionality.
 
 pub mod filter;
+mod payload;
+pub mod sdl;
 pub mod state;
 
+pub(crate) use self::payload::PayloadStore;
+pub use self::payload::{EventRef, OwnedEvent, Payload, PayloadId, PayloadKind};
+
 use std::{
     fmt::{Display, Formatter, Result as FmtResult},
     time::SystemTime,
 };
 
-use crate::{constants::*, gamepad::GamepadId, utils};
+use crate::{
+    constants::*,
+    gamepad::{ConnectionType, GamepadId, HeadsetState},
+    profiles::ProfileId,
+    utils,
+};
 
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
@@ -79,6 +89,30 @@ impl Event {
     pub fn is_dropped(&self) -> bool {
         self.event == EventType::Dropped
     }
+
+    /// Returns handle to payload if event is `EventType::Payload`.
+    pub fn payload_id(&self) -> Option<PayloadId> {
+        match self.event {
+            EventType::Payload(_, id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// Events of one frame returned by [`Gilrs::drain_frame()`](crate::Gilrs::drain_frame), tagged
+/// with frame number chosen by application.
+///
+/// With `serde-serialize` feature it can be sent to other players and applied to state of their
+/// copy of remote gamepads with [`Gilrs::inject_frame()`](crate::Gilrs::inject_frame). Events own their payloads, because
+/// [`PayloadId`] is only valid for `Gilrs` that created it. Keep in mind that `Code`s are
+/// platform-specific.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct FrameEvents {
+    /// Frame number passed to `drain_frame()`.
+    pub frame: u64,
+    /// Events in order they were returned.
+    pub events: Vec<OwnedEvent>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -94,8 +128,15 @@ pub enum EventType {
     ButtonReleased(Button, Code),
     /// Value of button has changed. Value can be in range [0.0, 1.0].
     ButtonChanged(Button, f32, Code),
-    /// Value of axis has changed. Value can be in range [-1.0, 1.0].
+    /// Value of axis has changed. Value can be in range [-1.0, 1.0]. See [`Axis`] for
+    /// orientation of axes.
     AxisChanged(Axis, f32, Code),
+    /// Axis has been pushed past press threshold. This event can be generated by
+    /// [`ev::AxisThreshold`](filter/struct.AxisThreshold.html) event filter.
+    AxisPressed(Axis, AxisDirection, Code),
+    /// Previously pressed axis has returned below release threshold. This event can be generated
+    /// by [`ev::AxisThreshold`](filter/struct.AxisThreshold.html) event filter.
+    AxisReleased(Axis, AxisDirection, Code),
     /// Gamepad has been connected. If gamepad's UUID doesn't match one of disconnected gamepads,
     /// newly connected gamepad will get new ID.
     Connected,
@@ -105,6 +146,58 @@ pub enum EventType {
     Dropped,
     /// A force feedback effect has ran for its duration and stopped.
     ForceFeedbackEffectCompleted,
+    /// XInput user index of the gamepad has changed. `None` means that gamepad no longer has any
+    /// index assigned. See [`Gamepad::xinput_user_index()`](crate::Gamepad::xinput_user_index).
+    ///
+    /// XInput doesn't move connected gamepad to other slot, so currently this event is only
+    /// emitted after `Connected` and `Disconnected` events of gamepads driven by `xinput` backend.
+    XInputUserIndexChanged(Option<u32>),
+    /// Headset has been connected to or disconnected from gamepad's audio jack. See
+    /// [`Gamepad::headset_state()`](crate::Gamepad::headset_state).
+    HeadsetChanged(HeadsetState),
+    /// Gamepad reconnected with the same ID, but over different transport, for example Bluetooth
+    /// instead of USB. See [`Gamepad::connection_type()`](crate::Gamepad::connection_type).
+    ConnectionTypeChanged(ConnectionType),
+    /// Gamepad produced input after being idle (or for the first time since it was connected).
+    /// It is emitted right after the input event and can be used to implement "press any button"
+    /// screens or to switch active device. See
+    /// [`GilrsBuilder::set_idle_timeout()`](crate::GilrsBuilder::set_idle_timeout) and
+    /// [`Gilrs::last_active_gamepad()`](crate::Gilrs::last_active_gamepad).
+    GamepadActivity,
+    /// Gamepad produced no input for longer than idle timeout. Only emitted if enabled with
+    /// [`GilrsBuilder::set_idle_events()`](crate::GilrsBuilder::set_idle_events). Can be used to
+    /// pause game or warn that wireless gamepad may go to sleep soon. See also
+    /// [`Gamepad::idle_for()`](crate::Gamepad::idle_for).
+    GamepadIdle,
+    /// Gamepad was switched to other profile with
+    /// [`Profiles::switch()`](crate::Profiles::switch).
+    ProfileChanged(ProfileId),
+    /// Key of keyboard that is part of gamepad or attached to it (for example Xbox 360 chatpad)
+    /// was pressed. Only keys that are not mapped to any button or axis are reported this way.
+    /// Currently, only Linux reports such keys, and only if they are exposed by the same device
+    /// as the gamepad. Character typed with the key is reported by following `Payload` event with
+    /// [`Payload::Text`].
+    KeyPressed(Code),
+    /// Key of keyboard attached to gamepad was released. See `KeyPressed`.
+    KeyReleased(Code),
+    /// Event that carries data too large to be stored in `EventType`, like text typed on keyboard
+    /// attached to gamepad. `PayloadKind` tells what the payload is; the payload itself can be
+    /// obtained with [`Gilrs::payload()`](crate::Gilrs::payload) or together with the event using
+    /// [`Gilrs::event_ref()`](crate::Gilrs::event_ref).
+    Payload(PayloadKind, PayloadId),
+    /// Mapping of gamepad has changed at runtime, so names and codes of buttons and axes (or
+    /// glyphs) cached by application may be out of date. Emitted after
+    /// [`Gilrs::set_mapping()`](crate::Gilrs::set_mapping),
+    /// [`Gilrs::set_code_remap()`](crate::Gilrs::set_code_remap),
+    /// [`Gilrs::set_lever_mode()`](crate::Gilrs::set_lever_mode),
+    /// [`Gilrs::remap_button()`](crate::Gilrs::remap_button),
+    /// [`Gilrs::remap_axis()`](crate::Gilrs::remap_axis), switching to profile with mapping and
+    /// when mapping files are reloaded (see
+    /// [`GilrsBuilder::set_watch_mapping_files()`](crate::GilrsBuilder::set_watch_mapping_files)).
+    ///
+    /// It's also emitted by [`Calibration`](crate::calibration::Calibration) filter when it's used
+    /// for the first time and after it changes.
+    MappingChanged,
 }
 
 #[repr(u16)]
@@ -206,6 +299,10 @@ impl Button {
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// Gamepad's elements which state can be represented by value from -1.0 to 1.0.
 ///
+/// Orientation of axes is the same on all platforms: `X` axes are positive when pushed right,
+/// `Y` axes are positive when pushed up and `Z` axes (analog triggers) are positive when pressed.
+/// Axes that SDL mappings mark as inverted (with `~`) are negated to follow this convention.
+///
 /// ![Controller layout](https://gilrs-project.gitlab.io/gilrs/img/controller.svg)
 pub enum Axis {
     LeftStickX = AXIS_LSTICKX,
@@ -226,6 +323,11 @@ impl Axis {
         matches!(self, LeftStickX | LeftStickY | RightStickX | RightStickY)
     }
 
+    /// Returns true if axis is `LeftZ` or `RightZ`, which are usually analog triggers.
+    pub fn is_trigger(self) -> bool {
+        matches!(self, Axis::LeftZ | Axis::RightZ)
+    }
+
     /// Returns the other axis from same element of gamepad, if any.
     ///
     /// | input       | output            |
@@ -251,6 +353,37 @@ impl Axis {
     }
 }
 
+/// Direction in which axis is pushed, reported by `AxisPressed` and `AxisReleased` events.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum AxisDirection {
+    /// Axis value is below zero, for example stick is pushed left or down.
+    Negative,
+    /// Axis value is above zero, for example stick is pushed right or up.
+    Positive,
+}
+
+impl AxisDirection {
+    /// Returns direction of `value` or `None` if it's zero.
+    pub fn from_value(value: f32) -> Option<Self> {
+        if value > 0.0 {
+            Some(AxisDirection::Positive)
+        } else if value < 0.0 {
+            Some(AxisDirection::Negative)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `value` multiplied by `1.0` for `Positive` and by `-1.0` for `Negative`.
+    pub fn project(self, value: f32) -> f32 {
+        match self {
+            AxisDirection::Negative => -value,
+            AxisDirection::Positive => value,
+        }
+    }
+}
+
 /// Represents `Axis` or `Button`.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
@@ -0,0 +1,197 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::SystemTime;
+
+use super::{Event, EventType};
+use crate::gamepad::GamepadId;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+// Number of payloads kept by `Gilrs`. Older payloads are dropped.
+const PAYLOAD_CAPACITY: usize = 256;
+
+// Source of `PayloadStore::store`, so handles from other `Gilrs` are not resolved.
+static NEXT_STORE: AtomicU32 = AtomicU32::new(0);
+
+/// Identifies payload of `EventType::Payload` stored by `Gilrs`.
+///
+/// Payloads are kept until 256 newer payloads are added, so handle should be resolved with
+/// [`Gilrs::payload()`](crate::Gilrs::payload) or
+/// [`Gilrs::event_ref()`](crate::Gilrs::event_ref) soon after event was received. Handles are
+/// only valid for `Gilrs` that created them, other instances resolve them to `None`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct PayloadId {
+    store: u32,
+    seq: u32,
+}
+
+/// Family of payload carried by `EventType::Payload`, available without looking the payload up.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum PayloadKind {
+    /// See [`Payload::Text`].
+    Text,
+}
+
+/// Data that is too large to be stored in `EventType` directly.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum Payload {
+    /// Text typed on keyboard that is part of gamepad or attached to it (for example Xbox 360
+    /// chatpad). Emitted after `EventType::KeyPressed` of each key that produces character, US
+    /// layout is assumed. Currently, only Linux reports it.
+    Text(String),
+}
+
+impl Payload {
+    /// Returns family of the payload.
+    pub fn kind(&self) -> PayloadKind {
+        match self {
+            Payload::Text(_) => PayloadKind::Text,
+        }
+    }
+}
+
+/// Event together with its payload borrowed from `Gilrs`. Created with
+/// [`Gilrs::event_ref()`](crate::Gilrs::event_ref).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EventRef<'a> {
+    /// Id of gamepad.
+    pub id: GamepadId,
+    /// Event's data.
+    pub event: EventType,
+    /// Time when event was emitted.
+    pub time: SystemTime,
+    /// Payload of `EventType::Payload` event, `None` for other events or if payload was already
+    /// dropped.
+    pub payload: Option<&'a Payload>,
+}
+
+impl EventRef<'_> {
+    /// Clones payload, so event can outlive `Gilrs` borrow.
+    pub fn into_owned(self) -> OwnedEvent {
+        OwnedEvent {
+            id: self.id,
+            event: self.event,
+            time: self.time,
+            payload: self.payload.cloned(),
+        }
+    }
+}
+
+/// Event that owns its payload. Can be stored or sent to other threads.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct OwnedEvent {
+    /// Id of gamepad.
+    pub id: GamepadId,
+    /// Event's data.
+    pub event: EventType,
+    /// Time when event was emitted.
+    pub time: SystemTime,
+    /// Payload of `EventType::Payload` event.
+    pub payload: Option<Payload>,
+}
+
+#[derive(Debug)]
+pub(crate) struct PayloadStore {
+    entries: VecDeque<(PayloadId, Payload)>,
+    store: u32,
+    next_seq: u32,
+}
+
+impl Default for PayloadStore {
+    fn default() -> Self {
+        PayloadStore {
+            entries: VecDeque::new(),
+            store: NEXT_STORE.fetch_add(1, Ordering::Relaxed),
+            next_seq: 0,
+        }
+    }
+}
+
+impl PayloadStore {
+    pub fn insert(&mut self, payload: Payload) -> PayloadId {
+        let id = PayloadId {
+            store: self.store,
+            seq: self.next_seq,
+        };
+        self.next_seq = self.next_seq.wrapping_add(1);
+
+        if self.entries.len() == PAYLOAD_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((id, payload));
+
+        id
+    }
+
+    pub fn get(&self, id: PayloadId) -> Option<&Payload> {
+        if id.store != self.store {
+            return None;
+        }
+
+        // Ids are consecutive, so position can be computed from the oldest one.
+        let &(first, _) = self.entries.front()?;
+        let idx = id.seq.wrapping_sub(first.seq) as usize;
+        match self.entries.get(idx) {
+            Some((stored, payload)) if *stored == id => Some(payload),
+            _ => None,
+        }
+    }
+
+    pub fn resolve(&self, ev: &Event) -> EventRef<'_> {
+        let payload = match ev.event {
+            EventType::Payload(_, id) => self.get(id),
+            _ => None,
+        };
+
+        EventRef {
+            id: ev.id,
+            event: ev.event,
+            time: ev.time,
+            payload,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Payload, PayloadStore, PAYLOAD_CAPACITY};
+
+    #[test]
+    fn store_evicts_oldest() {
+        let mut store = PayloadStore::default();
+        let first = store.insert(Payload::Text("first".to_owned()));
+        assert_eq!(store.get(first), Some(&Payload::Text("first".to_owned())));
+
+        let mut last = first;
+        for _ in 0..PAYLOAD_CAPACITY {
+            last = store.insert(Payload::Text("next".to_owned()));
+        }
+        assert_eq!(store.get(first), None);
+        assert_eq!(store.get(last), Some(&Payload::Text("next".to_owned())));
+    }
+
+    #[test]
+    fn foreign_id() {
+        let mut store = PayloadStore::default();
+        let mut other = PayloadStore::default();
+        store.insert(Payload::Text("local".to_owned()));
+        let foreign = other.insert(Payload::Text("other".to_owned()));
+
+        assert_eq!(store.get(foreign), None);
+        assert_eq!(other.get(foreign), Some(&Payload::Text("other".to_owned())));
+    }
+}
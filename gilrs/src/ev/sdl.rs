@@ -0,0 +1,198 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Values of SDL's `SDL_GameControllerButton` and `SDL_GameControllerAxis` enums.
+//!
+//! Use [`Button::from_sdl_button()`], [`Button::to_sdl_button()`], [`Axis::from_sdl_axis()`],
+//! [`Axis::to_sdl_axis()`] and [`AxisOrBtn::from_sdl_axis()`] to convert between them and gilrs
+//! types.
+//!
+//! SDL reports analog triggers as axes, while gilrs reports them as `Button::LeftTrigger2` and
+//! `Button::RightTrigger2` (with value available from `ButtonChanged` event). Both these buttons
+//! and `Axis::LeftZ`/`Axis::RightZ` are converted to SDL trigger axes.
+//!
+//! ```
+//! use gilrs::ev::sdl;
+//! use gilrs::ev::AxisOrBtn;
+//! use gilrs::{Axis, Button};
+//!
+//! assert_eq!(Button::South.to_sdl_button(), Some(sdl::BUTTON_A));
+//! assert_eq!(Button::from_sdl_button(sdl::BUTTON_BACK), Some(Button::Select));
+//! assert_eq!(Axis::LeftStickY.to_sdl_axis(), Some(sdl::AXIS_LEFTY));
+//! assert_eq!(
+//!     AxisOrBtn::from_sdl_axis(sdl::AXIS_TRIGGERLEFT),
+//!     Some(AxisOrBtn::Btn(Button::LeftTrigger2))
+//! );
+//! ```
+
+use super::{Axis, AxisOrBtn, Button};
+
+pub const BUTTON_INVALID: i32 = -1;
+pub const BUTTON_A: i32 = 0;
+pub const BUTTON_B: i32 = 1;
+pub const BUTTON_X: i32 = 2;
+pub const BUTTON_Y: i32 = 3;
+pub const BUTTON_BACK: i32 = 4;
+pub const BUTTON_GUIDE: i32 = 5;
+pub const BUTTON_START: i32 = 6;
+pub const BUTTON_LEFTSTICK: i32 = 7;
+pub const BUTTON_RIGHTSTICK: i32 = 8;
+pub const BUTTON_LEFTSHOULDER: i32 = 9;
+pub const BUTTON_RIGHTSHOULDER: i32 = 10;
+pub const BUTTON_DPAD_UP: i32 = 11;
+pub const BUTTON_DPAD_DOWN: i32 = 12;
+pub const BUTTON_DPAD_LEFT: i32 = 13;
+pub const BUTTON_DPAD_RIGHT: i32 = 14;
+pub const BUTTON_MISC1: i32 = 15;
+pub const BUTTON_PADDLE1: i32 = 16;
+pub const BUTTON_PADDLE2: i32 = 17;
+pub const BUTTON_PADDLE3: i32 = 18;
+pub const BUTTON_PADDLE4: i32 = 19;
+pub const BUTTON_TOUCHPAD: i32 = 20;
+pub const BUTTON_MAX: i32 = 21;
+
+pub const AXIS_INVALID: i32 = -1;
+pub const AXIS_LEFTX: i32 = 0;
+pub const AXIS_LEFTY: i32 = 1;
+pub const AXIS_RIGHTX: i32 = 2;
+pub const AXIS_RIGHTY: i32 = 3;
+pub const AXIS_TRIGGERLEFT: i32 = 4;
+pub const AXIS_TRIGGERRIGHT: i32 = 5;
+pub const AXIS_MAX: i32 = 6;
+
+// Index is SDL button.
+static BUTTONS: [Button; 15] = [
+    Button::South,
+    Button::East,
+    Button::West,
+    Button::North,
+    Button::Select,
+    Button::Mode,
+    Button::Start,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::LeftTrigger,
+    Button::RightTrigger,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+
+impl Button {
+    /// Converts SDL `SDL_GameControllerButton` to `Button`. Returns `None` for buttons that
+    /// don't have equivalent in gilrs (misc, paddles and touchpad) and for invalid values.
+    pub fn from_sdl_button(button: i32) -> Option<Self> {
+        usize::try_from(button)
+            .ok()
+            .and_then(|idx| BUTTONS.get(idx))
+            .copied()
+    }
+
+    /// Converts `Button` to SDL `SDL_GameControllerButton`. Returns `None` for buttons that SDL
+    /// reports as axes (`LeftTrigger2` and `RightTrigger2`, see
+    /// [`to_sdl_axis()`](Self::to_sdl_axis)) or doesn't have (`C`, `Z` and `Unknown`).
+    pub fn to_sdl_button(self) -> Option<i32> {
+        BUTTONS
+            .iter()
+            .position(|&btn| btn == self)
+            .map(|idx| idx as i32)
+    }
+
+    /// Converts analog trigger to SDL `SDL_GameControllerAxis`. Returns `None` for other buttons.
+    pub fn to_sdl_axis(self) -> Option<i32> {
+        match self {
+            Button::LeftTrigger2 => Some(AXIS_TRIGGERLEFT),
+            Button::RightTrigger2 => Some(AXIS_TRIGGERRIGHT),
+            _ => None,
+        }
+    }
+}
+
+impl Axis {
+    /// Converts SDL `SDL_GameControllerAxis` to `Axis`. Trigger axes are converted to
+    /// `LeftZ` and `RightZ`, use [`AxisOrBtn::from_sdl_axis()`] to get buttons used by SDL
+    /// mappings instead.
+    pub fn from_sdl_axis(axis: i32) -> Option<Self> {
+        match axis {
+            AXIS_LEFTX => Some(Axis::LeftStickX),
+            AXIS_LEFTY => Some(Axis::LeftStickY),
+            AXIS_RIGHTX => Some(Axis::RightStickX),
+            AXIS_RIGHTY => Some(Axis::RightStickY),
+            AXIS_TRIGGERLEFT => Some(Axis::LeftZ),
+            AXIS_TRIGGERRIGHT => Some(Axis::RightZ),
+            _ => None,
+        }
+    }
+
+    /// Converts `Axis` to SDL `SDL_GameControllerAxis`. Returns `None` for `DPadX`, `DPadY` and
+    /// `Unknown`.
+    ///
+    /// Note that SDL's Y axes are positive when stick is pushed down, while gilrs' are positive
+    /// when pushed up, so values have to be negated.
+    pub fn to_sdl_axis(self) -> Option<i32> {
+        match self {
+            Axis::LeftStickX => Some(AXIS_LEFTX),
+            Axis::LeftStickY => Some(AXIS_LEFTY),
+            Axis::RightStickX => Some(AXIS_RIGHTX),
+            Axis::RightStickY => Some(AXIS_RIGHTY),
+            Axis::LeftZ => Some(AXIS_TRIGGERLEFT),
+            Axis::RightZ => Some(AXIS_TRIGGERRIGHT),
+            _ => None,
+        }
+    }
+}
+
+impl AxisOrBtn {
+    /// Converts SDL `SDL_GameControllerAxis` to element used by gilrs for gamepads with SDL
+    /// mappings. Trigger axes are converted to `Button::LeftTrigger2` and `Button::RightTrigger2`.
+    pub fn from_sdl_axis(axis: i32) -> Option<Self> {
+        match axis {
+            AXIS_TRIGGERLEFT => Some(AxisOrBtn::Btn(Button::LeftTrigger2)),
+            AXIS_TRIGGERRIGHT => Some(AxisOrBtn::Btn(Button::RightTrigger2)),
+            _ => Axis::from_sdl_axis(axis).map(AxisOrBtn::Axis),
+        }
+    }
+
+    /// Converts element to SDL `SDL_GameControllerAxis`, if SDL reports it as axis.
+    pub fn to_sdl_axis(self) -> Option<i32> {
+        match self {
+            AxisOrBtn::Axis(axis) => axis.to_sdl_axis(),
+            AxisOrBtn::Btn(btn) => btn.to_sdl_axis(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for sdl_btn in BUTTON_INVALID..BUTTON_MAX {
+            match Button::from_sdl_button(sdl_btn) {
+                Some(btn) => assert_eq!(btn.to_sdl_button(), Some(sdl_btn)),
+                None => assert!(!(0..BUTTON_MISC1).contains(&sdl_btn)),
+            }
+        }
+
+        for sdl_axis in AXIS_INVALID..AXIS_MAX {
+            match Axis::from_sdl_axis(sdl_axis) {
+                Some(axis) => assert_eq!(axis.to_sdl_axis(), Some(sdl_axis)),
+                None => assert_eq!(sdl_axis, AXIS_INVALID),
+            }
+            match AxisOrBtn::from_sdl_axis(sdl_axis) {
+                Some(el) => assert_eq!(el.to_sdl_axis(), Some(sdl_axis)),
+                None => assert_eq!(sdl_axis, AXIS_INVALID),
+            }
+        }
+
+        assert_eq!(Button::C.to_sdl_button(), None);
+        assert_eq!(Button::LeftTrigger2.to_sdl_button(), None);
+        assert_eq!(Axis::DPadX.to_sdl_axis(), None);
+    }
+}
@@ -5,28 +5,53 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use crate::ev::Code;
+use crate::ev::{AxisDirection, Code};
 
 use fnv::FnvHashMap;
 
 use std::collections::hash_map;
 use std::iter::Iterator;
-use std::time::SystemTime;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
 
 /// Cached gamepad state.
+///
+/// Cloning `GamepadState` is cheap, data is shared until one of the copies is modified. This
+/// makes it possible to keep snapshot of state from previous frame (see
+/// [`Gamepad::state_cloned()`](crate::Gamepad::state_cloned)).
+///
+/// There is no limit on number of buttons and axes. State is stored in maps keyed by [`Code`],
+/// which have capacity for all elements of gamepad when it connects, so updating state doesn't
+/// allocate unless snapshot is kept.
 #[derive(Clone, Debug)]
 pub struct GamepadState {
     // Indexed by EvCode (nec)
-    buttons: FnvHashMap<Code, ButtonData>,
+    buttons: Arc<FnvHashMap<Code, ButtonData>>,
     // Indexed by EvCode (nec)
-    axes: FnvHashMap<Code, AxisData>,
+    axes: Arc<FnvHashMap<Code, AxisData>>,
+    // Axes pressed according to `AxisPressed` events, indexed by EvCode (nec)
+    axes_pressed: Arc<FnvHashMap<Code, AxisDirection>>,
 }
 
 impl GamepadState {
-    pub(crate) fn new() -> Self {
+    /// Creates state with space for `buttons` buttons and `axes` axes.
+    pub(crate) fn with_capacity(buttons: usize, axes: usize) -> Self {
         GamepadState {
-            buttons: FnvHashMap::default(),
-            axes: FnvHashMap::default(),
+            buttons: Arc::new(FnvHashMap::with_capacity_and_hasher(
+                buttons,
+                Default::default(),
+            )),
+            axes: Arc::new(FnvHashMap::with_capacity_and_hasher(
+                axes,
+                Default::default(),
+            )),
+            axes_pressed: Arc::new(FnvHashMap::with_capacity_and_hasher(
+                axes,
+                Default::default(),
+            )),
         }
     }
 
@@ -69,6 +94,12 @@ impl GamepadState {
         self.axes.get(&axis)
     }
 
+    /// Returns direction in which axis is pressed, as reported by last `AxisPressed` or
+    /// `AxisReleased` event, or `None` if axis is not pressed.
+    pub fn axis_pressed(&self, axis: Code) -> Option<AxisDirection> {
+        self.axes_pressed.get(&axis).copied()
+    }
+
     pub(crate) fn set_btn_pressed(
         &mut self,
         btn: Code,
@@ -76,26 +107,40 @@ impl GamepadState {
         counter: u64,
         timestamp: SystemTime,
     ) {
-        let data = self.buttons.entry(btn).or_insert_with(|| {
-            ButtonData::new(
-                if pressed { 1.0 } else { 0.0 },
-                pressed,
-                false,
-                counter,
-                timestamp,
-            )
-        });
+        let data = Arc::make_mut(&mut self.buttons)
+            .entry(btn)
+            .or_insert_with(|| {
+                ButtonData::new(
+                    if pressed { 1.0 } else { 0.0 },
+                    pressed,
+                    false,
+                    counter,
+                    timestamp,
+                )
+            });
         data.is_pressed = pressed;
         data.is_repeating = false;
         data.counter = counter;
         data.last_event_ts = timestamp;
+        data.last_event_instant = Instant::now();
+        if pressed {
+            data.press_count = data.press_count.wrapping_add(1);
+        }
     }
 
     pub(crate) fn set_btn_repeating(&mut self, btn: Code, counter: u64, timestamp: SystemTime) {
-        let data = self
-            .buttons
+        let data = Arc::make_mut(&mut self.buttons)
             .entry(btn)
             .or_insert_with(|| ButtonData::new(1.0, true, true, counter, timestamp));
+        // `Repeat` schedules repeats relative to previous event, so advance monotonic time by the
+        // same offset instead of using time when event was processed. This keeps repeats evenly
+        // spaced even if events are processed late.
+        let now = Instant::now();
+        data.last_event_instant = timestamp
+            .duration_since(data.last_event_ts)
+            .ok()
+            .and_then(|offset| data.last_event_instant.checked_add(offset))
+            .map_or(now, |instant| instant.min(now));
         data.is_repeating = true;
         data.counter = counter;
         data.last_event_ts = timestamp;
@@ -108,20 +153,74 @@ impl GamepadState {
         counter: u64,
         timestamp: SystemTime,
     ) {
-        let data = self
-            .buttons
+        let data = Arc::make_mut(&mut self.buttons)
             .entry(btn)
             .or_insert_with(|| ButtonData::new(value, false, false, counter, timestamp));
         data.value = value;
         data.counter = counter;
         data.last_event_ts = timestamp;
+        data.last_event_instant = Instant::now();
     }
 
     pub(crate) fn update_axis(&mut self, axis: Code, data: AxisData) {
-        self.axes.insert(axis, data);
+        Arc::make_mut(&mut self.axes).insert(axis, data);
+    }
+
+    pub(crate) fn set_axis_pressed(&mut self, axis: Code, direction: Option<AxisDirection>) {
+        let axes_pressed = Arc::make_mut(&mut self.axes_pressed);
+        match direction {
+            Some(direction) => axes_pressed.insert(axis, direction),
+            None => axes_pressed.remove(&axis),
+        };
+    }
+
+    pub(crate) fn save(&self) -> SavedGamepadState {
+        SavedGamepadState {
+            buttons: self.buttons.iter().map(|(k, v)| (*k, *v)).collect(),
+            axes: self.axes.iter().map(|(k, v)| (*k, *v)).collect(),
+            axes_pressed: self.axes_pressed.iter().map(|(k, v)| (*k, *v)).collect(),
+        }
+    }
+
+    pub(crate) fn restore(&mut self, saved: &SavedGamepadState) {
+        self.clear();
+        Arc::make_mut(&mut self.buttons).extend(saved.buttons.iter().copied());
+        Arc::make_mut(&mut self.axes).extend(saved.axes.iter().copied());
+        Arc::make_mut(&mut self.axes_pressed).extend(saved.axes_pressed.iter().copied());
+    }
+
+    // Removes all data, but keeps allocated storage.
+    pub(crate) fn clear(&mut self) {
+        Arc::make_mut(&mut self.buttons).clear();
+        Arc::make_mut(&mut self.axes).clear();
+        Arc::make_mut(&mut self.axes_pressed).clear();
     }
 }
 
+/// State of all gamepads and value of counter, created by
+/// [`Gilrs::serialize_state()`](crate::Gilrs::serialize_state) and restored with
+/// [`Gilrs::apply_state()`](crate::Gilrs::apply_state).
+///
+/// Timers used by [`Repeat`](crate::ev::filter::Repeat) filter are part of button state, so they
+/// are also saved. If `serde-serialize` feature is enabled, `SavedState` can be serialized and
+/// deserialized, but, as with [`Code`], layout is platform-specific. Monotonic timestamps
+/// ([`ButtonData::instant()`]) are not serialized and are set to time of deserialization.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct SavedState {
+    pub(crate) counter: u64,
+    // Indexed by GamepadId
+    pub(crate) gamepads: Vec<SavedGamepadState>,
+}
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub(crate) struct SavedGamepadState {
+    buttons: Vec<(Code, ButtonData)>,
+    axes: Vec<(Code, AxisData)>,
+    axes_pressed: Vec<(Code, AxisDirection)>,
+}
+
 /// Iterator over `ButtonData`.
 pub struct ButtonDataIter<'a>(hash_map::Iter<'a, Code, ButtonData>);
 
@@ -146,9 +245,16 @@ impl<'a> Iterator for AxisDataIter<'a> {
 
 /// Information about button stored in `State`.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct ButtonData {
     last_event_ts: SystemTime,
+    // `Instant` can't be serialized, deserialized data gets time of deserialization.
+    #[cfg_attr(feature = "serde-serialize", serde(skip, default = "Instant::now"))]
+    last_event_instant: Instant,
     counter: u64,
+    // Missing in state serialized by older versions.
+    #[cfg_attr(feature = "serde-serialize", serde(default))]
+    press_count: u64,
     value: f32,
     is_pressed: bool,
     is_repeating: bool,
@@ -164,7 +270,9 @@ impl ButtonData {
     ) -> Self {
         ButtonData {
             last_event_ts: time,
+            last_event_instant: Instant::now(),
             counter,
+            press_count: 0,
             value,
             is_pressed: pressed,
             is_repeating: repeating,
@@ -192,13 +300,31 @@ impl ButtonData {
     }
 
     /// Returns when button state last changed.
+    ///
+    /// This is time reported with event, so it can go backwards if system clock is adjusted. Use
+    /// [`instant()`](Self::instant) to measure how long button is held.
     pub fn timestamp(&self) -> SystemTime {
         self.last_event_ts
     }
+
+    /// Returns monotonic time when button state last changed.
+    ///
+    /// Unlike [`timestamp()`](Self::timestamp), it is not affected by changes of system clock. It
+    /// is taken when event is processed by `Gilrs`, not when it was reported by device.
+    pub fn instant(&self) -> Instant {
+        self.last_event_instant
+    }
+
+    /// Returns how many times button was pressed since gamepad connected. Repeats don't increase
+    /// it.
+    pub fn press_count(&self) -> u64 {
+        self.press_count
+    }
 }
 
 /// Information about axis stored in `State`.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct AxisData {
     last_event_ts: SystemTime,
     last_event_c: u64,
@@ -228,3 +354,107 @@ impl AxisData {
         self.last_event_ts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AxisData, GamepadState};
+    use crate::ev::Code;
+    use gilrs_core::native_ev_codes as necs;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn save_restore() {
+        let now = SystemTime::now();
+        let btn = Code(necs::BTN_SOUTH);
+        let axis = Code(necs::AXIS_LSTICKX);
+
+        let mut state = GamepadState::with_capacity(1, 1);
+        state.set_btn_pressed(btn, true, 1, now);
+        state.set_btn_repeating(btn, 2, now);
+        state.update_axis(axis, AxisData::new(0.5, 3, now));
+        let saved = state.save();
+
+        state.set_btn_pressed(btn, false, 4, now);
+        state.update_axis(axis, AxisData::new(-0.5, 5, now));
+        state.restore(&saved);
+
+        let data = state.button_data(btn).unwrap();
+        assert!(data.is_pressed() && data.is_repeating());
+        assert_eq!(data.counter(), 2);
+        assert_eq!(state.value(axis), 0.5);
+        assert_eq!(state.axis_data(axis).unwrap().counter(), 3);
+    }
+
+    #[test]
+    fn clone_is_snapshot() {
+        let now = SystemTime::now();
+        let btn = Code(necs::BTN_SOUTH);
+
+        let mut state = GamepadState::with_capacity(1, 1);
+        state.set_btn_pressed(btn, true, 1, now);
+        let snapshot = state.clone();
+        state.set_btn_pressed(btn, false, 2, now);
+
+        assert!(snapshot.is_pressed(btn));
+        assert!(!state.is_pressed(btn));
+    }
+
+    #[test]
+    fn preallocated() {
+        let now = SystemTime::now();
+        let buttons = [
+            necs::BTN_SOUTH,
+            necs::BTN_EAST,
+            necs::BTN_NORTH,
+            necs::BTN_WEST,
+            necs::BTN_LT,
+            necs::BTN_RT,
+            necs::BTN_LT2,
+            necs::BTN_RT2,
+            necs::BTN_SELECT,
+            necs::BTN_START,
+            necs::BTN_MODE,
+            necs::BTN_LTHUMB,
+            necs::BTN_RTHUMB,
+            necs::BTN_DPAD_UP,
+            necs::BTN_DPAD_DOWN,
+            necs::BTN_DPAD_LEFT,
+            necs::BTN_DPAD_RIGHT,
+        ];
+        let mut state = GamepadState::with_capacity(buttons.len(), 0);
+        let capacity = state.buttons.capacity();
+        assert!(capacity >= buttons.len());
+
+        for btn in buttons {
+            state.set_btn_pressed(Code(btn), true, 1, now);
+        }
+        assert_eq!(state.buttons().count(), buttons.len());
+        assert_eq!(state.buttons.capacity(), capacity);
+
+        state.clear();
+        assert_eq!(state.buttons().count(), 0);
+        assert_eq!(state.buttons.capacity(), capacity);
+    }
+
+    #[test]
+    fn press_count_and_instant() {
+        let now = SystemTime::now();
+        let btn = Code(necs::BTN_SOUTH);
+
+        let mut state = GamepadState::with_capacity(1, 0);
+        state.set_btn_pressed(btn, true, 1, now);
+        let pressed = state.button_data(btn).unwrap().instant();
+
+        // Repeat advances monotonic time by its offset from previous event, not by time when it
+        // was processed.
+        state.set_btn_repeating(btn, 2, now);
+        assert_eq!(state.button_data(btn).unwrap().instant(), pressed);
+        // System clock went backwards.
+        state.set_btn_repeating(btn, 3, now - Duration::from_secs(60));
+        assert!(state.button_data(btn).unwrap().instant() >= pressed);
+
+        state.set_btn_pressed(btn, false, 4, now);
+        state.set_btn_pressed(btn, true, 5, now);
+        assert_eq!(state.button_data(btn).unwrap().press_count(), 2);
+    }
+}
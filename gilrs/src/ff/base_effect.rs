@@ -16,15 +16,48 @@ use super::time::Ticks;
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[non_exhaustive]
 pub enum BaseEffectType {
-    Weak { magnitude: u16 },
-    Strong { magnitude: u16 },
+    Weak {
+        magnitude: u16,
+    },
+    Strong {
+        magnitude: u16,
+    },
+    /// Effect for motor with given index. See
+    /// [`Gamepad::motor_count()`](../struct.Gamepad.html#method.motor_count) for motor order.
+    /// `Motor { motor: 0, .. }` is the same as `Strong` and `Motor { motor: 1, .. }` as `Weak`.
+    ///
+    /// If gamepad has fewer motors, effect is mixed into motor `motor % motor_count`, so, for
+    /// example, left trigger effect is played on strong motor of gamepads without trigger motors.
+    Motor {
+        motor: u8,
+        magnitude: u16,
+    },
 }
 
 impl BaseEffectType {
-    fn magnitude(&self) -> u16 {
+    pub(super) fn magnitude(&self) -> u16 {
         match *self {
             BaseEffectType::Weak { magnitude } => magnitude,
             BaseEffectType::Strong { magnitude } => magnitude,
+            BaseEffectType::Motor { magnitude, .. } => magnitude,
+        }
+    }
+
+    /// Returns the same kind of effect with changed magnitude.
+    pub(super) fn with_magnitude(self, magnitude: u16) -> Self {
+        match self {
+            BaseEffectType::Weak { .. } => BaseEffectType::Weak { magnitude },
+            BaseEffectType::Strong { .. } => BaseEffectType::Strong { magnitude },
+            BaseEffectType::Motor { motor, .. } => BaseEffectType::Motor { motor, magnitude },
+        }
+    }
+
+    /// Returns index of motor that this effect is played on.
+    pub(super) fn motor(&self) -> usize {
+        match *self {
+            BaseEffectType::Strong { .. } => 0,
+            BaseEffectType::Weak { .. } => 1,
+            BaseEffectType::Motor { motor, .. } => motor.into(),
         }
     }
 }
@@ -34,10 +67,7 @@ impl Mul<f32> for BaseEffectType {
 
     fn mul(self, rhs: f32) -> Self::Output {
         let mg = (self.magnitude() as f32 * rhs) as u16;
-        match self {
-            BaseEffectType::Weak { .. } => BaseEffectType::Weak { magnitude: mg },
-            BaseEffectType::Strong { .. } => BaseEffectType::Strong { magnitude: mg },
-        }
+        self.with_magnitude(mg)
     }
 }
 
@@ -7,12 +7,13 @@
 
 use std::error::Error;
 use std::ops::{AddAssign, Mul};
-use std::{fmt, mem, u16};
+use std::{fmt, mem};
 
 use crate::{Event, EventType, GamepadId};
 
 use super::base_effect::{BaseEffect, BaseEffectType};
 use super::time::{Repeat, Ticks};
+use super::PlaybackState;
 
 use vec_map::VecMap;
 
@@ -274,7 +275,13 @@ impl EffectSource {
         }
     }
 
-    pub(super) fn combine_base_effects(&mut self, ticks: Ticks, actor_pos: [f32; 3]) -> Magnitude {
+    /// Returns magnitude of motors of device with `motor_count` motors, placed at `actor_pos`.
+    pub(super) fn combine_base_effects(
+        &mut self,
+        ticks: Ticks,
+        actor_pos: [f32; 3],
+        motor_count: usize,
+    ) -> Magnitude {
         let ticks = match self.state {
             EffectState::Playing { since } => {
                 debug_assert!(ticks >= since);
@@ -304,61 +311,104 @@ impl EffectSource {
 
         let mut final_magnitude = Magnitude::zero();
         for effect in &self.base_effects {
-            match effect.magnitude_at(ticks) {
-                BaseEffectType::Strong { magnitude } => {
-                    final_magnitude.strong = final_magnitude.strong.saturating_add(magnitude)
-                }
-                BaseEffectType::Weak { magnitude } => {
-                    final_magnitude.weak = final_magnitude.weak.saturating_add(magnitude)
-                }
-            };
+            final_magnitude.add(effect.magnitude_at(ticks), motor_count);
         }
         final_magnitude * attenuation
     }
 
+    /// Returns base effect with given index. Changes to it are applied starting from next tick,
+    /// without restarting playback.
+    pub(super) fn base_effect_mut(&mut self, index: usize) -> Option<&mut BaseEffect> {
+        self.base_effects.get_mut(index)
+    }
+
     pub(super) fn flush_completion_events(&mut self) -> Vec<Event> {
         mem::take(&mut self.completion_events)
     }
+
+    pub(super) fn playback_state(&self, ticks: Ticks) -> PlaybackState {
+        let elapsed = match self.state {
+            EffectState::Playing { since } => ticks.checked_sub(since).unwrap_or_default(),
+            EffectState::Stopped => return PlaybackState::Stopped,
+        };
+
+        let start = self.base_effects.iter().map(|e| e.scheduling.after).min();
+        if let Some(starts_in) = start.and_then(|start| start.checked_sub(elapsed)) {
+            if starts_in > Ticks(0) {
+                return PlaybackState::Scheduled {
+                    starts_in: starts_in.as_duration(),
+                };
+            }
+        }
+
+        let remaining = match self.repeat {
+            Repeat::For(max_dur) => Some(max_dur.checked_sub(elapsed).unwrap_or_default()),
+            Repeat::Infinitely => None,
+        };
+
+        PlaybackState::Playing {
+            remaining: remaining.map(Ticks::as_duration),
+        }
+    }
 }
 
-/// (strong, weak) pair.
+/// Maximal number of motors that effects are played on. Devices with more motors use only the
+/// first `MAX_MOTORS` of them.
+pub(super) const MAX_MOTORS: usize = 4;
+
+/// Magnitude of every motor, in order: strong, weak, left trigger, right trigger.
 #[derive(Copy, Clone, Debug)]
 pub(super) struct Magnitude {
-    pub strong: u16,
-    pub weak: u16,
+    pub motors: [u16; MAX_MOTORS],
 }
 
 impl Magnitude {
     pub fn zero() -> Self {
-        Magnitude { strong: 0, weak: 0 }
+        Magnitude {
+            motors: [0; MAX_MOTORS],
+        }
+    }
+
+    /// Adds magnitude of `effect` to its motor. Effects for motors that device with
+    /// `motor_count` motors doesn't have are mixed into motor `motor % motor_count`.
+    fn add(&mut self, effect: BaseEffectType, motor_count: usize) {
+        let motor_count = motor_count.min(MAX_MOTORS);
+        if motor_count == 0 {
+            return;
+        }
+
+        let motor = &mut self.motors[effect.motor() % motor_count];
+        *motor = motor.saturating_add(effect.magnitude());
+    }
+
+    /// Returns magnitudes of the first `motor_count` motors.
+    pub fn motors(&self, motor_count: usize) -> &[u16] {
+        &self.motors[..motor_count.min(MAX_MOTORS)]
     }
 }
 
 impl Mul<f32> for Magnitude {
     type Output = Magnitude;
 
-    fn mul(self, rhs: f32) -> Self::Output {
+    fn mul(mut self, rhs: f32) -> Self::Output {
         debug_assert!(rhs >= 0.0);
-        let strong = self.strong as f32 * rhs;
-        let strong = if strong > u16::MAX as f32 {
-            u16::MAX
-        } else {
-            strong as u16
-        };
-        let weak = self.weak as f32 * rhs;
-        let weak = if weak > u16::MAX as f32 {
-            u16::MAX
-        } else {
-            weak as u16
-        };
-        Magnitude { strong, weak }
+        for motor in self.motors.iter_mut() {
+            let value = *motor as f32 * rhs;
+            *motor = if value > u16::MAX as f32 {
+                u16::MAX
+            } else {
+                value as u16
+            };
+        }
+        self
     }
 }
 
 impl AddAssign for Magnitude {
     fn add_assign(&mut self, rhs: Magnitude) {
-        self.strong = self.strong.saturating_add(rhs.strong);
-        self.weak = self.weak.saturating_add(rhs.weak);
+        for (motor, rhs) in self.motors.iter_mut().zip(rhs.motors) {
+            *motor = motor.saturating_add(rhs);
+        }
     }
 }
 
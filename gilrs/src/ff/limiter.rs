@@ -0,0 +1,135 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::time::Ticks;
+
+use std::collections::VecDeque;
+
+/// Limits average power of force feedback motors, so they can't run at full power for long time.
+///
+/// Power of each tick is the magnitude of the strongest motor in range [0.0, 1.0]. When average
+/// power over last `window` ticks would exceed `max_duty`, all motors are scaled down.
+///
+/// See [`Gamepad::set_ff_duty_limit()`](../struct.Gamepad.html#method.set_ff_duty_limit).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DutyCycleLimit {
+    /// Maximum average power in range [0.0, 1.0].
+    pub max_duty: f32,
+    /// Length of time over which average power is computed.
+    pub window: Ticks,
+}
+
+impl DutyCycleLimit {
+    /// Creates new limit. `max_duty` is clamped to range [0.0, 1.0].
+    pub fn new(max_duty: f32, window: Ticks) -> Self {
+        DutyCycleLimit {
+            max_duty: max_duty.clamp(0.0, 1.0),
+            window,
+        }
+    }
+}
+
+impl Default for DutyCycleLimit {
+    /// At most 50% of full power over 5 seconds.
+    fn default() -> Self {
+        DutyCycleLimit::new(0.5, Ticks::from_ms(5000))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct DutyLimiter {
+    limit: DutyCycleLimit,
+    history: VecDeque<f32>,
+    sum: f32,
+    limited: bool,
+}
+
+impl DutyLimiter {
+    pub fn new(limit: DutyCycleLimit) -> Self {
+        DutyLimiter {
+            limit,
+            history: VecDeque::with_capacity(limit.window.0 as usize),
+            sum: 0.0,
+            limited: false,
+        }
+    }
+
+    /// Scales down `motors` if they would exceed the limit. Should be called once per tick.
+    pub fn apply(&mut self, motors: &mut [u16]) {
+        let window = self.limit.window.0.max(1) as usize;
+        while self.history.len() >= window {
+            self.sum -= self.history.pop_front().unwrap_or(0.0);
+        }
+
+        let budget = (self.limit.max_duty * window as f32 - self.sum).max(0.0);
+        let power = motors.iter().copied().max().unwrap_or(0) as f32 / u16::MAX as f32;
+
+        self.limited = power > budget;
+        let power = if self.limited {
+            let scale = budget / power;
+            for motor in motors.iter_mut() {
+                *motor = (*motor as f32 * scale) as u16;
+            }
+
+            budget
+        } else {
+            power
+        };
+
+        self.history.push_back(power);
+        self.sum = (self.sum + power).max(0.0);
+    }
+
+    /// Returns `true` if motors were scaled down in last tick.
+    pub fn is_limited(&self) -> bool {
+        self.limited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DutyCycleLimit, DutyLimiter};
+    use crate::ff::Ticks;
+
+    #[test]
+    fn sustained_full_power_is_limited() {
+        let window = 20;
+        let mut limiter = DutyLimiter::new(DutyCycleLimit::new(0.5, Ticks(window)));
+        let mut total = 0.0;
+
+        for tick in 0..window * 5 {
+            let mut motors = [u16::MAX, u16::MAX / 2];
+            limiter.apply(&mut motors);
+
+            if tick < window / 2 {
+                // Short bursts are not affected.
+                assert_eq!(motors, [u16::MAX, u16::MAX / 2]);
+                assert!(!limiter.is_limited());
+            }
+            if tick >= window * 4 {
+                total += motors[0] as f32 / u16::MAX as f32;
+            }
+        }
+
+        assert!(limiter.is_limited());
+        let average = total / window as f32;
+        assert!(average <= 0.51, "{}", average);
+        assert!(average >= 0.45, "{}", average);
+    }
+
+    #[test]
+    fn weak_rumble_is_not_limited() {
+        let mut limiter = DutyLimiter::new(DutyCycleLimit::new(0.5, Ticks(20)));
+
+        for _ in 0..100 {
+            let mut motors = [u16::MAX / 3, 0];
+            limiter.apply(&mut motors);
+            assert_eq!(motors, [u16::MAX / 3, 0]);
+            assert!(!limiter.is_limited());
+        }
+    }
+}
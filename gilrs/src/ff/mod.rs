@@ -50,15 +50,23 @@
 //! effect.play().unwrap();
 //! ```
 //!
+//! Module [`presets`] contains ready-made effects, like weapon recoil or explosion.
+//!
+//! To protect motors of cheap gamepads, average power can be limited with
+//! [`Gamepad::set_ff_duty_limit()`](../struct.Gamepad.html#method.set_ff_duty_limit).
+//!
 //! See [`examples/ff_pos.rs`](https://gitlab.com/gilrs-project/gilrs/blob/v0.11.0/examples/ff_pos.rs) for
 //! more advanced example.
 mod base_effect;
 mod effect_source;
+mod limiter;
+pub mod presets;
 pub(crate) mod server;
 mod time;
 
 pub use self::base_effect::{BaseEffect, BaseEffectType, Envelope, Replay};
 pub use self::effect_source::{DistanceModel, DistanceModelError};
+pub use self::limiter::DutyCycleLimit;
 #[allow(unused_imports)]
 pub(crate) use self::time::TICK_DURATION;
 pub use self::time::{Repeat, Ticks};
@@ -66,10 +74,11 @@ pub use self::time::{Repeat, Ticks};
 use std::error::Error as StdError;
 use std::hash::{Hash, Hasher};
 use std::sync::mpsc::{SendError, Sender};
+use std::time::Duration;
 use std::{f32, fmt};
 
 use self::effect_source::EffectSource;
-use crate::ff::server::Message;
+use crate::ff::server::{EffectStates, Message};
 use crate::gamepad::{Gamepad, GamepadId, Gilrs};
 use crate::utils;
 
@@ -85,6 +94,9 @@ use vec_map::VecMap;
 pub struct Effect {
     id: usize,
     tx: Sender<Message>,
+    states: EffectStates,
+    // Number of base effects, used to validate indices before sending commands.
+    base_effects: usize,
 }
 
 impl PartialEq for Effect {
@@ -107,6 +119,8 @@ impl Clone for Effect {
         Effect {
             id: self.id,
             tx: self.tx.clone(),
+            states: self.states.clone(),
+            base_effects: self.base_effects,
         }
     }
 }
@@ -131,6 +145,24 @@ impl Effect {
         Ok(())
     }
 
+    /// Returns ID of the effect. Clones of `Effect` have the same ID.
+    pub fn id(&self) -> EffectId {
+        EffectId(self.id)
+    }
+
+    /// Returns playback state of the effect.
+    ///
+    /// State is updated by force feedback thread every tick (50 ms), so changes made by `play()`
+    /// and `stop()` are not visible immediately.
+    pub fn state(&self) -> PlaybackState {
+        self.states
+            .lock()
+            .unwrap()
+            .effects
+            .get(self.id)
+            .map_or(PlaybackState::Stopped, |status| status.state)
+    }
+
     /// Changes gamepads that are associated with effect. Effect will be only played on gamepads
     /// from last call to this function.
     ///
@@ -224,6 +256,60 @@ impl Effect {
 
         Ok(())
     }
+
+    /// Replaces base effect with given index (in order in which they were added to
+    /// `EffectBuilder`).
+    ///
+    /// Unlike rebuilding the effect, this doesn't interrupt playback. The change is applied
+    /// starting from next force feedback tick.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidIndex` if effect doesn't have base effect with given index.
+    pub fn set_base_effect(&self, index: usize, effect: BaseEffect) -> Result<(), Error> {
+        self.check_index(index)?;
+        self.tx.send(Message::SetBaseEffect {
+            id: self.id,
+            index,
+            effect,
+        })?;
+
+        Ok(())
+    }
+
+    /// Changes magnitude of base effect with given index, keeping its kind (strong or weak),
+    /// scheduling and envelope. See [`set_base_effect()`](#method.set_base_effect).
+    pub fn set_magnitude(&self, index: usize, magnitude: u16) -> Result<(), Error> {
+        self.check_index(index)?;
+        self.tx.send(Message::SetMagnitude {
+            id: self.id,
+            index,
+            magnitude,
+        })?;
+
+        Ok(())
+    }
+
+    /// Changes scheduling (playback duration and period) of base effect with given index. See
+    /// [`set_base_effect()`](#method.set_base_effect).
+    pub fn set_scheduling(&self, index: usize, scheduling: Replay) -> Result<(), Error> {
+        self.check_index(index)?;
+        self.tx.send(Message::SetScheduling {
+            id: self.id,
+            index,
+            scheduling,
+        })?;
+
+        Ok(())
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), Error> {
+        if index < self.base_effects {
+            Ok(())
+        } else {
+            Err(Error::InvalidIndex(index))
+        }
+    }
 }
 
 /// Creates new [`Effect`](struct.Effect.html).
@@ -335,7 +421,12 @@ impl EffectBuilder {
             id,
             effect: Box::new(effect),
         })?;
-        Ok(Effect { id, tx: tx.clone() })
+        Ok(Effect {
+            id,
+            tx: tx.clone(),
+            states: gilrs.ff_states().clone(),
+            base_effects: self.base_effects.len(),
+        })
     }
 }
 
@@ -345,6 +436,188 @@ impl Default for EffectBuilder {
     }
 }
 
+/// Collects commands for multiple effects and submits them at once. Created with
+/// [`Gilrs::ff_batch()`].
+///
+/// Commands sent with methods of [`Effect`] are applied by force feedback thread as they arrive,
+/// so when many effects change in the same frame, some of the changes may be applied one tick
+/// later than others. Commands in a batch are sent in one message and applied together, so device
+/// gets only one update with their combined result. Commands are applied in the same order as
+/// they were added.
+///
+/// Batch is discarded if it's dropped without calling [`submit()`](Self::submit).
+///
+/// ```
+/// use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder};
+/// use gilrs::Gilrs;
+///
+/// let mut gilrs = Gilrs::new().unwrap();
+/// let ids: Vec<_> = gilrs.gamepads().filter(|(_, g)| g.is_ff_supported()).map(|(id, _)| id).collect();
+/// let hit = EffectBuilder::new()
+///     .add_effect(BaseEffect {
+///         kind: BaseEffectType::Weak { magnitude: 20_000 },
+///         ..Default::default()
+///     })
+///     .gamepads(&ids)
+///     .finish(&mut gilrs)
+///     .unwrap();
+/// let engine = EffectBuilder::new().gamepads(&ids).finish(&mut gilrs).unwrap();
+///
+/// let mut batch = gilrs.ff_batch();
+/// batch.play(&hit).set_gain(&engine, 0.5);
+/// batch.submit().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FfBatch {
+    tx: Sender<Message>,
+    messages: Vec<Message>,
+    // First error found when adding commands, returned from `submit()`.
+    error: Option<Error>,
+}
+
+impl FfBatch {
+    pub(crate) fn new(tx: Sender<Message>) -> Self {
+        FfBatch {
+            tx,
+            messages: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn push_indexed(&mut self, effect: &Effect, index: usize, message: Message) -> &mut Self {
+        match effect.check_index(index) {
+            Ok(()) => self.messages.push(message),
+            Err(e) => {
+                self.error.get_or_insert(e);
+            }
+        }
+        self
+    }
+
+    /// Adds command that plays `effect`. See [`Effect::play()`].
+    pub fn play(&mut self, effect: &Effect) -> &mut Self {
+        self.messages.push(Message::Play { id: effect.id });
+        self
+    }
+
+    /// Adds command that stops `effect`. See [`Effect::stop()`].
+    pub fn stop(&mut self, effect: &Effect) -> &mut Self {
+        self.messages.push(Message::Stop { id: effect.id });
+        self
+    }
+
+    /// Adds command that changes gain of `effect`. See [`Effect::set_gain()`].
+    pub fn set_gain(&mut self, effect: &Effect, gain: f32) -> &mut Self {
+        let gain = utils::clamp(gain, 0.0, f32::MAX);
+        self.messages.push(Message::SetGain {
+            id: effect.id,
+            gain,
+        });
+        self
+    }
+
+    /// Adds command that replaces base effect of `effect`. See [`Effect::set_base_effect()`].
+    pub fn set_base_effect(
+        &mut self,
+        effect: &Effect,
+        index: usize,
+        base_effect: BaseEffect,
+    ) -> &mut Self {
+        self.push_indexed(
+            effect,
+            index,
+            Message::SetBaseEffect {
+                id: effect.id,
+                index,
+                effect: base_effect,
+            },
+        )
+    }
+
+    /// Adds command that changes magnitude of base effect of `effect`. See
+    /// [`Effect::set_magnitude()`].
+    pub fn set_magnitude(&mut self, effect: &Effect, index: usize, magnitude: u16) -> &mut Self {
+        self.push_indexed(
+            effect,
+            index,
+            Message::SetMagnitude {
+                id: effect.id,
+                index,
+                magnitude,
+            },
+        )
+    }
+
+    /// Returns number of commands in the batch.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns `true` if batch has no commands.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Sends all commands to force feedback thread. Does nothing if batch is empty.
+    ///
+    /// Effects have to be alive until batch is submitted, commands for effects that were dropped
+    /// are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidIndex` without sending any command if index of base effect in one of
+    /// commands was invalid.
+    pub fn submit(self) -> Result<(), Error> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        if !self.messages.is_empty() {
+            self.tx.send(Message::Batch {
+                messages: self.messages,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Identifies [`Effect`]. See [`Effect::id()`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct EffectId(usize);
+
+impl EffectId {
+    pub(crate) fn new(id: usize) -> Self {
+        EffectId(id)
+    }
+}
+
+impl From<EffectId> for usize {
+    fn from(x: EffectId) -> usize {
+        x.0
+    }
+}
+
+impl fmt::Display for EffectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Playback state of [`Effect`]. See [`Effect::state()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PlaybackState {
+    /// Effect is playing. `remaining` is time left until effect stops, `None` if effect repeats
+    /// until it's stopped. Effect may be silent at the moment, for example between repetitions.
+    Playing { remaining: Option<Duration> },
+    /// `Effect::play()` was called, but none of base effects has started yet, see
+    /// [`Replay::after`].
+    Scheduled { starts_in: Duration },
+    /// Effect is not playing.
+    Stopped,
+}
+
 /// Basic error type in force feedback module.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[non_exhaustive]
@@ -355,6 +628,8 @@ pub enum Error {
     Disconnected(GamepadId),
     /// Distance model is invalid.
     InvalidDistanceModel(DistanceModelError),
+    /// Effect doesn't have base effect with this index.
+    InvalidIndex(usize),
     /// The other end of channel was dropped.
     SendFailed,
     /// Unexpected error has occurred
@@ -386,6 +661,10 @@ impl fmt::Display for Error {
                 sbuf.as_ref()
             }
             Error::InvalidDistanceModel(_) => "distance model is invalid",
+            Error::InvalidIndex(index) => {
+                sbuf = format!("effect doesn't have base effect with index {}.", index);
+                sbuf.as_ref()
+            }
             Error::SendFailed => "receiving end of a channel is disconnected.",
             Error::Other => "unespected error has occurred.",
         };
@@ -439,6 +718,143 @@ mod tests {
         assert_eq!(env.at(Ticks(40), dur), 1.0);
     }
 
+    #[test]
+    fn update_playing_effect() {
+        let base = BaseEffect {
+            kind: BaseEffectType::Strong { magnitude: 1000 },
+            scheduling: Replay {
+                play_for: Ticks(10),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut effect = EffectSource::new(
+            vec![base],
+            VecMap::new(),
+            Repeat::Infinitely,
+            DistanceModel::None,
+            [0.0, 0.0, 0.0],
+            1.0,
+        );
+        effect.state = effect_source::EffectState::Playing { since: Ticks(0) };
+
+        assert_eq!(
+            effect.combine_base_effects(Ticks(5), [0.0; 3], 2).motors[0],
+            1000
+        );
+
+        let base = effect.base_effect_mut(0).unwrap();
+        base.kind = base.kind.with_magnitude(2000);
+        assert_eq!(
+            effect.combine_base_effects(Ticks(6), [0.0; 3], 2).motors[0],
+            2000
+        );
+
+        effect.base_effect_mut(0).unwrap().scheduling = Replay {
+            after: Ticks(0),
+            play_for: Ticks(5),
+            with_delay: Ticks(10),
+        };
+        assert_eq!(
+            effect.combine_base_effects(Ticks(7), [0.0; 3], 2).motors[0],
+            0
+        );
+
+        assert!(effect.base_effect_mut(1).is_none());
+    }
+
+    #[test]
+    fn playback_state() {
+        let base = BaseEffect {
+            scheduling: Replay {
+                after: Ticks(4),
+                play_for: Ticks(10),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut effect = EffectSource::new(
+            vec![base],
+            VecMap::new(),
+            Repeat::For(Ticks(20)),
+            DistanceModel::None,
+            [0.0, 0.0, 0.0],
+            1.0,
+        );
+        assert_eq!(effect.playback_state(Ticks(0)), PlaybackState::Stopped);
+
+        effect.state = effect_source::EffectState::Playing { since: Ticks(10) };
+        assert_eq!(
+            effect.playback_state(Ticks(11)),
+            PlaybackState::Scheduled {
+                starts_in: Ticks(3).as_duration()
+            }
+        );
+        assert_eq!(
+            effect.playback_state(Ticks(15)),
+            PlaybackState::Playing {
+                remaining: Some(Ticks(15).as_duration())
+            }
+        );
+
+        effect.repeat = Repeat::Infinitely;
+        assert_eq!(
+            effect.playback_state(Ticks(15)),
+            PlaybackState::Playing { remaining: None }
+        );
+    }
+
+    #[test]
+    fn motor_mixing() {
+        let mut effect = EffectSource::new(
+            vec![
+                BaseEffect {
+                    kind: BaseEffectType::Strong { magnitude: 1000 },
+                    ..Default::default()
+                },
+                BaseEffect {
+                    kind: BaseEffectType::Motor {
+                        motor: 2,
+                        magnitude: 300,
+                    },
+                    ..Default::default()
+                },
+                BaseEffect {
+                    kind: BaseEffectType::Motor {
+                        motor: 3,
+                        magnitude: 400,
+                    },
+                    ..Default::default()
+                },
+                BaseEffect {
+                    kind: BaseEffectType::Motor {
+                        motor: 5,
+                        magnitude: 50,
+                    },
+                    ..Default::default()
+                },
+            ],
+            VecMap::new(),
+            Repeat::Infinitely,
+            DistanceModel::None,
+            [0.0, 0.0, 0.0],
+            1.0,
+        );
+        effect.state = effect_source::EffectState::Playing { since: Ticks(0) };
+        let mut motors = |count| {
+            effect
+                .combine_base_effects(Ticks(0), [0.0; 3], count)
+                .motors(count)
+                .to_vec()
+        };
+
+        assert_eq!(motors(4), [1000, 50, 300, 400]);
+        assert_eq!(motors(3), [1400, 0, 350]);
+        assert_eq!(motors(2), [1300, 450]);
+        assert_eq!(motors(1), [1750]);
+        assert!(motors(0).is_empty());
+    }
+
     #[test]
     fn replay() {
         let replay = Replay {
@@ -455,4 +871,68 @@ mod tests {
         assert_eq!(replay.at(Ticks(60)), 0.0);
         assert_eq!(replay.at(Ticks(70)), 0.0);
     }
+
+    #[test]
+    fn batch() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let effect = Effect {
+            id: 3,
+            tx: tx.clone(),
+            states: Default::default(),
+            base_effects: 1,
+        };
+
+        let mut batch = FfBatch::new(tx.clone());
+        batch.play(&effect).set_gain(&effect, -1.0).stop(&effect);
+        assert_eq!(batch.len(), 3);
+        batch.submit().unwrap();
+        FfBatch::new(tx).submit().unwrap();
+
+        match rx.try_recv().unwrap() {
+            Message::Batch { messages } => match messages.as_slice() {
+                [Message::Play { id: 3 }, Message::SetGain { id: 3, gain }, Message::Stop { id: 3 }] =>
+                {
+                    assert_eq!(*gain, 0.0)
+                }
+                m => panic!("Unexpected messages {:?}", m),
+            },
+            m => panic!("Unexpected message {:?}", m),
+        }
+        // Empty batch is not sent.
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn invalid_base_effect_index() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let effect = Effect {
+            id: 3,
+            tx: tx.clone(),
+            states: Default::default(),
+            base_effects: 2,
+        };
+
+        assert_eq!(effect.set_magnitude(1, 100), Ok(()));
+        assert_eq!(effect.set_magnitude(2, 100), Err(Error::InvalidIndex(2)));
+        assert_eq!(
+            effect.set_base_effect(5, BaseEffect::default()),
+            Err(Error::InvalidIndex(5))
+        );
+        assert_eq!(
+            effect.set_scheduling(2, Replay::default()),
+            Err(Error::InvalidIndex(2))
+        );
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(Message::SetMagnitude { index: 1, .. })
+        ));
+        assert!(rx.try_recv().is_err());
+
+        let mut batch = FfBatch::new(tx);
+        batch
+            .set_magnitude(&effect, 0, 100)
+            .set_magnitude(&effect, 3, 100);
+        assert_eq!(batch.submit(), Err(Error::InvalidIndex(3)));
+        assert!(rx.try_recv().is_err());
+    }
 }
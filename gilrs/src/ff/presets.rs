@@ -0,0 +1,211 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Ready-made force feedback effects.
+//!
+//! Every function returns [`EffectBuilder`] with base effects and repeat already set, so only
+//! gamepads have to be added before calling `finish()`. `intensity` scales strength of the effect
+//! and is clamped to range [0.0, 1.0].
+//!
+//! ```rust
+//! use gilrs::Gilrs;
+//! use gilrs::ff::presets;
+//!
+//! let mut gilrs = Gilrs::new().unwrap();
+//! let ids = gilrs.gamepads().map(|(id, _)| id).collect::<Vec<_>>();
+//!
+//! let effect = presets::recoil_large(0.8).gamepads(&ids).finish(&mut gilrs).unwrap();
+//! effect.play().unwrap();
+//! ```
+
+use super::{BaseEffect, BaseEffectType, EffectBuilder, Envelope, Repeat, Replay, Ticks};
+use crate::utils;
+
+fn magnitude(strength: f32, intensity: f32) -> u16 {
+    (u16::MAX as f32 * strength * utils::clamp(intensity, 0.0, 1.0)) as u16
+}
+
+fn strong(strength: f32, intensity: f32, scheduling: Replay, envelope: Envelope) -> BaseEffect {
+    BaseEffect {
+        kind: BaseEffectType::Strong {
+            magnitude: magnitude(strength, intensity),
+        },
+        scheduling,
+        envelope,
+    }
+}
+
+fn weak(strength: f32, intensity: f32, scheduling: Replay, envelope: Envelope) -> BaseEffect {
+    BaseEffect {
+        kind: BaseEffectType::Weak {
+            magnitude: magnitude(strength, intensity),
+        },
+        scheduling,
+        envelope,
+    }
+}
+
+fn once(play_for: u32) -> Replay {
+    Replay {
+        play_for: Ticks::from_ms(play_for),
+        ..Default::default()
+    }
+}
+
+fn fade_out(fade_length: u32) -> Envelope {
+    Envelope {
+        fade_length: Ticks::from_ms(fade_length),
+        ..Default::default()
+    }
+}
+
+/// Very short, light tick, suitable for navigating menus.
+pub fn ui_tick(intensity: f32) -> EffectBuilder {
+    let mut builder = EffectBuilder::new();
+    builder
+        .add_effect(weak(0.5, intensity, once(50), Envelope::default()))
+        .repeat(Repeat::For(Ticks::from_ms(50)));
+
+    builder
+}
+
+/// Short, sharp kick of small weapon.
+pub fn recoil_small(intensity: f32) -> EffectBuilder {
+    let mut builder = EffectBuilder::new();
+    builder
+        .add_effect(weak(0.8, intensity, once(100), Envelope::default()))
+        .add_effect(strong(0.3, intensity, once(50), Envelope::default()))
+        .repeat(Repeat::For(Ticks::from_ms(100)));
+
+    builder
+}
+
+/// Heavy kick of large weapon that quickly fades out.
+pub fn recoil_large(intensity: f32) -> EffectBuilder {
+    let mut builder = EffectBuilder::new();
+    builder
+        .add_effect(strong(1.0, intensity, once(200), fade_out(100)))
+        .add_effect(weak(0.7, intensity, once(100), Envelope::default()))
+        .repeat(Repeat::For(Ticks::from_ms(200)));
+
+    builder
+}
+
+/// Strong blast followed by long rumble that fades out.
+pub fn explosion(intensity: f32) -> EffectBuilder {
+    let mut builder = EffectBuilder::new();
+    builder
+        .add_effect(strong(1.0, intensity, once(800), fade_out(600)))
+        .add_effect(weak(1.0, intensity, once(300), fade_out(200)))
+        .repeat(Repeat::For(Ticks::from_ms(800)));
+
+    builder
+}
+
+/// Two beats ("lub-dub") repeated about 67 times per minute, until effect is stopped.
+pub fn heartbeat(intensity: f32) -> EffectBuilder {
+    let period = Ticks::from_ms(900);
+    let beat = Ticks::from_ms(100);
+    let mut builder = EffectBuilder::new();
+    builder
+        .add_effect(strong(
+            0.9,
+            intensity,
+            Replay {
+                after: Ticks::from_ms(0),
+                play_for: beat,
+                with_delay: period - beat,
+            },
+            Envelope::default(),
+        ))
+        .add_effect(strong(
+            0.55,
+            intensity,
+            Replay {
+                after: Ticks::from_ms(250),
+                play_for: beat,
+                with_delay: period - beat,
+            },
+            Envelope::default(),
+        ))
+        .repeat(Repeat::Infinitely);
+
+    builder
+}
+
+/// Low, constant rumble with slight pulsation of idling engine, played until effect is stopped.
+pub fn engine_idle(intensity: f32) -> EffectBuilder {
+    let pulse = Ticks::from_ms(50);
+    let mut builder = EffectBuilder::new();
+    builder
+        .add_effect(weak(0.25, intensity, once(50), Envelope::default()))
+        .add_effect(strong(
+            0.15,
+            intensity,
+            Replay {
+                after: Ticks::from_ms(0),
+                play_for: pulse,
+                with_delay: pulse * 2,
+            },
+            Envelope::default(),
+        ))
+        .repeat(Repeat::Infinitely);
+
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presets() -> Vec<fn(f32) -> EffectBuilder> {
+        vec![
+            ui_tick,
+            recoil_small,
+            recoil_large,
+            explosion,
+            heartbeat,
+            engine_idle,
+        ]
+    }
+
+    fn max_magnitude(builder: &EffectBuilder, duration: Ticks) -> u16 {
+        let mut max = 0;
+        for t in 0..duration.0 {
+            for effect in &builder.base_effects {
+                // Also checks debug assertions in envelope.
+                let magnitude = match effect.magnitude_at(Ticks(t)) {
+                    BaseEffectType::Weak { magnitude }
+                    | BaseEffectType::Strong { magnitude }
+                    | BaseEffectType::Motor { magnitude, .. } => magnitude,
+                };
+                max = max.max(magnitude);
+            }
+        }
+
+        max
+    }
+
+    #[test]
+    fn intensity() {
+        for preset in presets() {
+            let full = preset(1.0);
+            let duration = match full.repeat {
+                Repeat::For(t) => t,
+                Repeat::Infinitely => Ticks::from_ms(2000),
+            };
+
+            assert!(max_magnitude(&full, duration) > 0);
+            assert_eq!(max_magnitude(&preset(0.0), duration), 0);
+            assert_eq!(
+                max_magnitude(&preset(2.0), duration),
+                max_magnitude(&full, duration)
+            );
+            assert!(max_magnitude(&preset(0.5), duration) < max_magnitude(&full, duration));
+        }
+    }
+}
@@ -5,12 +5,16 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use super::base_effect::{BaseEffect, Replay};
 use super::effect_source::{DistanceModel, EffectSource, EffectState, Magnitude};
+use super::limiter::{DutyCycleLimit, DutyLimiter};
 use super::time::{Repeat, Ticks, TICK_DURATION};
+use super::PlaybackState;
 
 use std::ops::{Deref, DerefMut};
-use std::sync::mpsc::{self, Receiver, Sender};
-use std::thread;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use crate::gamepad::GamepadId;
@@ -48,6 +52,10 @@ pub(crate) enum Message {
         id: usize,
         position: [f32; 3],
     },
+    SetDutyLimit {
+        id: usize,
+        limit: Option<DutyCycleLimit>,
+    },
     SetGamepads {
         id: usize,
         gamepads: VecMap<()>,
@@ -72,10 +80,47 @@ pub(crate) enum Message {
         id: usize,
         gain: f32,
     },
+    SetBaseEffect {
+        id: usize,
+        index: usize,
+        effect: BaseEffect,
+    },
+    SetMagnitude {
+        id: usize,
+        index: usize,
+        magnitude: u16,
+    },
+    SetScheduling {
+        id: usize,
+        index: usize,
+        scheduling: Replay,
+    },
+    /// Commands that are applied together, before the next tick.
+    Batch {
+        messages: Vec<Message>,
+    },
 }
 
+/// State of effect published by force feedback thread every tick.
+#[derive(Clone, Debug)]
+pub(crate) struct EffectStatus {
+    pub state: PlaybackState,
+    pub devices: VecMap<()>,
+}
+
+/// State of effects and devices published by force feedback thread every tick.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FfStates {
+    pub effects: VecMap<EffectStatus>,
+    /// Magnitudes last sent to motors of each device, after mixing and duty limit.
+    pub motors: VecMap<Vec<u16>>,
+}
+
+pub(crate) type EffectStates = Arc<Mutex<FfStates>>;
+
 pub(crate) enum FfMessage {
     EffectCompleted { event: Event },
+    DutyLimited { id: usize, limited: bool },
 }
 
 impl Message {
@@ -85,15 +130,35 @@ impl Message {
 
         matches!(
             self,
-            &SetListenerPosition { .. } | &HandleCloned { .. } | &HandleDropped { .. }
+            &SetListenerPosition { .. }
+                | &HandleCloned { .. }
+                | &HandleDropped { .. }
+                | &Batch { .. }
         )
     }
+
+    /// Number of commands sent by user that this message carries. Messages that only keep track
+    /// of effect handles and devices are not counted, commands in a batch are counted separately.
+    fn command_count(&self) -> u64 {
+        use self::Message::*;
+
+        match self {
+            HandleCloned { .. } | HandleDropped { .. } | Open { .. } | Close { .. } => 0,
+            Batch { messages } => messages.iter().map(Message::command_count).sum(),
+            _ => 1,
+        }
+    }
 }
 
 #[derive(Debug)]
 struct Device {
     inner: FfDevice,
     position: [f32; 3],
+    limiter: Option<DutyLimiter>,
+    // Last value sent with `FfMessage::DutyLimited`.
+    limited: bool,
+    // Magnitudes set in last tick.
+    motors: Vec<u16>,
 }
 
 struct Effect {
@@ -139,155 +204,300 @@ impl From<FfDevice> for Device {
         Device {
             inner,
             position: [0.0, 0.0, 0.0],
+            limiter: None,
+            limited: false,
+            motors: Vec::new(),
         }
     }
 }
 
-pub(crate) fn run(tx: Sender<FfMessage>, rx: Receiver<Message>) {
+pub(crate) fn run(
+    tx: Sender<FfMessage>,
+    rx: Receiver<Message>,
+    handled: Arc<AtomicU64>,
+    states: EffectStates,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("ff_server").entered();
+
     let mut effects = VecMap::<Effect>::new();
     let mut devices = VecMap::<Device>::new();
-    let sleep_dur = Duration::from_millis(TICK_DURATION.into());
+    let tick_dur = Duration::from_millis(TICK_DURATION.into());
     let mut tick = Ticks(0);
     let mut completion_events = Vec::<Event>::new();
+    // Deadlines are advanced by fixed steps instead of being computed from the end of previous
+    // iteration, so time spent on mixing and oversleeping doesn't accumulate.
+    let mut next_tick = Instant::now();
 
     loop {
-        let t1 = Instant::now();
-        while let Ok(ev) = rx.try_recv() {
-            if ev.use_trace_level() {
-                trace!("New ff event: {:?}", ev);
-            } else {
-                debug!("New ff event: {:?}", ev);
-            }
-
-            match ev {
-                Message::Create { id, effect } => {
-                    effects.insert(id, (*effect).into());
-                }
-                Message::Play { id } => {
-                    if let Some(effect) = effects.get_mut(id) {
-                        effect.source.state = EffectState::Playing { since: tick }
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
+        // Handle messages as they arrive until it's time for the next tick.
+        loop {
+            let timeout = next_tick.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(timeout) {
+                Ok(ev) => {
+                    handled.fetch_add(ev.command_count(), Ordering::Relaxed);
+                    handle_message(ev, &mut effects, &mut devices, tick)
                 }
-                Message::Stop { id } => {
-                    if let Some(effect) = effects.get_mut(id) {
-                        effect.source.state = EffectState::Stopped
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-                }
-                Message::Open { id, device } => {
-                    devices.insert(id, device.into());
-                }
-                Message::Close { id } => {
-                    devices.remove(id);
-                }
-                Message::SetListenerPosition { id, position } => {
-                    if let Some(device) = devices.get_mut(id) {
-                        device.position = position;
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-                }
-                Message::HandleCloned { id } => {
-                    if let Some(effect) = effects.get_mut(id) {
-                        effect.inc();
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-                }
-                Message::HandleDropped { id } => {
-                    let mut drop = false;
-                    if let Some(effect) = effects.get_mut(id) {
-                        if effect.dec() == 0 {
-                            drop = true;
-                        }
-                    } else {
-                        error!("{:?} with wrong ID", ev);
-                    }
-
-                    if drop {
-                        effects.remove(id);
-                    }
-                }
-                Message::SetGamepads { id, gamepads } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.devices = gamepads;
-                    } else {
-                        error!("Invalid effect id {} when changing gamepads.", id);
-                    }
-                }
-                Message::AddGamepad { id, gamepad_id } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.devices.insert(gamepad_id.0, ());
-                    } else {
-                        error!("Invalid effect id {} when changing gamepads.", id);
-                    }
-                }
-                Message::SetRepeat { id, repeat } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.repeat = repeat;
-                    } else {
-                        error!("Invalid effect id {} when changing repeat mode.", id);
-                    }
-                }
-                Message::SetDistanceModel { id, model } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.distance_model = model;
-                    } else {
-                        error!("Invalid effect id {} when changing distance model.", id);
-                    }
-                }
-                Message::SetPosition { id, position } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.position = position;
-                    } else {
-                        error!("Invalid effect id {}.", id);
-                    }
-                }
-                Message::SetGain { id, gain } => {
-                    if let Some(eff) = effects.get_mut(id) {
-                        eff.source.gain = gain;
-                    } else {
-                        error!("Invalid effect id {} when changing effect gain.", id);
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    debug!("Force feedback channel disconnected, stopping force feedback thread");
+                    for (_, dev) in devices.iter_mut() {
+                        dev.inner.set_ff_state(0, 0, Duration::ZERO);
                     }
+                    return;
                 }
             }
         }
 
         combine_and_play(&mut effects, &mut devices, tick, &mut completion_events);
+        publish_states(&effects, &devices, tick, &states);
         completion_events.iter().for_each(|ev| {
             let _ = tx.send(FfMessage::EffectCompleted { event: *ev });
         });
         completion_events.clear();
 
-        let dur = Instant::now().duration_since(t1);
-        if dur > sleep_dur {
-            // TODO: Should we add dur - sleep_dur to next iteration's dur?
+        for (id, dev) in devices.iter_mut() {
+            let limited = dev.limiter.as_ref().is_some_and(DutyLimiter::is_limited);
+            if limited != dev.limited {
+                dev.limited = limited;
+                let _ = tx.send(FfMessage::DutyLimited { id, limited });
+            }
+        }
+
+        tick.inc();
+        next_tick += tick_dur;
+
+        // If we fell behind by whole ticks (for example, thread wasn't scheduled for a while),
+        // skip them, so effects stay in sync with wall clock instead of being played faster to
+        // catch up.
+        let now = Instant::now();
+        let missed = missed_ticks(next_tick, now, tick_dur);
+        if missed > 0 {
             warn!(
-                "One iteration of a force feedback loop took more than {}ms!",
-                TICK_DURATION
+                "Force feedback loop is {}ms late, skipping {} ticks",
+                now.saturating_duration_since(next_tick).as_millis(),
+                missed
             );
-        } else {
-            thread::sleep(sleep_dur - dur);
+            tick += Ticks(missed);
+            next_tick += tick_dur * missed;
         }
-        tick.inc();
     }
 }
 
-pub(crate) fn init() -> (Sender<Message>, Receiver<FfMessage>) {
+/// Returns number of whole ticks of length `tick_dur` that passed between `next_tick` deadline and
+/// `now`.
+fn missed_ticks(next_tick: Instant, now: Instant, tick_dur: Duration) -> u32 {
+    let late = now.saturating_duration_since(next_tick);
+    u32::try_from(late.as_nanos() / tick_dur.as_nanos().max(1)).unwrap_or(u32::MAX)
+}
+
+fn handle_message(
+    ev: Message,
+    effects: &mut VecMap<Effect>,
+    devices: &mut VecMap<Device>,
+    tick: Ticks,
+) {
+    if ev.use_trace_level() {
+        diag!(trace, command = ev; "New ff command");
+    } else {
+        diag!(debug, command = ev; "New ff command");
+    }
+
+    match ev {
+        Message::Batch { messages } => {
+            for message in messages {
+                handle_message(message, effects, devices, tick);
+            }
+        }
+        Message::Create { id, effect } => {
+            effects.insert(id, (*effect).into());
+        }
+        Message::Play { id } => {
+            if let Some(effect) = effects.get_mut(id) {
+                effect.source.state = EffectState::Playing { since: tick }
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::Stop { id } => {
+            if let Some(effect) = effects.get_mut(id) {
+                effect.source.state = EffectState::Stopped
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::Open { id, device } => {
+            devices.insert(id, device.into());
+        }
+        Message::Close { id } => {
+            devices.remove(id);
+        }
+        Message::SetListenerPosition { id, position } => {
+            if let Some(device) = devices.get_mut(id) {
+                device.position = position;
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::SetDutyLimit { id, limit } => {
+            if let Some(device) = devices.get_mut(id) {
+                device.limiter = limit.map(DutyLimiter::new);
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::HandleCloned { id } => {
+            if let Some(effect) = effects.get_mut(id) {
+                effect.inc();
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+        }
+        Message::HandleDropped { id } => {
+            let mut drop = false;
+            if let Some(effect) = effects.get_mut(id) {
+                if effect.dec() == 0 {
+                    drop = true;
+                }
+            } else {
+                error!("{:?} with wrong ID", ev);
+            }
+
+            if drop {
+                effects.remove(id);
+            }
+        }
+        Message::SetGamepads { id, gamepads } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.devices = gamepads;
+            } else {
+                error!("Invalid effect id {} when changing gamepads.", id);
+            }
+        }
+        Message::AddGamepad { id, gamepad_id } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.devices.insert(gamepad_id.0, ());
+            } else {
+                error!("Invalid effect id {} when changing gamepads.", id);
+            }
+        }
+        Message::SetRepeat { id, repeat } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.repeat = repeat;
+            } else {
+                error!("Invalid effect id {} when changing repeat mode.", id);
+            }
+        }
+        Message::SetDistanceModel { id, model } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.distance_model = model;
+            } else {
+                error!("Invalid effect id {} when changing distance model.", id);
+            }
+        }
+        Message::SetPosition { id, position } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.position = position;
+            } else {
+                error!("Invalid effect id {}.", id);
+            }
+        }
+        Message::SetGain { id, gain } => {
+            if let Some(eff) = effects.get_mut(id) {
+                eff.source.gain = gain;
+            } else {
+                error!("Invalid effect id {} when changing effect gain.", id);
+            }
+        }
+        Message::SetBaseEffect { id, index, effect } => {
+            match effects.get_mut(id).map(|eff| eff.base_effect_mut(index)) {
+                Some(Some(base)) => *base = effect,
+                Some(None) => error!("Invalid base effect index {} of effect {}.", index, id),
+                None => error!("Invalid effect id {} when changing base effect.", id),
+            }
+        }
+        Message::SetMagnitude {
+            id,
+            index,
+            magnitude,
+        } => match effects.get_mut(id).map(|eff| eff.base_effect_mut(index)) {
+            Some(Some(base)) => base.kind = base.kind.with_magnitude(magnitude),
+            Some(None) => error!("Invalid base effect index {} of effect {}.", index, id),
+            None => error!("Invalid effect id {} when changing magnitude.", id),
+        },
+        Message::SetScheduling {
+            id,
+            index,
+            scheduling,
+        } => match effects.get_mut(id).map(|eff| eff.base_effect_mut(index)) {
+            Some(Some(base)) => base.scheduling = scheduling,
+            Some(None) => error!("Invalid base effect index {} of effect {}.", index, id),
+            None => error!("Invalid effect id {} when changing scheduling.", id),
+        },
+    }
+}
+
+/// Spawns force feedback thread. Returned counter is incremented for every handled command sent by
+/// user (see `Message::command_count()`) and returned states are updated every tick.
+pub(crate) fn init() -> (
+    Sender<Message>,
+    Receiver<FfMessage>,
+    Arc<AtomicU64>,
+    EffectStates,
+) {
     let (tx, _rx) = mpsc::channel();
     let (_tx2, rx2) = mpsc::channel();
+    let handled = Arc::new(AtomicU64::new(0));
+    let _handled = handled.clone();
+    let states = EffectStates::default();
+    let _states = states.clone();
 
     // Wasm doesn't support threads and force feedback
     #[cfg(not(target_arch = "wasm32"))]
     std::thread::Builder::new()
         .name("gilrs".to_owned())
-        .spawn(move || run(_tx2, _rx))
+        .spawn(move || run(_tx2, _rx, _handled, _states))
         .expect("failed to spawn thread");
 
-    (tx, rx2)
+    (tx, rx2, handled, states)
+}
+
+fn publish_states(
+    effects: &VecMap<Effect>,
+    devices: &VecMap<Device>,
+    tick: Ticks,
+    states: &EffectStates,
+) {
+    let mut states = states.lock().unwrap();
+    states.effects.retain(|id, _| effects.contains_key(id));
+    for (id, effect) in effects {
+        let state = effect.playback_state(tick);
+        match states.effects.get_mut(id) {
+            Some(status) => {
+                status.state = state;
+                status.devices.clone_from(&effect.devices);
+            }
+            None => {
+                states.effects.insert(
+                    id,
+                    EffectStatus {
+                        state,
+                        devices: effect.devices.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    states.motors.retain(|id, _| devices.contains_key(id));
+    for (id, dev) in devices {
+        match states.motors.get_mut(id) {
+            Some(motors) => motors.clone_from(&dev.motors),
+            None => {
+                states.motors.insert(id, dev.motors.clone());
+            }
+        }
+    }
 }
 
 fn combine_and_play(
@@ -297,10 +507,11 @@ fn combine_and_play(
     completion_events: &mut Vec<Event>,
 ) {
     for (dev_id, dev) in devices {
+        let motor_count = dev.inner.motor_count();
         let mut magnitude = Magnitude::zero();
         for (_, ref mut effect) in effects.iter_mut() {
             if effect.devices.contains_key(dev_id) {
-                magnitude += effect.combine_base_effects(tick, dev.position);
+                magnitude += effect.combine_base_effects(tick, dev.position, motor_count);
                 completion_events.extend(effect.flush_completion_events());
             }
         }
@@ -310,10 +521,57 @@ fn combine_and_play(
             dev,
             magnitude
         );
-        dev.inner.set_ff_state(
-            magnitude.strong,
-            magnitude.weak,
+        dev.motors.clear();
+        dev.motors.extend_from_slice(magnitude.motors(motor_count));
+        if let Some(limiter) = &mut dev.limiter {
+            limiter.apply(&mut dev.motors);
+        }
+        dev.inner.set_motors(
+            &dev.motors,
             Duration::from_millis(u64::from(TICK_DURATION) * 2),
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{missed_ticks, Message};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn counting_commands() {
+        assert_eq!(Message::Play { id: 0 }.command_count(), 1);
+        assert_eq!(Message::HandleCloned { id: 0 }.command_count(), 0);
+        assert_eq!(Message::Close { id: 0 }.command_count(), 0);
+
+        let batch = Message::Batch {
+            messages: vec![
+                Message::Play { id: 0 },
+                Message::SetGain { id: 1, gain: 0.5 },
+                Message::HandleDropped { id: 2 },
+                Message::Batch {
+                    messages: vec![Message::Stop { id: 0 }],
+                },
+            ],
+        };
+        assert_eq!(batch.command_count(), 3);
+        assert_eq!(Message::Batch { messages: vec![] }.command_count(), 0);
+    }
+
+    #[test]
+    fn skipping_ticks() {
+        let tick = Duration::from_millis(50);
+        let deadline = Instant::now();
+
+        assert_eq!(missed_ticks(deadline, deadline, tick), 0);
+        // Oversleeping by less than a tick doesn't skip any.
+        assert_eq!(missed_ticks(deadline, deadline + tick / 2, tick), 0);
+        assert_eq!(missed_ticks(deadline, deadline + tick, tick), 1);
+        assert_eq!(
+            missed_ticks(deadline, deadline + tick * 7 + tick / 2, tick),
+            7
+        );
+        // Waking up early is not late.
+        assert_eq!(missed_ticks(deadline + tick, deadline, tick), 0);
+    }
+}
@@ -45,6 +45,10 @@ impl Ticks {
     pub(super) fn checked_sub(self, rhs: Ticks) -> Option<Ticks> {
         self.0.checked_sub(rhs.0).map(Ticks)
     }
+
+    pub(super) fn as_duration(self) -> Duration {
+        Duration::from_millis(u64::from(self.0) * u64::from(TICK_DURATION))
+    }
 }
 
 impl From<Duration> for Ticks {
@@ -7,33 +7,50 @@
 
 use crate::{
     ev::{
-        state::{AxisData, ButtonData, GamepadState},
-        Axis, AxisOrBtn, Button, Code, Event, EventType,
+        filter::FilterCheck,
+        state::{AxisData, ButtonData, GamepadState, SavedState},
+        Axis, AxisOrBtn, Button, Code, Event, EventRef, EventType, FrameEvents, Payload, PayloadId,
+        PayloadKind, PayloadStore,
     },
     ff::{
-        server::{self, FfMessage, Message},
-        Error as FfError,
+        server::{self, EffectStates, FfMessage, Message},
+        DutyCycleLimit, EffectId, Error as FfError, FfBatch, PlaybackState,
+    },
+    mapping::{
+        CodeRemap, Lever, LeverMode, LeverOutput, Mapping, MappingData, MappingDb, MappingEntry,
+        MappingLayer, MappingSources, MappingWatch, UserMappings,
     },
-    mapping::{Mapping, MappingData, MappingDb},
     utils, MappingError,
 };
 
-use gilrs_core::{
-    self, AxisInfo, Error as PlatformError, Event as RawEvent, EventType as RawEventType,
+use gilrs_core::{self, AxisInfo, Event as RawEvent, EventType as RawEventType};
+
+// Unit tests replace backend with one that lets them create gamepads and their events.
+#[cfg(test)]
+pub(crate) use crate::test_backend::{
+    Error as PlatformError, Gamepad as RawGamepad, Gilrs as RawGilrs,
 };
+#[cfg(not(test))]
+pub(crate) use gilrs_core::{Error as PlatformError, Gamepad as RawGamepad, Gilrs as RawGilrs};
 
 use uuid::Uuid;
 
 use std::cmp::Ordering;
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     error,
     fmt::{self, Display},
+    mem,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
     sync::mpsc::{Receiver, Sender},
-    time::Duration,
+    sync::Arc,
+    time::{Duration, SystemTime},
 };
 
-pub use gilrs_core::PowerInfo;
+pub use gilrs_core::{
+    ConnectionType, Guid, HeadsetState, HidError, InaccessibleDevice, ParseGuidError, PowerInfo,
+};
 
 #[cfg(feature = "serde-serialize")]
 use serde::{Deserialize, Serialize};
@@ -131,23 +148,112 @@ const DEFAULT_DEADZONE: f32 = 0.1;
 ///
 #[derive(Debug)]
 pub struct Gilrs {
-    inner: gilrs_core::Gilrs,
+    inner: RawGilrs,
     next_id: usize,
     tx: Sender<Message>,
     rx: Receiver<FfMessage>,
     counter: u64,
     mappings: MappingDb,
+    mapping_watch: Option<MappingWatch>,
+    keep_runtime_mappings: bool,
     default_filters: bool,
     events: VecDeque<Event>,
+    // Filtered events that were too late for frame latched with `latch_frame()`.
+    latched: VecDeque<Event>,
+    payloads: PayloadStore,
+    // State of gamepads of other players updated by `inject_frame()`, indexed by player and id of
+    // gamepad on player's side.
+    remote_states: HashMap<(u32, GamepadId), GamepadState>,
     axis_to_btn_pressed: f32,
     axis_to_btn_released: f32,
+    trigger_range: TriggerRange,
+    trigger_deadzone: TriggerDeadzone,
+    filter_check: FilterCheck,
+    idle_timeout: Duration,
+    idle_events: bool,
+    stuck_button_timeout: Option<Duration>,
+    repeat_max_catch_up: u32,
+    // Gamepad that produced last input event.
+    last_active: Option<GamepadId>,
     pub(crate) update_state: bool,
     pub(crate) gamepads_data: Vec<GamepadData>,
+    subscriptions: Subscriptions,
+    stats: Stats,
+    ff_commands: Arc<AtomicU64>,
+    // Value of `ff_commands` when stats were reset.
+    ff_commands_base: u64,
+    ff_states: EffectStates,
+}
+
+/// Identifies callback registered with [`Gilrs::on_button`] or [`Gilrs::on_axis`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SubscriptionId(usize);
+
+type Callback = Box<dyn FnMut(&Event) + Send>;
+
+#[derive(Default)]
+struct Subscriptions {
+    next_id: usize,
+    entries: Vec<(SubscriptionId, Option<GamepadId>, AxisOrBtn, Callback)>,
+}
+
+impl Subscriptions {
+    fn add(
+        &mut self,
+        id: Option<GamepadId>,
+        element: AxisOrBtn,
+        callback: Callback,
+    ) -> SubscriptionId {
+        let sub = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.entries.push((sub, id, element, callback));
+
+        sub
+    }
+
+    fn remove(&mut self, sub: SubscriptionId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.0 != sub);
+
+        self.entries.len() != len
+    }
+
+    fn dispatch(&mut self, event: &Event) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        let element = match event.event {
+            EventType::ButtonPressed(btn, _)
+            | EventType::ButtonRepeated(btn, _)
+            | EventType::ButtonReleased(btn, _)
+            | EventType::ButtonChanged(btn, _, _) => AxisOrBtn::Btn(btn),
+            EventType::AxisChanged(axis, _, _)
+            | EventType::AxisPressed(axis, _, _)
+            | EventType::AxisReleased(axis, _, _) => AxisOrBtn::Axis(axis),
+            _ => return,
+        };
+
+        for (_, id, el, callback) in &mut self.entries {
+            if *el == element && id.map_or(true, |id| id == event.id) {
+                callback(event);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Subscriptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|(sub, id, el, _)| (sub, id, el)))
+            .finish()
+    }
 }
 
 impl Gilrs {
     /// Creates new `Gilrs` with default settings. See [`GilrsBuilder`](struct.GilrsBuilder.html)
     /// for more details.
+    #[allow(clippy::result_large_err)]
     pub fn new() -> Result<Self, Error> {
         GilrsBuilder::new().build()
     }
@@ -170,10 +276,192 @@ impl Gilrs {
         self.next_event_inner(true, timeout)
     }
 
+    /// Collects events that happened up to `deadline` and returns them in order. Use it once per
+    /// frame, with deadline set to the time at which frame samples input, instead of calling
+    /// [`next_event()`](Gilrs::next_event) in a loop.
+    ///
+    /// `deadline` should be the current time or earlier. Events are read on the calling thread,
+    /// so if `deadline` is in the future, this function blocks until then and delays the frame by
+    /// the same amount. Events with timestamps after the deadline are buffered and returned in the
+    /// next frame (or by `next_event()`). Gamepad state is updated with each returned event as
+    /// it's read, so filters see the same state as with `next_event()` and the state matches the
+    /// end of the frame.
+    /// With variable refresh rate or uncapped frame rate this reduces jitter of input sampling,
+    /// because events are assigned to frames by their timestamps, not by the time they were read.
+    ///
+    /// ```
+    /// use gilrs::Gilrs;
+    /// use std::time::SystemTime;
+    ///
+    /// let mut gilrs = Gilrs::new().unwrap();
+    ///
+    /// # for _ in 0..1 {
+    /// // Game loop
+    /// loop {
+    ///     // Time at which this frame samples input.
+    ///     let deadline = SystemTime::now();
+    ///     for event in gilrs.latch_frame(deadline) {
+    ///         // Handle event
+    ///     }
+    ///     // Simulate and render frame
+    /// #   break;
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// ## Platform support
+    ///
+    /// Blocking is not supported on web, there deadline in the future is treated as current time.
+    pub fn latch_frame(&mut self, deadline: SystemTime) -> Vec<Event> {
+        let mut frame = Vec::new();
+
+        while let Some(ev) = self.latched.front() {
+            if ev.time > deadline {
+                break;
+            }
+            if let Some(ev) = self.latched.pop_front() {
+                self.latch(ev, &mut frame);
+            }
+        }
+
+        // Events from backend are returned in order of timestamps, so if there is already
+        // buffered event, all newer ones are too late for this frame too.
+        while self.latched.is_empty() {
+            let timeout = deadline
+                .duration_since(utils::time_now())
+                .ok()
+                .filter(|t| !t.is_zero() && !cfg!(target_arch = "wasm32"));
+
+            match self.next_filtered_event(timeout.is_some(), timeout) {
+                Some(ev) if ev.time > deadline => self.latched.push_back(ev),
+                Some(ev) => self.latch(ev, &mut frame),
+                None if timeout.is_none() => break,
+                None => (),
+            }
+        }
+
+        frame
+    }
+
+    // Adds event to frame collected by `latch_frame()`. State is updated before next event is
+    // read, because default filters and axis to button conversion depend on it.
+    fn latch(&mut self, ev: Event, frame: &mut Vec<Event>) {
+        if self.update_state {
+            self.update(&ev);
+        }
+        frame.push(ev);
+    }
+
+    /// Returns all pending events as batch tagged with `frame`, updating gamepad state like
+    /// [`latch_frame()`](Gilrs::latch_frame) with current time as deadline. Intended for
+    /// lockstep and rollback networking, where input of each frame is sent to other players.
+    ///
+    /// ```
+    /// use gilrs::Gilrs;
+    ///
+    /// let mut gilrs = Gilrs::new().unwrap();
+    /// let mut frame = 0;
+    ///
+    /// # for _ in 0..1 {
+    /// // Game loop
+    /// loop {
+    ///     let batch = gilrs.drain_frame(frame);
+    ///     for event in &batch.events {
+    ///         // Handle event
+    ///     }
+    ///     // Send `batch` to other players, simulate and render frame
+    ///     frame += 1;
+    /// #   break;
+    /// }
+    /// # }
+    /// ```
+    pub fn drain_frame(&mut self, frame: u64) -> FrameEvents {
+        let events = self
+            .latch_frame(utils::time_now())
+            .iter()
+            .map(|ev| self.payloads.resolve(ev).into_owned())
+            .collect();
+
+        FrameEvents { frame, events }
+    }
+
+    /// Updates state of remote gamepads with events of `batch` received from other `player`.
+    /// Player number is chosen by application and together with `id` of events identifies remote
+    /// gamepad, so it doesn't have to exist in this `Gilrs`. Its state can be read with
+    /// [`remote_state()`](Gilrs::remote_state).
+    ///
+    /// Events are only applied to remote state, they are not returned by
+    /// [`next_event()`](Gilrs::next_event), [`latch_frame()`](Gilrs::latch_frame) or
+    /// [`drain_frame()`](Gilrs::drain_frame), so input of other players is never sent back to
+    /// them. Handle events of `batch` directly, they were already filtered where `batch` was
+    /// drained and own their payloads.
+    ///
+    /// ```
+    /// use gilrs::ev::FrameEvents;
+    /// use gilrs::Gilrs;
+    ///
+    /// let mut gilrs = Gilrs::new().unwrap();
+    /// // Received from player 1.
+    /// let batch = FrameEvents::default();
+    ///
+    /// gilrs.inject_frame(1, &batch);
+    /// for event in &batch.events {
+    ///     let state = gilrs.remote_state(1, event.id).unwrap();
+    ///     let pressed = state.buttons().filter(|(_, data)| data.is_pressed()).count();
+    ///     println!("{:?}, {} buttons pressed", event.event, pressed);
+    /// }
+    /// ```
+    pub fn inject_frame(&mut self, player: u32, batch: &FrameEvents) {
+        for ev in &batch.events {
+            let state = self
+                .remote_states
+                .entry((player, ev.id))
+                .or_insert_with(|| GamepadState::with_capacity(0, 0));
+            let event = Event {
+                id: ev.id,
+                event: ev.event,
+                time: ev.time,
+            };
+            update_gamepad_state(state, &event, self.counter);
+        }
+    }
+
+    /// Returns state of gamepad `id` of remote `player` updated by
+    /// [`inject_frame()`](Gilrs::inject_frame), or `None` if no events of this gamepad were
+    /// injected.
+    pub fn remote_state(&self, player: u32, id: GamepadId) -> Option<&GamepadState> {
+        self.remote_states.get(&(player, id))
+    }
+
+    /// Drops state of all gamepads of remote `player`, for example after the player left.
+    pub fn remove_remote_player(&mut self, player: u32) {
+        self.remote_states.retain(|&(p, _), _| p != player);
+    }
+
     fn next_event_inner(
         &mut self,
         is_blocking: bool,
         blocking_timeout: Option<Duration>,
+    ) -> Option<Event> {
+        let ev = match self.latched.pop_front() {
+            Some(ev) => Some(ev),
+            None => self.next_filtered_event(is_blocking, blocking_timeout),
+        };
+
+        if self.update_state {
+            if let Some(ref ev) = ev {
+                self.update(ev);
+            }
+        }
+
+        ev
+    }
+
+    // Returns next event after default filters, without updating gamepad state.
+    fn next_filtered_event(
+        &mut self,
+        is_blocking: bool,
+        blocking_timeout: Option<Duration>,
     ) -> Option<Event> {
         use crate::ev::filter::{axis_dpad_to_button, deadzone, Filter, Jitter};
 
@@ -188,19 +476,27 @@ impl Gilrs {
 
                 // Skip all dropped events, there is no reason to return them
                 match ev {
-                    Some(ev) if ev.is_dropped() => (),
+                    Some(ev) if ev.is_dropped() => {
+                        diag!(trace, gamepad = ev.id.0; "Event dropped by filter");
+                        self.stats.dropped_events += 1;
+                    }
                     _ => break ev,
                 }
             }
         } else {
-            self.next_event_priv(is_blocking, blocking_timeout)
+            let ev = self.next_event_priv(is_blocking, blocking_timeout);
+            if let Some(ev) = ev.filter(Event::is_dropped) {
+                diag!(trace, gamepad = ev.id.0; "Event dropped by filter");
+                self.stats.dropped_events += 1;
+            }
+
+            ev
         };
 
-        if self.update_state {
-            if let Some(ref ev) = ev {
-                self.update(ev);
-            }
+        if ev.is_some() {
+            self.stats.events += 1;
         }
+        self.stats.max_queue_len = self.stats.max_queue_len.max(self.events.len());
 
         ev
     }
@@ -211,19 +507,40 @@ impl Gilrs {
         is_blocking: bool,
         blocking_timeout: Option<Duration>,
     ) -> Option<Event> {
-        if let Some(msg) = self.rx.try_recv().ok() {
+        while let Ok(msg) = self.rx.try_recv() {
             match msg {
                 FfMessage::EffectCompleted { event } => return Some(event),
+                FfMessage::DutyLimited { id, limited } => {
+                    if let Some(data) = self.gamepads_data.get_mut(id) {
+                        data.ff_limited = limited;
+                    }
+                }
             }
         }
+        if self.idle_events {
+            self.check_idle(utils::time_now());
+        }
+        if let Some(timeout) = self.stuck_button_timeout {
+            self.check_stuck_buttons(timeout);
+        }
+        if self.mapping_watch.is_some() {
+            self.check_mapping_files();
+        }
         if let Some(ev) = self.events.pop_front() {
             Some(ev)
         } else {
+            let poll_start = utils::time_now();
             let event = if is_blocking {
                 self.inner.next_event_blocking(blocking_timeout)
             } else {
                 self.inner.next_event()
             };
+            let poll_time = utils::time_now()
+                .duration_since(poll_start)
+                .unwrap_or_default();
+            self.stats.polls += 1;
+            self.stats.poll_time += poll_time;
+            self.stats.max_poll_time = self.stats.max_poll_time.max(poll_time);
 
             match event {
                 Some(RawEvent {
@@ -237,7 +554,7 @@ impl Gilrs {
 
                     let event = match event_type {
                         RawEventType::ButtonPressed(nec) => {
-                            let nec = Code(nec);
+                            let nec = self.gamepad(id).data.settings.code_remap.get(Code(nec));
                             match self.gamepad(id).axis_or_btn_name(nec) {
                                 Some(AxisOrBtn::Btn(b)) => {
                                     self.events.push_back(Event {
@@ -249,6 +566,21 @@ impl Gilrs {
                                     EventType::ButtonPressed(b, nec)
                                 }
                                 Some(AxisOrBtn::Axis(a)) => EventType::AxisChanged(a, 1.0, nec),
+                                None if nec.0.is_key() => {
+                                    let data = &mut self.gamepads_data[id.0];
+                                    if nec.0.is_shift_key() {
+                                        data.shift_keys = data.shift_keys.saturating_add(1);
+                                    } else if let Some(c) = nec.0.key_char(data.shift_keys > 0) {
+                                        let payload = self.payloads.insert(Payload::Text(c.into()));
+                                        self.events.push_back(Event {
+                                            id,
+                                            time,
+                                            event: EventType::Payload(PayloadKind::Text, payload),
+                                        });
+                                    }
+
+                                    EventType::KeyPressed(nec)
+                                }
                                 None => {
                                     self.events.push_back(Event {
                                         id,
@@ -261,7 +593,7 @@ impl Gilrs {
                             }
                         }
                         RawEventType::ButtonReleased(nec) => {
-                            let nec = Code(nec);
+                            let nec = self.gamepad(id).data.settings.code_remap.get(Code(nec));
                             match self.gamepad(id).axis_or_btn_name(nec) {
                                 Some(AxisOrBtn::Btn(b)) => {
                                     self.events.push_back(Event {
@@ -273,6 +605,14 @@ impl Gilrs {
                                     EventType::ButtonReleased(b, nec)
                                 }
                                 Some(AxisOrBtn::Axis(a)) => EventType::AxisChanged(a, 0.0, nec),
+                                None if nec.0.is_key() => {
+                                    if nec.0.is_shift_key() {
+                                        let data = &mut self.gamepads_data[id.0];
+                                        data.shift_keys = data.shift_keys.saturating_sub(1);
+                                    }
+
+                                    EventType::KeyReleased(nec)
+                                }
                                 None => {
                                     self.events.push_back(Event {
                                         id,
@@ -287,11 +627,14 @@ impl Gilrs {
                         RawEventType::AxisValueChanged(val, nec) => {
                             // Let's trust at least our backend code
                             let axis_info = *self.gamepad(id).inner.axis_info(nec).unwrap();
-                            let nec = Code(nec);
+                            let nec = self.gamepad(id).data.settings.code_remap.get(Code(nec));
 
                             match self.gamepad(id).axis_or_btn_name(nec) {
                                 Some(AxisOrBtn::Btn(b)) => {
-                                    let val = btn_value(&axis_info, val);
+                                    let mut val = btn_value(&axis_info, val);
+                                    if self.gamepad(id).mapping().is_inverted(&nec.0) {
+                                        val = 1.0 - val;
+                                    }
 
                                     if val >= self.axis_to_btn_pressed
                                         && !self.gamepad(id).state().is_pressed(nec)
@@ -318,7 +661,15 @@ impl Gilrs {
                                     }
                                 }
                                 Some(AxisOrBtn::Axis(a)) => {
-                                    EventType::AxisChanged(a, axis_value(&axis_info, val, a), nec)
+                                    let mut val = axis_value(&axis_info, val, a);
+                                    if self.gamepad(id).mapping().is_inverted(&nec.0) {
+                                        val = -val;
+                                    }
+                                    if a == Axis::LeftZ || a == Axis::RightZ {
+                                        val = self.trigger_range.convert(val);
+                                    }
+
+                                    EventType::AxisChanged(a, val, nec)
                                 }
                                 None => EventType::AxisChanged(
                                     Axis::Unknown,
@@ -328,21 +679,42 @@ impl Gilrs {
                             }
                         }
                         RawEventType::Connected => {
+                            #[cfg(feature = "tracing")]
+                            let _span = tracing::debug_span!("connect", gamepad = id.0).entered();
+
+                            let prev = self.gamepads_data.get_mut(id.0);
+                            let prev_user_index =
+                                prev.as_ref().and_then(|data| data.xinput_user_index);
+                            let prev_connection_type =
+                                prev.as_ref().map(|data| data.connection_type);
+                            let (settings, prev_runtime_mapping) = match prev {
+                                Some(data) => {
+                                    (mem::take(&mut data.settings), data.runtime_mapping.take())
+                                }
+                                None => (GamepadSettings::default(), None),
+                            };
+                            let prev_runtime_mapping =
+                                prev_runtime_mapping.filter(|_| self.keep_runtime_mappings);
+
                             match id.0.cmp(&self.gamepads_data.len()) {
                                 Ordering::Equal => {
                                     self.gamepads_data.push(GamepadData::new(
                                         id,
                                         self.tx.clone(),
+                                        self.ff_states.clone(),
                                         self.inner.gamepad(id.0).unwrap(),
                                         &self.mappings,
+                                        settings,
                                     ));
                                 }
                                 Ordering::Less => {
                                     self.gamepads_data[id.0] = GamepadData::new(
                                         id,
                                         self.tx.clone(),
+                                        self.ff_states.clone(),
                                         self.inner.gamepad(id.0).unwrap(),
                                         &self.mappings,
+                                        settings,
                                     );
                                 }
                                 Ordering::Greater => {
@@ -355,18 +727,71 @@ impl Gilrs {
                                 }
                             }
 
+                            if let Some(sdl) = prev_runtime_mapping {
+                                self.restore_runtime_mapping(id, sdl);
+                            }
+
+                            if let Some(data) = self.gamepads_data.get(id.0) {
+                                if data.xinput_user_index != prev_user_index {
+                                    self.events.push_back(Event {
+                                        id,
+                                        time,
+                                        event: EventType::XInputUserIndexChanged(
+                                            data.xinput_user_index,
+                                        ),
+                                    });
+                                }
+
+                                if prev_connection_type
+                                    .is_some_and(|prev| prev != data.connection_type)
+                                {
+                                    self.events.push_back(Event {
+                                        id,
+                                        time,
+                                        event: EventType::ConnectionTypeChanged(
+                                            data.connection_type,
+                                        ),
+                                    });
+                                }
+                            }
+
+                            if let Some(gamepad) = self.inner.gamepad(id.0) {
+                                diag!(
+                                    info,
+                                    gamepad = id.0,
+                                    name = gamepad.name(),
+                                    uuid = Uuid::from_bytes(gamepad.uuid());
+                                    "Gamepad connected"
+                                );
+                            }
+
                             EventType::Connected
                         }
                         RawEventType::Disconnected => {
+                            diag!(info, gamepad = id.0; "Gamepad disconnected");
+                            self.release_buttons(id, time, |_| true);
                             let _ = self.tx.send(Message::Close { id: id.0 });
 
+                            if let Some(data) = self.gamepads_data.get_mut(id.0) {
+                                if data.xinput_user_index.take().is_some() {
+                                    self.events.push_back(Event {
+                                        id,
+                                        time,
+                                        event: EventType::XInputUserIndexChanged(None),
+                                    });
+                                }
+                            }
+
                             EventType::Disconnected
                         }
+                        RawEventType::HeadsetChanged(state) => EventType::HeadsetChanged(state),
                         _ => {
                             unimplemented!()
                         }
                     };
 
+                    self.emulate_lever(id, time, &event);
+
                     Some(Event { id, event, time })
                 }
                 None => None,
@@ -378,9 +803,14 @@ impl Gilrs {
     ///
     /// Please note, that it's not necessary to call this function unless you modify events by using
     /// additional filters and disabled automatic updates when creating `Gilrs`.
+    ///
+    /// Callbacks registered with [`on_button`](Gilrs::on_button) and
+    /// [`on_axis`](Gilrs::on_axis) are also invoked from this function.
     pub fn update(&mut self, event: &Event) {
         use crate::EventType::*;
 
+        self.subscriptions.dispatch(event);
+
         let counter = self.counter;
 
         let data = match self.gamepads_data.get_mut(event.id.0) {
@@ -388,27 +818,277 @@ impl Gilrs {
             None => return,
         };
 
-        match event.event {
-            ButtonPressed(_, nec) => {
-                data.state.set_btn_pressed(nec, true, counter, event.time);
+        if matches!(event.event, Connected) {
+            data.last_activity = event.time;
+            data.active = false;
+        }
+        update_gamepad_state(&mut data.state, event, counter);
+
+        if matches!(
+            event.event,
+            ButtonPressed(..)
+                | ButtonReleased(..)
+                | ButtonChanged(..)
+                | AxisChanged(..)
+                | AxisPressed(..)
+                | AxisReleased(..)
+                | KeyPressed(..)
+                | KeyReleased(..)
+        ) {
+            let was_idle =
+                !data.active || is_idle(event.time, data.last_activity, self.idle_timeout);
+            data.last_activity = event.time;
+            data.active = true;
+            self.last_active = Some(event.id);
+
+            if was_idle {
+                self.events.push_front(Event {
+                    id: event.id,
+                    event: GamepadActivity,
+                    time: event.time,
+                });
+            }
+        }
+    }
+
+    /// Reloads mappings if any of watched mapping files changed and applies them to connected
+    /// gamepads. `MappingChanged` is queued for every gamepad whose mapping changed.
+    fn check_mapping_files(&mut self) {
+        let now = utils::time_now();
+        let db = match self.mapping_watch.as_mut().and_then(|w| w.poll(now)) {
+            Some(db) => db,
+            None => return,
+        };
+        let old = mem::replace(&mut self.mappings, db);
+
+        for (id, data) in self.gamepads_data.iter_mut().enumerate() {
+            let gamepad = match self.inner.gamepad(id) {
+                Some(gamepad) if gamepad.is_connected() => gamepad,
+                _ => continue,
+            };
+
+            // Mapping set with `set_mapping()` takes precedence over all files.
+            if data.mapping_layer == Some(MappingLayer::Runtime)
+                || lookup_mapping(gamepad, &old) == lookup_mapping(gamepad, &self.mappings)
+            {
+                continue;
+            }
+
+            let (mapping, mapping_layer) = resolve_mapping(gamepad, &self.mappings);
+            data.set_mapping(mapping);
+            data.mapping_layer = mapping_layer;
+            self.events.push_back(Event {
+                id: GamepadId(id),
+                event: EventType::MappingChanged,
+                time: now,
+            });
+        }
+    }
+
+    /// Queues `GamepadIdle` events for active gamepads that produced no input for longer than idle
+    /// timeout at `now`.
+    fn check_idle(&mut self, now: SystemTime) {
+        for (id, data) in self.gamepads_data.iter_mut().enumerate() {
+            let connected = self.inner.gamepad(id).is_some_and(|g| g.is_connected());
+            if !data.active || !connected {
+                continue;
+            }
+
+            if is_idle(now, data.last_activity, self.idle_timeout) {
+                data.active = false;
+                self.events.push_back(Event {
+                    id: GamepadId(id),
+                    event: EventType::GamepadIdle,
+                    time: now,
+                });
             }
-            ButtonReleased(_, nec) => {
-                data.state.set_btn_pressed(nec, false, counter, event.time);
+        }
+    }
+
+    fn check_stuck_buttons(&mut self, timeout: Duration) {
+        let now = utils::time_now();
+        for id in 0..self.gamepads_data.len() {
+            let data = &mut self.gamepads_data[id];
+            let gamepad = match self.inner.gamepad(id) {
+                Some(g) if g.is_connected() => g,
+                _ => continue,
+            };
+
+            if !stuck_check_due(now, data.last_activity, data.stuck_check, timeout) {
+                continue;
             }
-            ButtonRepeated(_, nec) => {
-                data.state.set_btn_repeating(nec, counter, event.time);
+            data.stuck_check = now;
+
+            let threshold = self.axis_to_btn_released;
+            let remap = &data.settings.code_remap;
+            let stuck: Vec<_> = data
+                .state
+                .buttons()
+                .filter(|&(code, btn)| {
+                    let raw = remap.original(code).0;
+                    btn.is_pressed()
+                        && is_released(gamepad.query_value(raw), gamepad.axis_info(raw), threshold)
+                })
+                .map(|(code, _)| code)
+                .collect();
+
+            if !stuck.is_empty() {
+                warn!("Releasing stuck buttons of gamepad {}: {:?}", id, stuck);
+                self.release_buttons(GamepadId(id), now, |code| stuck.contains(&code));
             }
-            ButtonChanged(_, value, nec) => {
-                data.state.set_btn_value(nec, value, counter, event.time);
+        }
+    }
+
+    // Queues `ButtonReleased` and `ButtonChanged` events for pressed buttons selected by `filter`.
+    fn release_buttons<F: Fn(Code) -> bool>(&mut self, id: GamepadId, time: SystemTime, filter: F) {
+        let data = match self.gamepads_data.get(id.0) {
+            Some(data) => data,
+            None => return,
+        };
+
+        for (code, btn) in data.state.buttons() {
+            if !btn.is_pressed() || !filter(code) {
+                continue;
             }
-            AxisChanged(_, value, nec) => {
-                data.state
-                    .update_axis(nec, AxisData::new(value, counter, event.time));
+
+            let button = match data.mapping.map(&code.0) {
+                Some(AxisOrBtn::Btn(b)) => b,
+                Some(AxisOrBtn::Axis(_)) => continue,
+                None => Button::Unknown,
+            };
+            self.events.push_back(Event {
+                id,
+                time,
+                event: EventType::ButtonReleased(button, code),
+            });
+            self.events.push_back(Event {
+                id,
+                time,
+                event: EventType::ButtonChanged(button, 0.0, code),
+            });
+        }
+    }
+
+    pub(crate) fn trigger_range(&self) -> TriggerRange {
+        self.trigger_range
+    }
+
+    pub(crate) fn trigger_deadzone(&self) -> TriggerDeadzone {
+        self.trigger_deadzone
+    }
+
+    pub(crate) fn filter_check(&self) -> FilterCheck {
+        self.filter_check
+    }
+
+    /// Returns id of connected gamepad that produced the most recent input event, for example to
+    /// choose which gamepad controls menu.
+    ///
+    /// Like gamepad state, this is updated by [`update()`](Gilrs::update).
+    pub fn last_active_gamepad(&self) -> Option<GamepadId> {
+        self.last_active
+            .filter(|&id| self.connected_gamepad(id).is_some())
+    }
+
+    /// Returns ids of gamepads that have at least one force feedback motor running, for example to
+    /// show which players are receiving rumble. See [`Gamepad::vibrating()`].
+    pub fn vibrating_gamepads(&self) -> Vec<GamepadId> {
+        self.ff_states
+            .lock()
+            .unwrap()
+            .motors
+            .iter()
+            .filter(|(_, motors)| motors.iter().any(|&m| m > 0))
+            .map(|(id, _)| GamepadId(id))
+            .collect()
+    }
+
+    /// Returns state of all gamepads together with value of counter. It can be later restored with
+    /// [`apply_state()`](Gilrs::apply_state), for example to implement save states or rewind.
+    pub fn serialize_state(&self) -> SavedState {
+        SavedState {
+            counter: self.counter,
+            gamepads: self.gamepads_data.iter().map(|d| d.state.save()).collect(),
+        }
+    }
+
+    /// Restores state of gamepads and counter saved with
+    /// [`serialize_state()`](Gilrs::serialize_state). Gamepads that didn't exist when state was
+    /// saved have their state cleared. Pending events are not affected.
+    pub fn apply_state(&mut self, state: &SavedState) {
+        self.counter = state.counter;
+        for (i, data) in self.gamepads_data.iter_mut().enumerate() {
+            match state.gamepads.get(i) {
+                Some(saved) => data.state.restore(saved),
+                None => data.state.clear(),
             }
-            Disconnected | Connected | Dropped | ForceFeedbackEffectCompleted => (),
         }
     }
 
+    /// Clears cached state of gamepad with given id, so all buttons are reported as released and
+    /// all axes as centered until new events arrive. Events are not generated and pending events
+    /// are not affected.
+    ///
+    /// State is also cleared automatically when gamepad disconnects.
+    pub fn reset_state(&mut self, id: GamepadId) {
+        if let Some(data) = self.gamepads_data.get_mut(id.0) {
+            data.state.clear();
+            data.lever = Lever::new(data.settings.lever_mode);
+        }
+    }
+
+    /// Registers `callback` that will be called with every button event (pressed, released,
+    /// repeated or changed) for `button`. If `id` is `None`, events from all gamepads are
+    /// delivered, otherwise only from gamepad with given id.
+    ///
+    /// Callbacks are invoked from [`update`](Gilrs::update), so they are not called if automatic
+    /// state updates are disabled and `update()` is not called manually.
+    ///
+    /// ```
+    /// use gilrs::{Button, EventType, Gilrs};
+    ///
+    /// let mut gilrs = Gilrs::new().unwrap();
+    /// let sub = gilrs.on_button(None, Button::South, |event| {
+    ///     if let EventType::ButtonPressed(..) = event.event {
+    ///         println!("{} pressed South", event.id);
+    ///     }
+    /// });
+    ///
+    /// while let Some(_) = gilrs.next_event() {}
+    /// gilrs.unsubscribe(sub);
+    /// ```
+    pub fn on_button<F>(
+        &mut self,
+        id: Option<GamepadId>,
+        button: Button,
+        callback: F,
+    ) -> SubscriptionId
+    where
+        F: FnMut(&Event) + Send + 'static,
+    {
+        self.subscriptions
+            .add(id, AxisOrBtn::Btn(button), Box::new(callback))
+    }
+
+    /// Registers `callback` that will be called with every axis event (changed, pressed or
+    /// released) for `axis`. If `id` is `None`, events from all gamepads are delivered, otherwise
+    /// only from gamepad with given id.
+    ///
+    /// See [`on_button`](Gilrs::on_button) for more details.
+    pub fn on_axis<F>(&mut self, id: Option<GamepadId>, axis: Axis, callback: F) -> SubscriptionId
+    where
+        F: FnMut(&Event) + Send + 'static,
+    {
+        self.subscriptions
+            .add(id, AxisOrBtn::Axis(axis), Box::new(callback))
+    }
+
+    /// Removes callback registered with [`on_button`](Gilrs::on_button) or
+    /// [`on_axis`](Gilrs::on_axis). Returns `false` if there was no such callback.
+    pub fn unsubscribe(&mut self, subscription: SubscriptionId) -> bool {
+        self.subscriptions.remove(subscription)
+    }
+
     /// Increases internal counter by one. Counter data is stored with state and can be used to
     /// determine when last event happened. You probably want to use this function in your update
     /// loop after processing events.
@@ -434,13 +1114,15 @@ impl Gilrs {
 
     fn finish_gamepads_creation(&mut self) {
         let tx = self.tx.clone();
-        for id in 0..self.inner.last_gamepad_hint() {
+        for id in self.gamepads_data.len()..self.inner.last_gamepad_hint() {
             let gamepad = self.inner.gamepad(id).unwrap();
             self.gamepads_data.push(GamepadData::new(
                 GamepadId(id),
                 tx.clone(),
+                self.ff_states.clone(),
                 gamepad,
                 &self.mappings,
+                GamepadSettings::default(),
             ))
         }
     }
@@ -467,7 +1149,7 @@ impl Gilrs {
     ///     # break;
     /// }
     /// ```
-    pub fn gamepad(&self, id: GamepadId) -> Gamepad {
+    pub fn gamepad(&self, id: GamepadId) -> Gamepad<'_> {
         Gamepad {
             inner: self.inner.gamepad(id.0).unwrap(),
             data: &self.gamepads_data[id.0],
@@ -505,46 +1187,463 @@ impl Gilrs {
         ConnectedGamepadsIterator(self, 0)
     }
 
-    /// Adds `ev` at the end of internal event queue. It can later be retrieved with `next_event()`.
-    pub fn insert_event(&mut self, ev: Event) {
-        self.events.push_back(ev);
-    }
-
-    pub(crate) fn ff_sender(&self) -> &Sender<Message> {
-        &self.tx
+    /// Returns devices that look like gamepads, but couldn't be opened because of missing
+    /// permissions, so they are not listed by [`gamepads()`](#method.gamepads).
+    ///
+    /// This can be used to tell the user how to fix access to the device, for example by
+    /// installing udev rules. Currently, only Linux reports such devices.
+    ///
+    /// ```
+    /// # let gilrs = gilrs::Gilrs::new().unwrap();
+    /// for device in gilrs.inaccessible_devices() {
+    ///     println!(
+    ///         "Can't access {} ({:?}), owned by group {:?}",
+    ///         device.path, device.name, device.group
+    ///     );
+    /// }
+    /// ```
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        self.inner.inaccessible_devices()
     }
 
-    /// Sets gamepad's mapping and returns SDL2 representation of them. Returned mappings may not be
-    /// compatible with SDL2 - if it is important, use
-    /// [`set_mapping_strict()`](#method.set_mapping_strict).
-    ///
-    /// The `name` argument can be a string slice with custom gamepad name or `None`. If `None`,
-    /// gamepad name reported by driver will be used.
+    /// Adds `ev` at the end of internal event queue. It can later be retrieved with `next_event()`.
     ///
-    /// # Errors
+    /// Inserted events are processed the same way as events from gamepads: they go through
+    /// default filters (if enabled) and update gamepad state, so this function can be used to
+    /// simulate input, for example in tutorials or tests. Events with id of gamepad that doesn't
+    /// exist are ignored.
     ///
-    /// This function return error if `name` contains comma, `mapping` have axis and button entry
-    /// for same element (for example `Axis::LetfTrigger` and `Button::LeftTrigger`) or gamepad does
-    /// not have any element with `EvCode` used in mapping. `Button::Unknown` and
-    /// `Axis::Unknown` are not allowd as keys to `mapping` – in this case,
-    /// `MappingError::UnknownElement` is returned.
+    /// ```
+    /// use gilrs::{Button, Event, EventType, Gilrs};
     ///
-    /// Error is also returned if this function is not implemented or gamepad is not connected.
+    /// let mut gilrs = Gilrs::new().unwrap();
+    /// let south = gilrs
+    ///     .gamepads()
+    ///     .find_map(|(id, gamepad)| Some((id, gamepad.button_code(Button::South)?)));
     ///
-    /// # Example
+    /// if let Some((id, code)) = south {
+    ///     gilrs.insert_event(Event::new(id, EventType::ButtonPressed(Button::South, code)));
     ///
+    ///     while gilrs.next_event().is_some() {}
+    ///     assert!(gilrs.gamepad(id).is_pressed(Button::South));
+    /// }
     /// ```
-    /// use gilrs::{Mapping, Button};
-    ///
-    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
-    /// let mut data = Mapping::new();
-    /// // …
+    pub fn insert_event(&mut self, ev: Event) {
+        if ev.id.0 >= self.gamepads_data.len() {
+            warn!("Ignoring event for gamepad {} that doesn't exist", ev.id);
+            return;
+        }
+
+        self.events.push_back(ev);
+        self.stats.max_queue_len = self.stats.max_queue_len.max(self.events.len());
+    }
+
+    /// Stores `payload` and adds `EventType::Payload` event referring to it to the event queue,
+    /// like [`insert_event()`](Self::insert_event). Returns handle to the payload.
     ///
-    /// // or `match gilrs.set_mapping(0, &data, None) {`
-    /// match gilrs.set_mapping(0, &data, "Custom name") {
-    ///     Ok(sdl) => println!("SDL2 mapping: {}", sdl),
-    ///     Err(e) => println!("Failed to set mapping: {}", e),
-    /// };
+    /// ```
+    /// use gilrs::ev::{Payload, PayloadKind};
+    /// use gilrs::{EventType, Gilrs};
+    ///
+    /// let mut gilrs = Gilrs::new().unwrap();
+    /// if let Some((id, _)) = gilrs.gamepads().next() {
+    ///     let text = Payload::Text("gg".to_owned());
+    ///     gilrs.insert_payload_event(id, text.clone());
+    ///
+    ///     while let Some(ev) = gilrs.next_event() {
+    ///         if let EventType::Payload(PayloadKind::Text, _) = ev.event {
+    ///             let ev = gilrs.event_ref(&ev);
+    ///             assert_eq!(ev.payload, Some(&text));
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn insert_payload_event(&mut self, id: GamepadId, payload: Payload) -> PayloadId {
+        let kind = payload.kind();
+        let payload_id = self.payloads.insert(payload);
+        self.insert_event(Event::new(id, EventType::Payload(kind, payload_id)));
+
+        payload_id
+    }
+
+    /// Returns payload with given handle or `None` if it was already dropped. See
+    /// [`PayloadId`](crate::ev::PayloadId).
+    pub fn payload(&self, id: PayloadId) -> Option<&Payload> {
+        self.payloads.get(id)
+    }
+
+    /// Returns event together with its payload. Use
+    /// [`EventRef::into_owned()`](crate::ev::EventRef::into_owned) to keep the event for longer.
+    pub fn event_ref(&self, ev: &Event) -> EventRef<'_> {
+        self.payloads.resolve(ev)
+    }
+
+    /// Returns statistics collected since `Gilrs` was created or since last call to
+    /// `reset_stats()`.
+    ///
+    /// ```
+    /// use gilrs::Gilrs;
+    ///
+    /// let mut gilrs = Gilrs::new().unwrap();
+    ///
+    /// // Game loop
+    /// loop {
+    ///     while let Some(event) = gilrs.next_event() {
+    ///         // ...
+    ///     }
+    ///
+    ///     let stats = gilrs.stats();
+    ///     println!("{} events, polling took {:?}", stats.events, stats.poll_time);
+    ///     gilrs.reset_stats();
+    ///     # break;
+    /// }
+    /// ```
+    pub fn stats(&self) -> Stats {
+        Stats {
+            ff_commands: self.ff_commands.load(AtomicOrdering::Relaxed) - self.ff_commands_base,
+            ..self.stats
+        }
+    }
+
+    /// Sets all counters returned by `stats()` to zero. Call it at the end of every frame to get
+    /// per frame statistics.
+    pub fn reset_stats(&mut self) {
+        self.stats = Stats::default();
+        self.ff_commands_base = self.ff_commands.load(AtomicOrdering::Relaxed);
+    }
+
+    /// Returns how many missed repeats are emitted at once by
+    /// [`Repeat`](crate::ev::filter::Repeat) filter. See
+    /// [`GilrsBuilder::set_repeat_max_catch_up()`].
+    pub fn repeat_max_catch_up(&self) -> u32 {
+        self.repeat_max_catch_up
+    }
+
+    /// Returns how often backend reads state of gamepads or `None` if backend doesn't poll them.
+    pub fn poll_interval(&self) -> Option<Duration> {
+        self.inner.poll_interval()
+    }
+
+    /// Sets how often backend reads state of gamepads. Shorter interval reduces input latency at
+    /// the cost of CPU usage.
+    ///
+    /// Only backends that poll gamepads are affected. XInput and Windows Gaming Input backends
+    /// read gamepads in separate thread, every 10 ms and 8 ms by default. Wasm backend calls
+    /// `getGamepads()` from `next_event()`, by default on every call; with nonzero interval it
+    /// skips calls that come too early, independently of `requestAnimationFrame()`. Linux and
+    /// macOS backends are notified about events by the OS, so interval is ignored there.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.inner.set_poll_interval(interval);
+    }
+
+    /// Looks for connected gamepads if `Gilrs` was created with
+    /// [`GilrsBuilder::with_deferred_enumeration()`]. `EventType::Connected` is emitted for every
+    /// gamepad found. Does nothing if devices were already scanned.
+    ///
+    /// Scanning may take a while on some platforms, so it's a good idea to call this function
+    /// while loading screen is shown.
+    ///
+    /// ```
+    /// use gilrs::{EventType, GilrsBuilder};
+    ///
+    /// let mut gilrs = GilrsBuilder::new().with_deferred_enumeration().build().unwrap();
+    /// assert_eq!(gilrs.gamepads().count(), 0);
+    ///
+    /// // Show loading screen…
+    /// gilrs.scan_devices().unwrap();
+    ///
+    /// while let Some(ev) = gilrs.next_event() {
+    ///     if let EventType::Connected = ev.event {
+    ///         println!("{} connected", gilrs.gamepad(ev.id).name());
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::Other` if backend could not be initialized. On platforms that are not
+    /// supported, no gamepad is found, but this function succeeds.
+    #[allow(clippy::result_large_err)]
+    pub fn scan_devices(&mut self) -> Result<(), Error> {
+        if self.inner.is_scanned() {
+            return Ok(());
+        }
+
+        self.inner.scan_devices().map_err(Error::Other)?;
+        self.finish_gamepads_creation();
+
+        let time = utils::time_now();
+        for id in 0..self.gamepads_data.len() {
+            if self.inner.gamepad(id).is_some_and(|g| g.is_connected()) {
+                self.events.push_back(Event {
+                    id: GamepadId(id),
+                    event: EventType::Connected,
+                    time,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets user-visible label of the gamepad, for example name chosen by the player. `None`
+    /// removes the label. The label is kept when gamepad reconnects.
+    ///
+    /// See [`Gamepad::name()`](struct.Gamepad.html#method.name).
+    pub fn set_label<'b, O: Into<Option<&'b str>>>(&mut self, id: GamepadId, label: O) {
+        if let Some(data) = self.gamepads_data.get_mut(id.0) {
+            data.settings.label = label.into().map(ToOwned::to_owned);
+        }
+    }
+
+    /// Sets table that replaces raw codes reported by gamepad before they are mapped. Empty table
+    /// removes remapping. The table is kept when gamepad reconnects. `EventType::MappingChanged`
+    /// is emitted if the table differs from the previous one.
+    ///
+    /// See [`CodeRemap`] for details.
+    pub fn set_code_remap(&mut self, id: GamepadId, remap: CodeRemap) {
+        if let Some(data) = self.gamepads_data.get_mut(id.0) {
+            if data.settings.code_remap != remap {
+                data.settings.code_remap = remap;
+                self.mapping_changed(id);
+            }
+        }
+    }
+
+    /// Maps `code` of gamepad `id` to `btn`, on top of gamepad's current mapping. `btn` is no
+    /// longer reported for code it was mapped to before. Remaps are kept when gamepad reconnects
+    /// or its mapping changes and can be removed with
+    /// [`clear_element_remaps()`](#method.clear_element_remaps). `EventType::MappingChanged` is
+    /// emitted.
+    ///
+    /// `code` is compared after [`CodeRemap`] is applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MappingError::UnknownElement` if `btn` is `Button::Unknown`,
+    /// `MappingError::InvalidCode` if gamepad doesn't have element with `code` and
+    /// `MappingError::NotConnected` if gamepad is not connected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gilrs::{Button, Gilrs};
+    ///
+    /// let mut gilrs = Gilrs::new().unwrap();
+    /// let ids: Vec<_> = gilrs.gamepads().map(|(id, _)| id).collect();
+    /// for id in ids {
+    ///     // Use element currently reported as `East` as `South` button.
+    ///     if let Some(code) = gilrs.gamepad(id).button_code(Button::East) {
+    ///         gilrs.remap_button(id, code, Button::South).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn remap_button(
+        &mut self,
+        id: GamepadId,
+        code: Code,
+        btn: Button,
+    ) -> Result<(), MappingError> {
+        if btn == Button::Unknown {
+            return Err(MappingError::UnknownElement);
+        }
+
+        self.remap_element(id, code, AxisOrBtn::Btn(btn))
+    }
+
+    /// Maps `code` of gamepad `id` to `axis`, on top of gamepad's current mapping. Values of the
+    /// axis are not inverted, even if previous mapping of `code` inverted them.
+    ///
+    /// See [`remap_button()`](#method.remap_button) for details and errors.
+    pub fn remap_axis(
+        &mut self,
+        id: GamepadId,
+        code: Code,
+        axis: Axis,
+    ) -> Result<(), MappingError> {
+        if axis == Axis::Unknown {
+            return Err(MappingError::UnknownElement);
+        }
+
+        self.remap_element(id, code, AxisOrBtn::Axis(axis))
+    }
+
+    fn remap_element(
+        &mut self,
+        id: GamepadId,
+        code: Code,
+        el: AxisOrBtn,
+    ) -> Result<(), MappingError> {
+        let gamepad = match self.inner.gamepad(id.0) {
+            Some(gamepad) if gamepad.is_connected() => gamepad,
+            _ => return Err(MappingError::NotConnected),
+        };
+        let data = &mut self.gamepads_data[id.0];
+
+        let has_code = gamepad
+            .buttons()
+            .iter()
+            .chain(gamepad.axes())
+            .any(|&nec| data.settings.code_remap.get(Code(nec)) == code);
+        if !has_code {
+            return Err(MappingError::InvalidCode(code));
+        }
+
+        let remaps = &mut data.settings.element_remaps;
+        remaps.retain(|&(c, e)| c != code && e != el);
+        remaps.push((code, el));
+        self.rebuild_mapping(id);
+
+        Ok(())
+    }
+
+    /// Removes all remaps set with [`remap_button()`](#method.remap_button) and
+    /// [`remap_axis()`](#method.remap_axis) and restores mapping gamepad had before them.
+    /// `EventType::MappingChanged` is emitted if there were any remaps.
+    pub fn clear_element_remaps(&mut self, id: GamepadId) {
+        match self.gamepads_data.get_mut(id.0) {
+            Some(data) if !data.settings.element_remaps.is_empty() => {
+                data.settings.element_remaps.clear()
+            }
+            _ => return,
+        }
+
+        if self.inner.gamepad(id.0).is_some_and(|g| g.is_connected()) {
+            self.rebuild_mapping(id);
+        }
+    }
+
+    // Resolves mapping of connected gamepad `id` again and applies element remaps on top of it.
+    fn rebuild_mapping(&mut self, id: GamepadId) {
+        match self.gamepads_data[id.0].runtime_mapping.take() {
+            Some(sdl) => self.restore_runtime_mapping(id, sdl),
+            None => {
+                let gamepad = self.inner.gamepad(id.0).unwrap();
+                let (mapping, mapping_layer) = resolve_mapping(gamepad, &self.mappings);
+                let data = &mut self.gamepads_data[id.0];
+                data.set_mapping(mapping);
+                data.mapping_layer = mapping_layer;
+            }
+        }
+        self.mapping_changed(id);
+    }
+
+    /// Sets how lever of arcade stick or hitbox connected as `id` is reported. The mode is kept
+    /// when gamepad reconnects. `EventType::MappingChanged` is emitted if the mode differs from
+    /// the previous one.
+    ///
+    /// See [`LeverMode`] for details.
+    pub fn set_lever_mode(&mut self, id: GamepadId, mode: LeverMode) {
+        if let Some(data) = self.gamepads_data.get_mut(id.0) {
+            if data.settings.lever_mode != mode {
+                data.settings.lever_mode = mode;
+                data.lever = Lever::new(mode);
+                self.mapping_changed(id);
+            }
+        }
+    }
+
+    // Queues events of elements emulated by lever of gamepad `id`.
+    fn emulate_lever(&mut self, id: GamepadId, time: SystemTime, event: &EventType) {
+        let data = match self.gamepads_data.get_mut(id.0) {
+            Some(data) if data.settings.lever_mode != LeverMode::Mapped => data,
+            _ => return,
+        };
+
+        let mut out = Vec::new();
+        data.lever.handle(
+            event,
+            self.axis_to_btn_pressed,
+            self.axis_to_btn_released,
+            &mut out,
+        );
+
+        for output in out {
+            match output {
+                LeverOutput::Button(btn, pressed, code) => {
+                    let code = data.button_code(btn).unwrap_or(code);
+                    let event = if pressed {
+                        EventType::ButtonPressed(btn, code)
+                    } else {
+                        EventType::ButtonReleased(btn, code)
+                    };
+                    let value = if pressed { 1.0 } else { 0.0 };
+
+                    self.events.push_back(Event { id, event, time });
+                    self.events.push_back(Event {
+                        id,
+                        time,
+                        event: EventType::ButtonChanged(btn, value, code),
+                    });
+                }
+                LeverOutput::Axis(axis, value, code) => {
+                    let code = data.axis_code(axis).unwrap_or(code);
+                    self.events.push_back(Event {
+                        id,
+                        time,
+                        event: EventType::AxisChanged(axis, value, code),
+                    });
+                }
+            }
+        }
+    }
+
+    fn mapping_changed(&mut self, id: GamepadId) {
+        self.events
+            .push_back(Event::new(id, EventType::MappingChanged));
+    }
+
+    pub(crate) fn ff_sender(&self) -> &Sender<Message> {
+        &self.tx
+    }
+
+    /// Creates [`FfBatch`](crate::ff::FfBatch) that submits commands for multiple force feedback
+    /// effects at once.
+    pub fn ff_batch(&self) -> FfBatch {
+        FfBatch::new(self.tx.clone())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn test_backend(&mut self) -> &mut RawGilrs {
+        &mut self.inner
+    }
+
+    pub(crate) fn ff_states(&self) -> &EffectStates {
+        &self.ff_states
+    }
+
+    /// Sets gamepad's mapping and returns SDL2 representation of them. Returned mappings may not be
+    /// compatible with SDL2 - if it is important, use
+    /// [`set_mapping_strict()`](#method.set_mapping_strict).
+    ///
+    /// The `name` argument can be a string slice with custom gamepad name or `None`. If `None`,
+    /// gamepad name reported by driver will be used.
+    ///
+    /// # Errors
+    ///
+    /// This function return error if `name` contains comma, same `Code` is mapped to more than
+    /// one element (`MappingError::DuplicatedCode`), only one axis of stick is mapped
+    /// (`MappingError::MissingElement`) or gamepad does not have any element with `EvCode` used in
+    /// mapping. `Button::Unknown` and `Axis::Unknown` are not allowd as keys to `mapping` – in
+    /// this case, `MappingError::UnknownElement` is returned. Mapping of gamepad is not changed if
+    /// error is returned.
+    ///
+    /// Error is also returned if this function is not implemented or gamepad is not connected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gilrs::{Mapping, Button};
+    ///
+    /// # let mut gilrs = gilrs::Gilrs::new().unwrap();
+    /// let mut data = Mapping::new();
+    /// // …
+    ///
+    /// // or `match gilrs.set_mapping(0, &data, None) {`
+    /// match gilrs.set_mapping(0, &data, "Custom name") {
+    ///     Ok(sdl) => println!("SDL2 mapping: {}", sdl),
+    ///     Err(e) => println!("Failed to set mapping: {}", e),
+    /// };
     /// ```
     ///
     /// See also `examples/mapping.rs`.
@@ -572,9 +1671,7 @@ impl Gilrs {
                 Uuid::from_bytes(gamepad.uuid()),
             )?;
 
-            // We checked if gamepad is connected, so it should never panic
-            let data = &mut self.gamepads_data[gamepad_id];
-            data.mapping = mapping;
+            self.apply_runtime_mapping(gamepad_id, mapping, s.clone());
 
             Ok(s)
         } else {
@@ -582,6 +1679,63 @@ impl Gilrs {
         }
     }
 
+    /// Sets gamepad's mapping from SDL2 mapping string (GUID is ignored).
+    ///
+    /// Unlike mappings from database, where entries that can't be used with gamepad are skipped,
+    /// mapping is applied only if it is entirely valid. Otherwise mapping of gamepad is not
+    /// changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MappingError::InvalidSdlMapping` with offset of invalid entry if `mapping` can't
+    /// be parsed or references button, axis or hat that gamepad doesn't have,
+    /// `MappingError::DuplicatedElement` if more than one entry maps to the same button or axis
+    /// and `MappingError::MissingElement` if only one axis of stick is mapped.
+    ///
+    /// Error is also returned if gamepad is not connected.
+    pub fn set_sdl_mapping(
+        &mut self,
+        gamepad_id: usize,
+        mapping: &str,
+    ) -> Result<(), MappingError> {
+        match self.inner.gamepad(gamepad_id) {
+            Some(gamepad) if gamepad.is_connected() => {
+                let parsed = Mapping::from_sdl(mapping, gamepad.buttons(), gamepad.axes())?;
+                self.apply_runtime_mapping(gamepad_id, parsed, mapping.to_owned());
+
+                Ok(())
+            }
+            _ => Err(MappingError::NotConnected),
+        }
+    }
+
+    fn apply_runtime_mapping(&mut self, gamepad_id: usize, mapping: Mapping, sdl: String) {
+        // Callers checked if gamepad is connected, so it should never panic
+        let data = &mut self.gamepads_data[gamepad_id];
+        data.set_mapping(mapping);
+        data.mapping_layer = Some(MappingLayer::Runtime);
+        data.runtime_mapping = Some(sdl);
+        self.mapping_changed(GamepadId(gamepad_id));
+    }
+
+    // Applies mapping kept from before gamepad reconnected, see `set_keep_runtime_mappings()`.
+    fn restore_runtime_mapping(&mut self, id: GamepadId, sdl: String) {
+        let gamepad = match self.inner.gamepad(id.0) {
+            Some(gamepad) => gamepad,
+            None => return,
+        };
+
+        match Mapping::from_sdl(&sdl, gamepad.buttons(), gamepad.axes()) {
+            Ok(mapping) => {
+                let data = &mut self.gamepads_data[id.0];
+                data.set_mapping(mapping);
+                data.mapping_layer = Some(MappingLayer::Runtime);
+                data.runtime_mapping = Some(sdl);
+            }
+            Err(e) => warn!("Mapping of gamepad {} can't be restored: {}", id, e),
+        }
+    }
+
     /// Similar to [`set_mapping()`](#method.set_mapping) but returned string should be compatible
     /// with SDL2.
     ///
@@ -619,26 +1773,48 @@ impl Gilrs {
 
 /// Allow to create `Gilrs ` with customized behaviour.
 pub struct GilrsBuilder {
-    mappings: MappingDb,
+    user_mappings: Vec<UserMappings>,
     default_filters: bool,
     axis_to_btn_pressed: f32,
     axis_to_btn_released: f32,
+    trigger_range: TriggerRange,
+    trigger_deadzone: TriggerDeadzone,
+    filter_check: FilterCheck,
+    idle_timeout: Duration,
+    idle_events: bool,
+    stuck_button_timeout: Option<Duration>,
+    repeat_max_catch_up: u32,
     update_state: bool,
+    poll_interval: Option<Duration>,
+    deferred_enumeration: bool,
     env_mappings: bool,
     included_mappings: bool,
+    watch_mapping_files: bool,
+    keep_runtime_mappings: bool,
 }
 
 impl GilrsBuilder {
     /// Create builder with default settings. Use `build()` to create `Gilrs`.
     pub fn new() -> Self {
         GilrsBuilder {
-            mappings: MappingDb::new(),
+            user_mappings: Vec::new(),
             default_filters: true,
             axis_to_btn_pressed: 0.75,
             axis_to_btn_released: 0.65,
+            trigger_range: TriggerRange::MinusOneToOne,
+            trigger_deadzone: TriggerDeadzone::default(),
+            filter_check: FilterCheck::default(),
+            idle_timeout: Duration::from_secs(10),
+            idle_events: false,
+            stuck_button_timeout: None,
+            repeat_max_catch_up: 3,
             update_state: true,
+            poll_interval: None,
+            deferred_enumeration: false,
             env_mappings: true,
             included_mappings: true,
+            watch_mapping_files: false,
+            keep_runtime_mappings: false,
         }
     }
 
@@ -651,15 +1827,52 @@ impl GilrsBuilder {
         self
     }
 
-    /// Adds SDL mappings.
+    /// Adds SDL mappings. They take precedence over included mappings and mappings from
+    /// environment variable. See [`MappingLayer`](enum.MappingLayer.html).
     pub fn add_mappings(mut self, mappings: &str) -> Self {
-        self.mappings.insert(mappings);
+        self.user_mappings
+            .push(UserMappings::Str(mappings.to_owned()));
 
         self
     }
 
-    /// If true, will add SDL mappings from `SDL_GAMECONTROLLERCONFIG` environment variable.
-    /// Defaults to true.
+    /// Adds SDL mappings from file. Like mappings added with
+    /// [`add_mappings()`](Self::add_mappings), they use `MappingLayer::User`. File is read by
+    /// `build()`; if it can't be read, warning is logged and file is ignored.
+    pub fn add_mappings_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.user_mappings
+            .push(UserMappings::File(path.as_ref().to_owned()));
+
+        self
+    }
+
+    /// If true, files added with [`add_mappings_file()`](Self::add_mappings_file) and file named
+    /// by `SDL_GAMECONTROLLERCONFIG_FILE` (if environment mappings are enabled) are checked for
+    /// changes about once a second, when events are processed. When any of them changes,
+    /// mappings are reloaded and applied to connected gamepads, except gamepads with mapping set
+    /// by [`Gilrs::set_mapping()`]. `EventType::MappingChanged` is emitted for every gamepad
+    /// whose mapping changed.
+    ///
+    /// This is mostly useful when writing mappings for unusual gamepads. Defaults to false.
+    pub fn set_watch_mapping_files(mut self, enabled: bool) -> Self {
+        self.watch_mapping_files = enabled;
+
+        self
+    }
+
+    /// If true, mapping set with [`Gilrs::set_mapping()`] or [`Gilrs::set_sdl_mapping()`] is kept
+    /// when gamepad reconnects. Mapping is validated again with reconnected device, if it's no
+    /// longer valid, warning is logged and mapping from database is used. Defaults to false.
+    pub fn set_keep_runtime_mappings(mut self, enabled: bool) -> Self {
+        self.keep_runtime_mappings = enabled;
+
+        self
+    }
+
+    /// If true, will add SDL mappings from `SDL_GAMECONTROLLERCONFIG` and
+    /// `GILRS_GAMECONTROLLERCONFIG` environment variables, and from file named by
+    /// `SDL_GAMECONTROLLERCONFIG_FILE`. Variables can contain multiple mappings separated by new
+    /// lines. Defaults to true.
     pub fn add_env_mappings(mut self, env_mappings: bool) -> Self {
         self.env_mappings = env_mappings;
 
@@ -685,6 +1898,88 @@ impl GilrsBuilder {
         self
     }
 
+    /// Sets range of values reported for analog triggers, `Axis::LeftZ` and `Axis::RightZ`.
+    /// Triggers mapped to `Button::LeftTrigger2` and `Button::RightTrigger2` always report values
+    /// from 0.0 to 1.0.
+    ///
+    /// Defaults to `TriggerRange::MinusOneToOne`.
+    pub fn set_trigger_range(mut self, range: TriggerRange) -> Self {
+        self.trigger_range = range;
+
+        self
+    }
+
+    /// Sets dead zone that [`deadzone`](crate::ev::filter::deadzone) filter applies to analog
+    /// triggers (`Axis::LeftZ`, `Axis::RightZ`, `Button::LeftTrigger2` and
+    /// `Button::RightTrigger2`) instead of dead zone used for sticks.
+    ///
+    /// Defaults to `TriggerDeadzone::default()`.
+    pub fn set_trigger_deadzone(mut self, deadzone: TriggerDeadzone) -> Self {
+        self.trigger_deadzone = deadzone;
+
+        self
+    }
+
+    /// Sets what happens when filter applied with
+    /// [`Filter::filter_ev()`](crate::Filter::filter_ev) returns `None` for `Some(event)`, which
+    /// would end event loop.
+    ///
+    /// Defaults to `FilterCheck::DebugPanic`.
+    pub fn set_filter_check(mut self, check: FilterCheck) -> Self {
+        self.filter_check = check;
+
+        self
+    }
+
+    /// Sets how long gamepad has to produce no input to be considered idle. First input after
+    /// that generates `EventType::GamepadActivity`.
+    ///
+    /// Defaults to 10 seconds.
+    pub fn set_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+
+        self
+    }
+
+    /// If `true`, `EventType::GamepadIdle` is emitted when gamepad produces no input for longer
+    /// than idle timeout (see [`set_idle_timeout()`](Self::set_idle_timeout)).
+    ///
+    /// Defaults to `false`.
+    pub fn set_idle_events(mut self, enabled: bool) -> Self {
+        self.idle_events = enabled;
+
+        self
+    }
+
+    /// Enables watchdog that releases buttons whose release event was missed, for example because
+    /// of driver hiccup. `None` (default) disables it.
+    ///
+    /// When gamepad produces no input for `timeout`, every button that is pressed in gamepad state
+    /// is checked and, if device reports it as released, `ButtonReleased` and
+    /// `ButtonChanged` events are generated for it. Buttons are released only when the OS confirms
+    /// it, so the watchdog works only on Linux, Windows Gaming Input and XInput backends. macOS and
+    /// web backends can't read button state from device and the watchdog does nothing there.
+    ///
+    /// Independently of this setting, buttons that are pressed when gamepad disconnects are
+    /// released after `Disconnected` event, so every `ButtonPressed` event is paired with
+    /// `ButtonReleased`.
+    pub fn set_stuck_button_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.stuck_button_timeout = timeout;
+
+        self
+    }
+
+    /// Sets how many repeats that were missed because events were not processed for a while are
+    /// emitted at once by [`Repeat`](crate::ev::filter::Repeat) filter. Values lower than 1 are
+    /// treated as 1.
+    ///
+    /// Defaults to 3.
+    pub fn set_repeat_max_catch_up(mut self, max_catch_up: u32) -> Self {
+        self.repeat_max_catch_up = max_catch_up;
+
+        self
+    }
+
     /// Disable or enable automatic state updates. You should use this if you use custom filters;
     /// in this case you have to update state manually anyway.
     pub fn set_update_state(mut self, enabled: bool) -> Self {
@@ -693,17 +1988,43 @@ impl GilrsBuilder {
         self
     }
 
-    /// Creates `Gilrs`.
-    pub fn build(mut self) -> Result<Gilrs, Error> {
-        if self.included_mappings {
-            self.mappings.add_included_mappings();
-        }
+    /// Sets how often backend reads state of gamepads, if it polls them. Shorter interval reduces
+    /// input latency at the cost of CPU usage. See [`Gilrs::set_poll_interval()`].
+    ///
+    /// Defaults to backend's own interval.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = Some(interval);
 
-        if self.env_mappings {
-            self.mappings.add_env_mappings();
-        }
+        self
+    }
 
-        debug!("Loaded {} mappings.", self.mappings.len());
+    /// Don't look for gamepads when `Gilrs` is created. No gamepad is connected and no event is
+    /// emitted until [`Gilrs::scan_devices()`] is called, which makes creating `Gilrs` cheap.
+    ///
+    /// Because backend isn't initialized by `build()`, it never returns `Error::NotImplemented`
+    /// with this option.
+    pub fn with_deferred_enumeration(mut self) -> Self {
+        self.deferred_enumeration = true;
+
+        self
+    }
+
+    /// Creates `Gilrs`.
+    #[allow(clippy::result_large_err)]
+    pub fn build(self) -> Result<Gilrs, Error> {
+        let sources = MappingSources {
+            included: self.included_mappings,
+            env: self.env_mappings,
+            user: self.user_mappings,
+        };
+        let mappings = sources.build();
+        let mapping_watch = if self.watch_mapping_files {
+            Some(MappingWatch::new(sources, utils::time_now()))
+        } else {
+            None
+        };
+
+        debug!("Loaded {} mappings.", mappings.len());
 
         if self.axis_to_btn_pressed <= self.axis_to_btn_released
             || self.axis_to_btn_pressed < 0.0
@@ -715,7 +2036,12 @@ impl GilrsBuilder {
         }
 
         let mut is_dummy = false;
-        let inner = match gilrs_core::Gilrs::new() {
+        let core = if self.deferred_enumeration {
+            Ok(RawGilrs::new_deferred())
+        } else {
+            RawGilrs::new()
+        };
+        let mut inner = match core {
             Ok(g) => g,
             Err(PlatformError::NotImplemented(g)) => {
                 is_dummy = true;
@@ -723,10 +2049,15 @@ impl GilrsBuilder {
                 g
             }
             Err(PlatformError::Other(e)) => return Err(Error::Other(e)),
+            #[cfg_attr(test, allow(unreachable_patterns))]
             Err(_) => unimplemented!(),
         };
 
-        let (tx, rx) = server::init();
+        if let Some(interval) = self.poll_interval {
+            inner.set_poll_interval(interval);
+        }
+
+        let (tx, rx, ff_commands, ff_states) = server::init();
 
         let mut gilrs = Gilrs {
             inner,
@@ -734,13 +2065,31 @@ impl GilrsBuilder {
             tx,
             rx,
             counter: 0,
-            mappings: self.mappings,
+            mappings,
+            mapping_watch,
+            keep_runtime_mappings: self.keep_runtime_mappings,
             default_filters: self.default_filters,
             events: VecDeque::new(),
+            latched: VecDeque::new(),
+            payloads: PayloadStore::default(),
+            remote_states: HashMap::new(),
             axis_to_btn_pressed: self.axis_to_btn_pressed,
             axis_to_btn_released: self.axis_to_btn_released,
+            trigger_range: self.trigger_range,
+            trigger_deadzone: self.trigger_deadzone,
+            filter_check: self.filter_check,
+            idle_timeout: self.idle_timeout,
+            idle_events: self.idle_events,
+            stuck_button_timeout: self.stuck_button_timeout,
+            repeat_max_catch_up: self.repeat_max_catch_up,
+            last_active: None,
             update_state: self.update_state,
             gamepads_data: Vec::new(),
+            subscriptions: Subscriptions::default(),
+            stats: Stats::default(),
+            ff_commands,
+            ff_commands_base: 0,
+            ff_states,
         };
         gilrs.finish_gamepads_creation();
 
@@ -788,31 +2137,80 @@ impl<'a> Iterator for ConnectedGamepadsIterator<'a> {
 #[derive(Debug, Copy, Clone)]
 pub struct Gamepad<'a> {
     data: &'a GamepadData,
-    inner: &'a gilrs_core::Gamepad,
+    inner: &'a RawGamepad,
 }
 
 impl<'a> Gamepad<'a> {
-    /// Returns the mapping name if it exists otherwise returns the os provided name.
+    /// Returns name that should be displayed to the user.
+    ///
+    /// This is the first available of: [`label()`](#method.label),
+    /// [`map_name()`](#method.map_name) and [`product_name()`](#method.product_name).
     pub fn name(&self) -> &str {
-        if let Some(map_name) = self.map_name() {
+        if let Some(label) = self.label() {
+            label
+        } else if let Some(map_name) = self.map_name() {
             map_name
         } else {
-            self.os_name()
+            self.product_name()
         }
     }
 
+    /// Returns label set with [`Gilrs::set_label()`](struct.Gilrs.html#method.set_label).
+    pub fn label(&self) -> Option<&str> {
+        self.data.settings.label.as_deref()
+    }
+
+    /// Returns remap table set with
+    /// [`Gilrs::set_code_remap()`](struct.Gilrs.html#method.set_code_remap).
+    pub fn code_remap(&self) -> &CodeRemap {
+        &self.data.settings.code_remap
+    }
+
+    /// Returns remaps set with
+    /// [`Gilrs::remap_button()`](struct.Gilrs.html#method.remap_button) and
+    /// [`Gilrs::remap_axis()`](struct.Gilrs.html#method.remap_axis), in order they were set.
+    pub fn element_remaps(&self) -> &[(Code, AxisOrBtn)] {
+        &self.data.settings.element_remaps
+    }
+
+    /// Returns lever mode set with
+    /// [`Gilrs::set_lever_mode()`](struct.Gilrs.html#method.set_lever_mode).
+    pub fn lever_mode(&self) -> LeverMode {
+        self.data.settings.lever_mode
+    }
+
+    /// Returns time elapsed since gamepad produced last input event or, if there wasn't any,
+    /// since it was connected. Like gamepad state, this is updated by
+    /// [`Gilrs::update()`](struct.Gilrs.html#method.update).
+    pub fn idle_for(&self) -> Duration {
+        utils::time_now()
+            .duration_since(self.data.last_activity)
+            .unwrap_or_default()
+    }
+
     /// if `mapping_source()` is `SdlMappings` returns the name of the mapping used by the gamepad.
     /// Otherwise returns `None`.
     pub fn map_name(&self) -> Option<&str> {
         self.data.map_name()
     }
 
-    /// Returns the name of the gamepad supplied by the OS.
+    /// Returns the name of the gamepad supplied by the OS, as is.
+    ///
+    /// Depending on the platform and driver, it may contain vendor name or trailing whitespace.
+    /// See also [`product_name()`](#method.product_name).
     pub fn os_name(&self) -> &str {
         self.inner.name()
     }
 
-    /// Returns gamepad's UUID.
+    /// Returns the name of the gamepad supplied by the OS, with control characters removed,
+    /// whitespace collapsed and well-known vendor prefixes (like "Sony Interactive
+    /// Entertainment") stripped.
+    pub fn product_name(&self) -> &str {
+        &self.data.product_name
+    }
+
+    /// Returns gamepad's UUID. Bytes have the same layout as SDL joystick GUID without CRC, see
+    /// [`guid()`](Self::guid).
     ///
     /// It is recommended to process with the [UUID crate](https://crates.io/crates/uuid).
     /// Use `Uuid::from_bytes` method to create a `Uuid` from the returned bytes.
@@ -820,6 +2218,18 @@ impl<'a> Gamepad<'a> {
         self.inner.uuid()
     }
 
+    /// Returns gamepad's SDL GUID without CRC. Its string representation is the same as used by
+    /// SDL_GameControllerDB and older SDL versions.
+    pub fn guid(&self) -> Guid {
+        Guid::from_bytes(self.inner.uuid())
+    }
+
+    /// Returns gamepad's SDL GUID with CRC of its name, like SDL 2.26 and newer report it. Nil GUID
+    /// is returned if [`guid()`](Self::guid) is nil.
+    pub fn guid_with_crc(&self) -> Guid {
+        guid_with_crc(self.inner)
+    }
+
     /// Returns the vendor ID, as assigned by the USB-IF, when available.
     pub fn vendor_id(&self) -> Option<u16> {
         self.inner.vendor_id()
@@ -835,6 +2245,13 @@ impl<'a> Gamepad<'a> {
         &self.data.state
     }
 
+    /// Returns copy of cached gamepad state that isn't borrowed from `Gilrs`, for example to
+    /// compare it with state in the next frame. This is cheap, data is only copied when state
+    /// changes.
+    pub fn state_cloned(&self) -> GamepadState {
+        self.data.state.clone()
+    }
+
     /// Returns true if gamepad is connected.
     pub fn is_connected(&self) -> bool {
         self.inner.is_connected()
@@ -882,6 +2299,97 @@ impl<'a> Gamepad<'a> {
         self.inner.power_info()
     }
 
+    /// Returns XInput user index (0–3) of the gamepad.
+    ///
+    /// Other libraries that use XInput (for example audio APIs for headsets plugged into the
+    /// controller) identify devices by this index, so it can be used to correlate them with
+    /// gilrs' gamepads. `EventType::XInputUserIndexChanged` is emitted when it changes.
+    ///
+    /// Returns `None` if gamepad is disconnected or is not driven by XInput. Currently, only
+    /// Windows `xinput` backend provides this information. With `xinput` backend, index is the
+    /// slot gamepad was connected to and it doesn't change until gamepad is disconnected. Windows
+    /// Gaming Input (default `wgi` backend) doesn't expose XInput slot of its controllers, so it
+    /// always returns `None`.
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        self.inner.xinput_user_index()
+    }
+
+    /// Returns state of headset connected to gamepad's audio jack. See
+    /// [`HeadsetState`](enum.HeadsetState.html) for details.
+    ///
+    /// This can be used to decide whether to offer routing voice chat or game audio to the
+    /// controller. `EventType::HeadsetChanged` is emitted when the state changes.
+    ///
+    /// Currently, headset state is reported on Linux (for drivers that expose audio jack switches
+    /// on gamepad's event device or on other event device of the same controller, like
+    /// "Headset Jack" device of DualSense) and with Windows `wgi` backend. On Linux, DualShock 4
+    /// driver doesn't report audio jack, so its state is unknown. Other platforms and backends
+    /// return `HeadsetState::Unknown`.
+    pub fn headset_state(&self) -> HeadsetState {
+        self.inner.headset_state()
+    }
+
+    /// Returns how gamepad is connected to the computer. See
+    /// [`ConnectionType`](enum.ConnectionType.html) for details.
+    ///
+    /// When gamepad reconnects over different transport, for example Bluetooth after USB cable
+    /// was unplugged, and keeps its ID, `EventType::ConnectionTypeChanged` is emitted right after
+    /// `EventType::Connected`. On Linux gamepad's UUID includes bus type, so gamepad is recognized
+    /// by vendor, product and unique identifier (usually Bluetooth address) reported by driver.
+    /// Drivers of DualShock 4 and DualSense report it over both USB and Bluetooth, but for
+    /// gamepads without it, or with different product ID for each bus, new ID is assigned. With
+    /// Windows `wgi` backend gamepad always gets new ID in this case, so the event is not
+    /// emitted there.
+    ///
+    /// Connection type is reported on Linux, macOS and with Windows `wgi` backend (which only
+    /// distinguishes wired and wireless gamepads). Other platforms and backends return
+    /// `ConnectionType::Unknown`. None of the platforms exposes quality of wireless link.
+    pub fn connection_type(&self) -> ConnectionType {
+        self.inner.connection_type()
+    }
+
+    /// Returns path of the OS device node that backs this gamepad (for example
+    /// `/dev/input/event5` on Linux), or `None` if gamepad is disconnected or platform doesn't
+    /// use device nodes.
+    ///
+    /// On Linux, when device has multiple event nodes, gilrs uses only the one with most gamepad
+    /// buttons, so each physical gamepad is reported once. Adapters with multiple ports have one
+    /// node with gamepad buttons and sticks per port and each of them is a separate gamepad.
+    pub fn device_path(&self) -> Option<&str> {
+        self.inner.device_path()
+    }
+
+    /// Sends raw HID output report to gamepad. First byte of `data` is report ID, or 0 if device
+    /// doesn't use numbered reports.
+    ///
+    /// This is escape hatch for using vendor features that gilrs doesn't support, like lightbars
+    /// or player LEDs. Raw HID reports are only available on Linux (through `hidraw` node, which
+    /// usually requires additional permissions) and macOS, for gamepads that are HID devices.
+    /// Otherwise `HidError::NotSupported` is returned.
+    pub fn send_output_report(&self, data: &[u8]) -> Result<(), HidError> {
+        self.inner.send_output_report(data)
+    }
+
+    /// Sends raw HID feature report to gamepad. See
+    /// [`send_output_report()`](Self::send_output_report) for details.
+    pub fn send_feature_report(&self, data: &[u8]) -> Result<(), HidError> {
+        self.inner.send_feature_report(data)
+    }
+
+    /// Reads raw HID feature report with ID set in first byte of `buf`. Returns number of bytes
+    /// written to `buf`, including report ID. See [`send_output_report()`](Self::send_output_report)
+    /// for details.
+    pub fn get_feature_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        self.inner.get_feature_report(buf)
+    }
+
+    /// Reads current raw HID input report with ID set in first byte of `buf`. Returns number of
+    /// bytes written to `buf`, including report ID. See
+    /// [`send_output_report()`](Self::send_output_report) for details.
+    pub fn get_input_report(&self, buf: &mut [u8]) -> Result<usize, HidError> {
+        self.inner.get_input_report(buf)
+    }
+
     /// Returns source of gamepad mapping. Can be used to filter gamepads which do not provide
     /// unified controller layout.
     ///
@@ -904,11 +2412,46 @@ impl<'a> Gamepad<'a> {
         }
     }
 
+    /// Returns layer of mapping database that provided gamepad's mapping or `None` if gamepad
+    /// uses default mapping. See [`MappingLayer`](enum.MappingLayer.html) for precedence rules.
+    pub fn mapping_layer(&self) -> Option<MappingLayer> {
+        self.data.mapping_layer
+    }
+
+    /// Returns entries of mapping currently used by gamepad, sorted by source code, so tools can
+    /// display it, compare it with SDL mappings or edit it (see
+    /// [`Mapping::from_entries()`](crate::Mapping::from_entries)).
+    ///
+    /// ```
+    /// # let gilrs = gilrs::Gilrs::new().unwrap();
+    /// for (_, gamepad) in gilrs.gamepads() {
+    ///     for entry in gamepad.mapping_entries() {
+    ///         let source = entry.sdl_element.unwrap_or_else(|| entry.source.to_string());
+    ///         println!("{} -> {:?}", source, entry.target);
+    ///     }
+    /// }
+    /// ```
+    pub fn mapping_entries(&self) -> Vec<MappingEntry> {
+        self.data
+            .mapping
+            .entries(self.inner.buttons(), self.inner.axes())
+    }
+
     /// Returns true if force feedback is supported by device.
     pub fn is_ff_supported(&self) -> bool {
         self.inner.is_ff_supported()
     }
 
+    /// Returns number of force feedback motors, 0 if force feedback is not supported.
+    ///
+    /// Motors are ordered as follows: strong (usually left, low frequency), weak (usually right,
+    /// high frequency), left trigger, right trigger. Use
+    /// [`BaseEffectType::Motor`](ff/enum.BaseEffectType.html#variant.Motor) to play effect on
+    /// specific motor. Currently, only Windows `wgi` backend reports trigger motors.
+    pub fn motor_count(&self) -> usize {
+        self.inner.motor_count()
+    }
+
     /// Change gamepad position used by force feedback effects.
     pub fn set_listener_position<Vec3: Into<[f32; 3]>>(
         &self,
@@ -927,6 +2470,72 @@ impl<'a> Gamepad<'a> {
         }
     }
 
+    /// Limits average power of force feedback motors, or removes limit if `limit` is `None`.
+    /// By default there is no limit.
+    ///
+    /// This can be used to protect small motors of cheap gamepads from running at full power for
+    /// long time. Use [`is_ff_limited()`](Self::is_ff_limited) to check if limit is reached.
+    pub fn set_ff_duty_limit(&self, limit: Option<DutyCycleLimit>) -> Result<(), FfError> {
+        if !self.is_connected() {
+            Err(FfError::Disconnected(self.id()))
+        } else if !self.is_ff_supported() {
+            Err(FfError::FfNotSupported(self.id()))
+        } else {
+            self.data.tx.send(Message::SetDutyLimit {
+                id: self.data.id.0,
+                limit,
+            })?;
+            Ok(())
+        }
+    }
+
+    /// Returns `true` if force feedback is currently scaled down because of limit set with
+    /// [`set_ff_duty_limit()`](Self::set_ff_duty_limit). Value is updated when events are
+    /// processed.
+    pub fn is_ff_limited(&self) -> bool {
+        self.data.ff_limited
+    }
+
+    /// Returns effects that are playing or scheduled on this gamepad, together with their state.
+    /// See [`Effect::state()`](crate::ff::Effect::state).
+    pub fn playing_effects(&self) -> Vec<(EffectId, PlaybackState)> {
+        self.data
+            .ff_states
+            .lock()
+            .unwrap()
+            .effects
+            .iter()
+            .filter(|(_, status)| {
+                status.devices.contains_key(self.data.id.0)
+                    && status.state != PlaybackState::Stopped
+            })
+            .map(|(id, status)| (EffectId::new(id), status.state))
+            .collect()
+    }
+
+    /// Returns `true` if any force feedback motor of this gamepad is currently running.
+    ///
+    /// This reflects what is actually sent to the device, so it can be used to show on-screen
+    /// rumble indicator. Like [`playing_effects()`](Self::playing_effects), it's updated by force
+    /// feedback thread every tick (50 ms).
+    pub fn vibrating(&self) -> bool {
+        self.vibration_magnitude() > 0.0
+    }
+
+    /// Returns magnitude of the strongest force feedback motor of this gamepad, in range from 0.0
+    /// to 1.0. Effects on motors that gamepad doesn't have and duty limit set with
+    /// [`set_ff_duty_limit()`](Self::set_ff_duty_limit) are taken into account.
+    pub fn vibration_magnitude(&self) -> f32 {
+        self.data
+            .ff_states
+            .lock()
+            .unwrap()
+            .motors
+            .get(self.data.id.0)
+            .and_then(|motors| motors.iter().max())
+            .map_or(0.0, |&max| f32::from(max) / f32::from(u16::MAX))
+    }
+
     /// Returns `AxisOrBtn` mapped to `Code`.
     pub fn axis_or_btn_name(&self, ec: Code) -> Option<AxisOrBtn> {
         self.data.axis_or_btn_name(ec)
@@ -942,6 +2551,16 @@ impl<'a> Gamepad<'a> {
         self.data.axis_code(axis)
     }
 
+    /// Returns human-readable name of raw button or axis, for example "Paddle P3" for back paddle
+    /// of Xbox Elite controller instead of "Button 17". Useful for remapping UIs.
+    ///
+    /// Names come from known layouts of some devices or, for other devices, from the platform:
+    /// evdev code names on Linux and HID usage names on macOS. On other platforms this function
+    /// always returns `None`.
+    pub fn code_name(&self, code: Code) -> Option<String> {
+        self.inner.code_name(code.0)
+    }
+
     /// Returns area in which axis events should be ignored.
     pub fn deadzone(&self, axis: Code) -> Option<f32> {
         self.inner.axis_info(axis.0).map(|i| {
@@ -957,6 +2576,18 @@ impl<'a> Gamepad<'a> {
         })
     }
 
+    /// Returns dead zone reported by the device for trigger with given code, as fraction of its
+    /// range. Unlike `deadzone()`, it doesn't assume that element is centered at rest.
+    pub(crate) fn trigger_threshold(&self, trigger: Code) -> Option<f32> {
+        let info = self.inner.axis_info(trigger.0)?;
+        let range = info.max as f32 - info.min as f32;
+
+        match info.deadzone {
+            Some(d) if range != 0.0 => Some(d as f32 / range),
+            _ => None,
+        }
+    }
+
     /// Returns ID of gamepad.
     pub fn id(&self) -> GamepadId {
         self.data.id
@@ -967,40 +2598,75 @@ impl<'a> Gamepad<'a> {
     }
 }
 
+/// Settings of gamepad chosen by user that are kept when it reconnects.
+#[derive(Debug, Default)]
+struct GamepadSettings {
+    // Name set by user with `Gilrs::set_label()`.
+    label: Option<String>,
+    // Raw code remapping set with `Gilrs::set_code_remap()`.
+    code_remap: CodeRemap,
+    // Single element remaps set with `Gilrs::remap_button()` and `remap_axis()`.
+    element_remaps: Vec<(Code, AxisOrBtn)>,
+    // Lever emulation set with `Gilrs::set_lever_mode()`.
+    lever_mode: LeverMode,
+}
+
 #[derive(Debug)]
 pub(crate) struct GamepadData {
     state: GamepadState,
     mapping: Mapping,
+    // Layer of mapping database from which `mapping` comes, `None` if it's default mapping.
+    mapping_layer: Option<MappingLayer>,
+    // SDL representation of mapping set with `Gilrs::set_mapping()` or `set_sdl_mapping()`.
+    runtime_mapping: Option<String>,
     tx: Sender<Message>,
+    ff_states: EffectStates,
     id: GamepadId,
+    // Last XInput user index reported for this gamepad.
+    xinput_user_index: Option<u32>,
+    // Connection type reported when gamepad was connected.
+    connection_type: ConnectionType,
+    // OS name after `utils::normalize_product_name()`.
+    product_name: String,
+    settings: GamepadSettings,
+    // State of lever emulation, see `GamepadSettings::lever_mode`.
+    lever: Lever,
+    // Time of last input event or connection.
+    last_activity: SystemTime,
+    // Time when stuck button watchdog last checked this gamepad.
+    stuck_check: SystemTime,
+    // Set by first input event, cleared on connection and when gamepad is reported as idle.
+    active: bool,
+    // Whether force feedback duty cycle limiter scaled down last tick.
+    ff_limited: bool,
+    // Number of pressed Shift keys of attached keyboard.
+    shift_keys: u8,
     // Flags used by the deadzone filter.
     pub(crate) have_sent_nonzero_for_axis: [bool; 6],
 }
 
 impl GamepadData {
+    /// Creates data of newly connected gamepad. `settings` are kept from the previous connection
+    /// of gamepad with the same ID, if any.
     fn new(
         id: GamepadId,
         tx: Sender<Message>,
-        gamepad: &gilrs_core::Gamepad,
+        ff_states: EffectStates,
+        gamepad: &RawGamepad,
         db: &MappingDb,
+        settings: GamepadSettings,
     ) -> Self {
-        let mapping = db
-            .get(Uuid::from_bytes(gamepad.uuid()))
-            .map(
-                |s| match Mapping::parse_sdl_mapping(s, gamepad.buttons(), gamepad.axes()) {
-                    Ok(result) => result,
-                    Err(e) => {
-                        warn!(
-                            "Unable to parse SDL mapping for UUID {}\n\t{:?}\n\tDefault mapping \
-                             will be used.",
-                            Uuid::from_bytes(gamepad.uuid()),
-                            e
-                        );
-                        Mapping::default(gamepad)
-                    }
-                },
-            )
-            .unwrap_or_else(|| Mapping::default(gamepad));
+        let (mut mapping, mapping_layer) = resolve_mapping(gamepad, db);
+        mapping.apply_overrides(&settings.element_remaps);
+
+        diag!(
+            debug,
+            gamepad = id.0,
+            mapping = mapping.name(),
+            layer = mapping_layer,
+            default = mapping.is_default();
+            "Mapping resolved"
+        );
 
         if gamepad.is_ff_supported() && gamepad.is_connected() {
             if let Some(device) = gamepad.ff_device() {
@@ -1008,15 +2674,41 @@ impl GamepadData {
             }
         }
 
+        // Axes can be mapped to buttons and d-pad can be emulated with standard codes (see
+        // `axis_dpad_to_button` filter and `LeverMode`), so leave some space for them too.
+        let state = GamepadState::with_capacity(
+            gamepad.buttons().len() + gamepad.axes().len() + 4,
+            gamepad.axes().len() + 2,
+        );
+
         GamepadData {
-            state: GamepadState::new(),
+            state,
             mapping,
+            mapping_layer,
+            runtime_mapping: None,
             tx,
+            ff_states,
             id,
+            xinput_user_index: gamepad.xinput_user_index(),
+            connection_type: gamepad.connection_type(),
+            product_name: utils::normalize_product_name(gamepad.name()),
+            lever: Lever::new(settings.lever_mode),
+            settings,
+            last_activity: utils::time_now(),
+            stuck_check: SystemTime::UNIX_EPOCH,
+            active: false,
+            ff_limited: false,
+            shift_keys: 0,
             have_sent_nonzero_for_axis: Default::default(),
         }
     }
 
+    // Replaces mapping and applies element remaps on top of it.
+    fn set_mapping(&mut self, mut mapping: Mapping) {
+        mapping.apply_overrides(&self.settings.element_remaps);
+        self.mapping = mapping;
+    }
+
     /// if `mapping_source()` is `SdlMappings` returns the name of the mapping used by the gamepad.
     /// Otherwise returns `None`.
     ///
@@ -1094,8 +2786,113 @@ impl GamepadData {
     }
 }
 
+/// Counters collected by `Gilrs`. See [`Gilrs::stats()`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// Number of events returned from `next_event()` and its variants.
+    pub events: u64,
+    /// Number of events dropped by filters. Events dropped by default filters are counted even
+    /// though they are never returned.
+    pub dropped_events: u64,
+    /// Largest number of events that were waiting in internal queue.
+    pub max_queue_len: usize,
+    /// Number of times backend was asked for new events.
+    pub polls: u64,
+    /// Total time spent waiting for backend. Includes time spent blocked in
+    /// `next_event_blocking()`.
+    pub poll_time: Duration,
+    /// Longest time spent waiting for backend in single poll.
+    pub max_poll_time: Duration,
+    /// Number of force feedback commands (for example `Effect::play()` or
+    /// `Effect::set_gain()`) applied by force feedback thread. Each command of
+    /// [`FfBatch`](crate::ff::FfBatch) is counted separately. Internal bookkeeping, like cloning
+    /// or dropping `Effect`, is not counted.
+    pub ff_commands: u64,
+}
+
+/// Range of values reported for analog triggers that are mapped to axes.
+///
+/// See [`GilrsBuilder::set_trigger_range()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TriggerRange {
+    /// Released trigger is reported as -1.0 and fully pressed as 1.0.
+    MinusOneToOne,
+    /// Released trigger is reported as 0.0 and fully pressed as 1.0.
+    ZeroToOne,
+}
+
+impl TriggerRange {
+    fn convert(self, val: f32) -> f32 {
+        match self {
+            TriggerRange::MinusOneToOne => val,
+            TriggerRange::ZeroToOne => (val + 1.0) / 2.0,
+        }
+    }
+
+    /// Converts value in this range to value from 0.0 to 1.0.
+    pub(crate) fn normalize(self, val: f32) -> f32 {
+        match self {
+            TriggerRange::MinusOneToOne => (val + 1.0) / 2.0,
+            TriggerRange::ZeroToOne => val,
+        }
+    }
+
+    /// Converts value from 0.0 to 1.0 to value in this range.
+    pub(crate) fn denormalize(self, val: f32) -> f32 {
+        self.convert(val * 2.0 - 1.0)
+    }
+}
+
+/// Dead zone of analog triggers. Unlike sticks, triggers use separate thresholds at both ends of
+/// their range.
+///
+/// See [`GilrsBuilder::set_trigger_deadzone()`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TriggerDeadzone {
+    /// Values (from 0.0 to 1.0) below this threshold are reported as released trigger. If gamepad
+    /// reports larger dead zone for a trigger, the larger one is used.
+    pub inner: f32,
+    /// Values above `1.0 - outer` are reported as fully pressed trigger.
+    pub outer: f32,
+}
+
+impl TriggerDeadzone {
+    /// Creates new `TriggerDeadzone`. Both values are clamped to range from 0.0 to 0.5.
+    pub fn new(inner: f32, outer: f32) -> Self {
+        TriggerDeadzone {
+            inner: utils::clamp(inner, 0.0, 0.5),
+            outer: utils::clamp(outer, 0.0, 0.5),
+        }
+    }
+
+    pub(crate) fn apply(self, val: f32, gamepad_threshold: Option<f32>) -> f32 {
+        let inner = gamepad_threshold.map_or(self.inner, |t| t.max(self.inner));
+        let outer = self.outer;
+        let range = 1.0 - inner - outer;
+
+        if val <= inner {
+            0.0
+        } else if val >= 1.0 - outer || range <= 0.0 {
+            1.0
+        } else {
+            (val - inner) / range
+        }
+    }
+}
+
+impl Default for TriggerDeadzone {
+    /// Returns dead zone with both thresholds set to 0.0, so only dead zone reported by gamepad is
+    /// applied.
+    fn default() -> Self {
+        TriggerDeadzone::new(0.0, 0.0)
+    }
+}
+
 /// Source of gamepad mappings.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub enum MappingSource {
     /// Gamepad uses SDL mappings.
     SdlMappings,
@@ -1126,7 +2923,45 @@ impl Display for GamepadId {
     }
 }
 
+fn guid_with_crc(gamepad: &RawGamepad) -> Guid {
+    let guid = Guid::from_bytes(gamepad.uuid());
+    if guid.is_nil() {
+        guid
+    } else {
+        guid.with_name_crc(gamepad.name())
+    }
+}
+
+fn lookup_mapping<'a>(gamepad: &RawGamepad, db: &'a MappingDb) -> Option<(&'a str, MappingLayer)> {
+    // Prefer mappings for GUID with CRC, like SDL does.
+    db.get(guid_with_crc(gamepad).into())
+        .or_else(|| db.get(Uuid::from_bytes(gamepad.uuid())))
+}
+
+fn resolve_mapping(gamepad: &RawGamepad, db: &MappingDb) -> (Mapping, Option<MappingLayer>) {
+    lookup_mapping(gamepad, db)
+        .and_then(|(s, layer)| {
+            match Mapping::parse_sdl_mapping(s, gamepad.buttons(), gamepad.axes()) {
+                Ok(result) => Some((result, Some(layer))),
+                Err(e) => {
+                    warn!(
+                        "Unable to parse SDL mapping for UUID {}\n\t{:?}\n\tDefault mapping will \
+                         be used.",
+                        Uuid::from_bytes(gamepad.uuid()),
+                        e
+                    );
+                    None
+                }
+            }
+        })
+        .unwrap_or_else(|| (Mapping::default(gamepad), None))
+}
+
 fn axis_value(info: &AxisInfo, val: i32, axis: Axis) -> f32 {
+    axis_value_impl(info, val, axis, gilrs_core::IS_Y_AXIS_REVERSED)
+}
+
+fn axis_value_impl(info: &AxisInfo, val: i32, axis: Axis, y_reversed: bool) -> f32 {
     let mut range = info.max as f32 - info.min as f32;
     let mut val = val as f32 - info.min as f32;
 
@@ -1142,7 +2977,7 @@ fn axis_value(info: &AxisInfo, val: i32, axis: Axis) -> f32 {
 
     val = val / range * 2.0 - 1.0;
 
-    if gilrs_core::IS_Y_AXIS_REVERSED
+    if y_reversed
         && (axis == Axis::LeftStickY || axis == Axis::RightStickY || axis == Axis::DPadY)
         && val != 0.0
     {
@@ -1152,6 +2987,79 @@ fn axis_value(info: &AxisInfo, val: i32, axis: Axis) -> f32 {
     utils::clamp(val, -1.0, 1.0)
 }
 
+// Returns `true` if gamepad that had last input at `last_activity` is idle at `now`.
+fn is_idle(now: SystemTime, last_activity: SystemTime, timeout: Duration) -> bool {
+    now.duration_since(last_activity).unwrap_or_default() >= timeout
+}
+
+// Updates `state` with button and axis values from `event`.
+fn update_gamepad_state(state: &mut GamepadState, event: &Event, counter: u64) {
+    use crate::EventType::*;
+
+    match event.event {
+        ButtonPressed(_, nec) => {
+            state.set_btn_pressed(nec, true, counter, event.time);
+        }
+        ButtonReleased(_, nec) => {
+            state.set_btn_pressed(nec, false, counter, event.time);
+        }
+        ButtonRepeated(_, nec) => {
+            state.set_btn_repeating(nec, counter, event.time);
+        }
+        ButtonChanged(_, value, nec) => {
+            state.set_btn_value(nec, value, counter, event.time);
+        }
+        AxisChanged(_, value, nec) => {
+            state.update_axis(nec, AxisData::new(value, counter, event.time));
+        }
+        AxisPressed(_, direction, nec) => {
+            state.set_axis_pressed(nec, Some(direction));
+        }
+        AxisReleased(_, _, nec) => {
+            state.set_axis_pressed(nec, None);
+        }
+        Disconnected => {
+            // Don't keep values from before gamepad was disconnected.
+            state.clear();
+        }
+        Connected
+        | Dropped
+        | ForceFeedbackEffectCompleted
+        | XInputUserIndexChanged(_)
+        | HeadsetChanged(_)
+        | ConnectionTypeChanged(_)
+        | GamepadActivity
+        | GamepadIdle
+        | ProfileChanged(_)
+        | KeyPressed(_)
+        | KeyReleased(_)
+        | Payload(..)
+        | MappingChanged => (),
+    }
+}
+
+// Returns `true` if stuck button watchdog should check gamepad that had last input at
+// `last_activity` and was last checked at `last_check`.
+fn stuck_check_due(
+    now: SystemTime,
+    last_activity: SystemTime,
+    last_check: SystemTime,
+    timeout: Duration,
+) -> bool {
+    now.duration_since(last_activity.max(last_check))
+        .is_ok_and(|idle| idle >= timeout)
+}
+
+// Returns `true` if button that is pressed in gamepad state is released according to `value`
+// read from device. `None` means that backend can't read the value, so button is kept pressed.
+fn is_released(value: Option<i32>, info: Option<&AxisInfo>, threshold: f32) -> bool {
+    match (value, info) {
+        (Some(val), Some(info)) => btn_value(info, val) <= threshold,
+        (Some(val), None) => val == 0,
+        (None, _) => false,
+    }
+}
+
 fn btn_value(info: &AxisInfo, val: i32) -> f32 {
     let range = info.max as f32 - info.min as f32;
     let mut val = val as f32 - info.min as f32;
@@ -1163,6 +3071,7 @@ fn btn_value(info: &AxisInfo, val: i32) -> f32 {
 /// Error type which can be returned when creating `Gilrs`.
 #[non_exhaustive]
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Error {
     /// Gilrs does not support current platform, but you can use dummy context from this error if
     /// gamepad input is not essential.
@@ -1203,7 +3112,17 @@ const _: () = {
 
 #[cfg(test)]
 mod tests {
-    use super::{axis_value, btn_value, Axis, AxisInfo};
+    use super::{
+        axis_value, axis_value_impl, btn_value, is_idle, is_released, stuck_check_due, Axis,
+        AxisInfo, AxisOrBtn, ConnectionType, GamepadId, GilrsBuilder, Subscriptions,
+        TriggerDeadzone, TriggerRange,
+    };
+    use crate::ev::{Button, Code, Event, EventType, Payload};
+    use crate::mapping::{CodeRemap, LeverMode, MappingData, MappingError};
+    use crate::test_backend;
+    use gilrs_core::{native_ev_codes as nec, EventType as RawEventType};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
 
     #[test]
     fn axis_value_documented_case() {
@@ -1219,8 +3138,8 @@ mod tests {
     #[test]
     fn axis_value_overflow() {
         let info = AxisInfo {
-            min: std::i32::MIN,
-            max: std::i32::MAX,
+            min: i32::MIN,
+            max: i32::MAX,
             deadzone: None,
         };
         let axis = Axis::LeftStickY;
@@ -1233,11 +3152,103 @@ mod tests {
         assert_eq!(-1.0, axis_value(&info, i32::MAX, axis));
     }
 
+    // Raw values that backends are expected to report for gamepad at rest and with element pushed
+    // right, up or pressed, following conventions of each platform API. These are not captured
+    // from devices: (backend, is Y axis reversed on it, min, max, axis, neutral, pushed).
+    #[rustfmt::skip]
+    const ORIENTATION_CASES: &[(&str, bool, i32, i32, Axis, i32, i32)] = &[
+        // Xbox 360 pad, ABS_X/ABS_Y/ABS_Z
+        ("evdev", true, -32768, 32767, Axis::LeftStickX, 0, 32767),
+        ("evdev", true, -32768, 32767, Axis::LeftStickY, -1, -32768),
+        ("evdev", true, 0, 255, Axis::LeftZ, 0, 255),
+        // DualShock 4, ABS_Y and ABS_HAT0Y
+        ("evdev", true, 0, 255, Axis::RightStickY, 127, 0),
+        ("evdev", true, -1, 1, Axis::DPadY, 0, -1),
+        ("xinput", false, -32768, 32767, Axis::LeftStickX, 0, 32767),
+        ("xinput", false, -32768, 32767, Axis::LeftStickY, 0, 32767),
+        ("xinput", false, -32768, 32767, Axis::RightStickY, 0, 32767),
+        // WGI Gamepad reading, Y multiplied by -1.0 in backend
+        ("wgi", true, i32::MIN, i32::MAX, Axis::LeftStickX, 0, i32::MAX),
+        ("wgi", true, i32::MIN, i32::MAX, Axis::LeftStickY, 0, -i32::MAX),
+        ("wgi", true, -1, 1, Axis::DPadY, 0, -1),
+        // IOKit generic desktop usages
+        ("macos", true, 0, 255, Axis::LeftStickX, 127, 255),
+        ("macos", true, 0, 255, Axis::LeftStickY, 127, 0),
+        // Standard gamepad mapping, value multiplied by i32::MAX in backend
+        ("wasm", true, i32::MIN, i32::MAX, Axis::RightStickX, 0, i32::MAX),
+        ("wasm", true, i32::MIN, i32::MAX, Axis::RightStickY, 0, -i32::MAX),
+    ];
+
+    #[test]
+    fn axis_orientation_conformance() {
+        for &(backend, y_reversed, min, max, axis, neutral, pushed) in ORIENTATION_CASES {
+            let info = AxisInfo {
+                min,
+                max,
+                deadzone: None,
+            };
+            let rest = axis_value_impl(&info, neutral, axis, y_reversed);
+            let value = axis_value_impl(&info, pushed, axis, y_reversed);
+
+            assert!(
+                value > 0.99,
+                "{}: {:?} pushed to {} reported as {}",
+                backend,
+                axis,
+                pushed,
+                value
+            );
+            if axis == Axis::LeftZ || axis == Axis::RightZ {
+                assert!(rest < -0.99, "{}: {:?} at rest is {}", backend, axis, rest);
+            } else {
+                assert!(
+                    rest.abs() < 0.01,
+                    "{}: {:?} at rest is {}",
+                    backend,
+                    axis,
+                    rest
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn trigger_range() {
+        let info = AxisInfo {
+            min: 0,
+            max: 255,
+            deadzone: None,
+        };
+        let released = axis_value(&info, 0, Axis::LeftZ);
+        let pressed = axis_value(&info, 255, Axis::LeftZ);
+
+        assert_eq!(TriggerRange::MinusOneToOne.convert(pressed), 1.0);
+        assert_eq!(TriggerRange::ZeroToOne.convert(pressed), 1.0);
+        assert!(TriggerRange::ZeroToOne.convert(released) < 0.01);
+        assert_eq!(TriggerRange::ZeroToOne.convert(-1.0), 0.0);
+    }
+
+    #[test]
+    fn trigger_deadzone() {
+        let dz = TriggerDeadzone::new(0.25, 0.25);
+        assert_eq!(dz.apply(0.2, None), 0.0);
+        assert_eq!(dz.apply(0.5, None), 0.5);
+        assert_eq!(dz.apply(0.8, None), 1.0);
+        // Larger threshold reported by gamepad wins.
+        assert_eq!(dz.apply(0.3, Some(0.4)), 0.0);
+        assert_eq!(dz.apply(0.375, Some(0.01)), 0.25);
+
+        for range in [TriggerRange::MinusOneToOne, TriggerRange::ZeroToOne] {
+            assert_eq!(range.denormalize(range.normalize(0.5)), 0.5);
+            assert_eq!(range.normalize(range.denormalize(0.0)), 0.0);
+        }
+    }
+
     #[test]
     fn btn_value_overflow() {
         let info = AxisInfo {
-            min: std::i32::MIN,
-            max: std::i32::MAX,
+            min: i32::MIN,
+            max: i32::MAX,
             deadzone: None,
         };
 
@@ -1248,4 +3259,544 @@ mod tests {
         assert_eq!(0.0, btn_value(&info, i32::MIN));
         assert_eq!(1.0, btn_value(&info, i32::MAX));
     }
+
+    #[test]
+    fn subscriptions() {
+        let code = Code(gilrs_core::native_ev_codes::BTN_SOUTH);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut subs = Subscriptions::default();
+
+        let c = calls.clone();
+        let south = subs.add(
+            None,
+            AxisOrBtn::Btn(Button::South),
+            Box::new(move |ev: &Event| c.lock().unwrap().push(ev.event)),
+        );
+        let c = calls.clone();
+        subs.add(
+            Some(GamepadId(1)),
+            AxisOrBtn::Axis(Axis::LeftStickX),
+            Box::new(move |ev: &Event| c.lock().unwrap().push(ev.event)),
+        );
+
+        subs.dispatch(&Event::new(
+            GamepadId(0),
+            EventType::ButtonPressed(Button::South, code),
+        ));
+        subs.dispatch(&Event::new(
+            GamepadId(0),
+            EventType::ButtonPressed(Button::East, code),
+        ));
+        subs.dispatch(&Event::new(
+            GamepadId(0),
+            EventType::AxisChanged(Axis::LeftStickX, 0.5, code),
+        ));
+        subs.dispatch(&Event::new(
+            GamepadId(1),
+            EventType::AxisChanged(Axis::LeftStickX, 0.7, code),
+        ));
+
+        assert!(subs.remove(south));
+        assert!(!subs.remove(south));
+        subs.dispatch(&Event::new(
+            GamepadId(1),
+            EventType::ButtonReleased(Button::South, code),
+        ));
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                EventType::ButtonPressed(Button::South, code),
+                EventType::AxisChanged(Axis::LeftStickX, 0.7, code),
+            ]
+        );
+    }
+
+    #[test]
+    fn drain_frame() {
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let now = SystemTime::now();
+
+        for val in [-20000, -30000, 200, 250] {
+            let nec = if val > 0 {
+                nec::AXIS_RT2
+            } else {
+                nec::AXIS_LSTICKX
+            };
+            gilrs
+                .test_backend()
+                .push(id.0, RawEventType::AxisValueChanged(val, nec), now);
+        }
+        let batch = gilrs.drain_frame(7);
+        assert_eq!(batch.frame, 7);
+
+        let stick: Vec<_> = batch
+            .events
+            .iter()
+            .filter_map(|ev| match ev.event {
+                EventType::AxisChanged(Axis::LeftStickX, val, _) => Some(val),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stick.len(), 2);
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), stick[1]);
+
+        let pressed = batch
+            .events
+            .iter()
+            .filter(|ev| matches!(ev.event, EventType::ButtonPressed(Button::RightTrigger2, _)))
+            .count();
+        assert_eq!(pressed, 1);
+        assert!(gilrs.drain_frame(8).events.is_empty());
+    }
+
+    #[test]
+    fn inject_frame() {
+        let mut local = GilrsBuilder::new().build().unwrap();
+        let mut remote = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut local, "Local");
+        test_backend::connect(&mut remote, "Remote");
+        // Gamepad that exists only on remote side.
+        let remote_only = test_backend::connect(&mut remote, "Second");
+
+        let text = Payload::Text("gg".to_owned());
+        local.insert_payload_event(id, Payload::Text("local".to_owned()));
+        remote.insert_payload_event(id, text.clone());
+        remote.test_backend().push(
+            remote_only.0,
+            RawEventType::ButtonPressed(nec::BTN_SOUTH),
+            SystemTime::now(),
+        );
+        let batch = remote.drain_frame(1);
+        assert_eq!(batch.events[0].payload, Some(text));
+        assert!(remote.gamepad(remote_only).is_pressed(Button::South));
+
+        local.inject_frame(1, &batch);
+        // Injected events are never returned locally, so they are not sent back.
+        let events = local.drain_frame(2).events;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload, Some(Payload::Text("local".to_owned())));
+
+        // Remote input doesn't change local gamepads.
+        let south = remote
+            .gamepad(remote_only)
+            .button_code(Button::South)
+            .unwrap();
+        assert!(local
+            .remote_state(1, remote_only)
+            .unwrap()
+            .is_pressed(south));
+        assert!(local.remote_state(2, remote_only).is_none());
+        assert!(!local.gamepad(id).is_pressed(Button::South));
+
+        local.remove_remote_player(1);
+        assert!(local.remote_state(1, remote_only).is_none());
+    }
+
+    #[test]
+    fn latch_frame_updates_state() {
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let now = SystemTime::now();
+
+        // Both samples are above threshold, only the first one presses the trigger.
+        for val in [200, 250] {
+            gilrs.test_backend().push(
+                id.0,
+                RawEventType::AxisValueChanged(val, nec::AXIS_RT2),
+                now,
+            );
+        }
+        let frame = gilrs.latch_frame(now);
+
+        let pressed = frame
+            .iter()
+            .filter(|ev| matches!(ev.event, EventType::ButtonPressed(Button::RightTrigger2, _)))
+            .count();
+        assert_eq!(pressed, 1);
+        assert!(gilrs.gamepad(id).is_pressed(Button::RightTrigger2));
+    }
+
+    #[test]
+    fn stuck_check_timing() {
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let timeout = Duration::from_secs(5);
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        assert!(!stuck_check_due(at(4), t0, t0, timeout));
+        assert!(stuck_check_due(at(5), t0, t0, timeout));
+        // Checked recently or had input recently.
+        assert!(!stuck_check_due(at(7), t0, at(3), timeout));
+        assert!(!stuck_check_due(at(7), at(3), t0, timeout));
+        // Clock went backwards.
+        assert!(!stuck_check_due(t0, at(1), t0, timeout));
+    }
+
+    #[test]
+    fn stuck_button_decision() {
+        let trigger = AxisInfo {
+            min: 0,
+            max: 255,
+            deadzone: None,
+        };
+
+        assert!(is_released(Some(0), None, 0.65));
+        assert!(!is_released(Some(1), None, 0.65));
+        assert!(is_released(Some(100), Some(&trigger), 0.65));
+        assert!(!is_released(Some(200), Some(&trigger), 0.65));
+        // Backend can't read state, button must not be released.
+        assert!(!is_released(None, None, 0.65));
+        assert!(!is_released(None, Some(&trigger), 0.65));
+    }
+
+    fn released(gilrs: &mut super::Gilrs) -> usize {
+        let mut count = 0;
+        while let Some(ev) = gilrs.next_event() {
+            if matches!(ev.event, EventType::ButtonReleased(Button::South, _)) {
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    #[test]
+    fn stuck_button_watchdog() {
+        let mut gilrs = GilrsBuilder::new()
+            .set_stuck_button_timeout(Some(Duration::ZERO))
+            .build()
+            .unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let now = SystemTime::now();
+        gilrs
+            .test_backend()
+            .push(id.0, RawEventType::ButtonPressed(nec::BTN_SOUTH), now);
+        assert_eq!(released(&mut gilrs), 0);
+        assert!(gilrs.gamepad(id).is_pressed(Button::South));
+
+        // Device still reports button as pressed.
+        assert_eq!(released(&mut gilrs), 0);
+
+        // Release event was lost.
+        gilrs.test_backend().set_value(id.0, nec::BTN_SOUTH, 0);
+        assert_eq!(released(&mut gilrs), 1);
+        assert!(!gilrs.gamepad(id).is_pressed(Button::South));
+    }
+
+    #[test]
+    fn release_on_disconnect() {
+        // Watchdog is disabled.
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        gilrs.test_backend().push(
+            id.0,
+            RawEventType::ButtonPressed(nec::BTN_SOUTH),
+            SystemTime::now(),
+        );
+        assert_eq!(released(&mut gilrs), 0);
+
+        gilrs.test_backend().disconnect(id.0);
+        assert_eq!(released(&mut gilrs), 1);
+        assert!(!gilrs.gamepad(id).is_pressed(Button::South));
+    }
+
+    #[test]
+    fn reset_state() {
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let now = SystemTime::now();
+        gilrs
+            .test_backend()
+            .push(id.0, RawEventType::ButtonPressed(nec::BTN_SOUTH), now);
+        gilrs.test_backend().push(
+            id.0,
+            RawEventType::AxisValueChanged(32767, nec::AXIS_LSTICKX),
+            now,
+        );
+        while gilrs.next_event().is_some() {}
+        assert!(gilrs.gamepad(id).is_pressed(Button::South));
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), 1.0);
+
+        gilrs.reset_state(id);
+        assert!(!gilrs.gamepad(id).is_pressed(Button::South));
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), 0.0);
+        assert!(gilrs.next_event().is_none());
+    }
+
+    #[test]
+    fn clear_state_on_disconnect() {
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        gilrs.test_backend().push(
+            id.0,
+            RawEventType::AxisValueChanged(32767, nec::AXIS_LSTICKX),
+            SystemTime::now(),
+        );
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), 1.0);
+
+        gilrs.test_backend().disconnect(id.0);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), 0.0);
+
+        gilrs.test_backend().reconnect(id.0);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(gilrs.gamepad(id).value(Axis::LeftStickX), 0.0);
+    }
+
+    #[test]
+    fn idle_timing() {
+        let last = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let timeout = Duration::from_secs(10);
+        assert!(!is_idle(last + Duration::from_secs(9), last, timeout));
+        assert!(is_idle(last + timeout, last, timeout));
+        // Clock going backwards doesn't make gamepad idle.
+        assert!(!is_idle(last - Duration::from_secs(20), last, timeout));
+    }
+
+    #[test]
+    fn idle_and_activity_events() {
+        let mut gilrs = GilrsBuilder::new()
+            .set_idle_events(true)
+            .set_idle_timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let start = SystemTime::now();
+        let press = |gilrs: &mut crate::Gilrs, time| {
+            gilrs
+                .test_backend()
+                .push(id.0, RawEventType::ButtonPressed(nec::BTN_SOUTH), time);
+            gilrs
+                .test_backend()
+                .push(id.0, RawEventType::ButtonReleased(nec::BTN_SOUTH), time);
+        };
+        let events = |gilrs: &mut crate::Gilrs| {
+            let mut events = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                events.push(ev.event);
+            }
+            events
+        };
+
+        let activity = |events: Vec<EventType>| {
+            events
+                .into_iter()
+                .filter(|&ev| ev == EventType::GamepadActivity)
+                .count()
+        };
+
+        // First input after connecting makes gamepad active.
+        press(&mut gilrs, start);
+        assert_eq!(activity(events(&mut gilrs)), 1);
+
+        gilrs.check_idle(start + Duration::from_secs(5));
+        assert!(events(&mut gilrs).is_empty());
+        gilrs.check_idle(start + Duration::from_secs(10));
+        assert_eq!(events(&mut gilrs), [EventType::GamepadIdle]);
+        // Idle is reported only once.
+        gilrs.check_idle(start + Duration::from_secs(20));
+        assert!(events(&mut gilrs).is_empty());
+
+        press(&mut gilrs, start + Duration::from_secs(30));
+        assert_eq!(activity(events(&mut gilrs)), 1);
+    }
+
+    #[test]
+    fn deferred_enumeration() {
+        let mut gilrs = GilrsBuilder::new()
+            .with_deferred_enumeration()
+            .build()
+            .unwrap();
+        gilrs
+            .test_backend()
+            .connect(test_backend::Gamepad::new("First"));
+        gilrs
+            .test_backend()
+            .connect(test_backend::Gamepad::new("Second"));
+        assert_eq!(gilrs.gamepads().count(), 0);
+        assert!(gilrs.next_event().is_none());
+
+        gilrs.scan_devices().unwrap();
+        let mut events = Vec::new();
+        while let Some(ev) = gilrs.next_event() {
+            events.push((ev.id, ev.event));
+        }
+        assert_eq!(
+            events,
+            [
+                (GamepadId(0), EventType::Connected),
+                (GamepadId(1), EventType::Connected)
+            ]
+        );
+        assert_eq!(gilrs.gamepad(GamepadId(1)).name(), "Second");
+
+        // Devices are scanned only once.
+        gilrs.scan_devices().unwrap();
+        assert!(gilrs.next_event().is_none());
+    }
+
+    #[test]
+    fn connection_type_changed() {
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let reconnect = |gilrs: &mut crate::Gilrs, connection_type| {
+            gilrs.test_backend().disconnect(id.0);
+            gilrs
+                .test_backend()
+                .set_connection_type(id.0, connection_type);
+            gilrs.test_backend().reconnect(id.0);
+            let mut events = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                events.push(ev.event);
+            }
+            events
+        };
+
+        assert_eq!(
+            reconnect(&mut gilrs, ConnectionType::Unknown),
+            [EventType::Disconnected, EventType::Connected]
+        );
+        assert_eq!(
+            reconnect(&mut gilrs, ConnectionType::Bluetooth),
+            [
+                EventType::Disconnected,
+                EventType::Connected,
+                EventType::ConnectionTypeChanged(ConnectionType::Bluetooth)
+            ]
+        );
+        assert_eq!(
+            gilrs.gamepad(id).connection_type(),
+            ConnectionType::Bluetooth
+        );
+    }
+
+    #[test]
+    fn inverted_axis_to_button() {
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        gilrs
+            .set_sdl_mapping(
+                id.0,
+                "00000000000000000000000000000000,Test,lefttrigger:a5~,",
+            )
+            .unwrap();
+        while gilrs.next_event().is_some() {}
+
+        let now = SystemTime::now();
+        gilrs
+            .test_backend()
+            .push(id.0, RawEventType::AxisValueChanged(0, nec::AXIS_LT2), now);
+        while gilrs.next_event().is_some() {}
+        assert!(gilrs.gamepad(id).is_pressed(Button::LeftTrigger2));
+        assert_eq!(
+            gilrs
+                .gamepad(id)
+                .button_data(Button::LeftTrigger2)
+                .map(|data| data.value()),
+            Some(1.0)
+        );
+
+        gilrs.test_backend().push(
+            id.0,
+            RawEventType::AxisValueChanged(255, nec::AXIS_LT2),
+            now,
+        );
+        while gilrs.next_event().is_some() {}
+        assert!(!gilrs.gamepad(id).is_pressed(Button::LeftTrigger2));
+    }
+
+    #[test]
+    fn remap_elements() {
+        fn pressed(gilrs: &mut super::Gilrs, id: GamepadId, nec: gilrs_core::EvCode) -> Button {
+            let now = SystemTime::now();
+            let backend = gilrs.test_backend();
+            backend.push(id.0, RawEventType::ButtonPressed(nec), now);
+            backend.push(id.0, RawEventType::ButtonReleased(nec), now);
+
+            let mut pressed = Button::Unknown;
+            while let Some(ev) = gilrs.next_event() {
+                if let EventType::ButtonPressed(btn, _) = ev.event {
+                    pressed = btn;
+                }
+            }
+            pressed
+        }
+
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let east = gilrs.gamepad(id).button_code(Button::East).unwrap();
+
+        assert_eq!(
+            gilrs.remap_button(id, east, Button::Unknown),
+            Err(MappingError::UnknownElement)
+        );
+        let missing = Code(gilrs_core::native_ev_codes::BTN_C);
+        assert_eq!(
+            gilrs.remap_button(id, missing, Button::South),
+            Err(MappingError::InvalidCode(missing))
+        );
+
+        gilrs.remap_button(id, east, Button::South).unwrap();
+        assert_eq!(pressed(&mut gilrs, id, nec::BTN_EAST), Button::South);
+        assert_eq!(
+            gilrs.gamepad(id).element_remaps(),
+            [(east, AxisOrBtn::Btn(Button::South))]
+        );
+
+        // Remaps are kept when gamepad reconnects.
+        gilrs.test_backend().disconnect(id.0);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(
+            gilrs.remap_button(id, east, Button::North),
+            Err(MappingError::NotConnected)
+        );
+        gilrs.test_backend().reconnect(id.0);
+        while gilrs.next_event().is_some() {}
+        assert_eq!(pressed(&mut gilrs, id, nec::BTN_EAST), Button::South);
+
+        gilrs.clear_element_remaps(id);
+        assert_eq!(pressed(&mut gilrs, id, nec::BTN_EAST), Button::East);
+        assert!(gilrs.gamepad(id).element_remaps().is_empty());
+    }
+
+    #[test]
+    fn mapping_changed_events() {
+        fn mapping_changed(gilrs: &mut super::Gilrs) -> usize {
+            let mut count = 0;
+            while let Some(ev) = gilrs.next_event() {
+                if ev.event == EventType::MappingChanged {
+                    count += 1;
+                }
+            }
+
+            count
+        }
+
+        let mut gilrs = GilrsBuilder::new().build().unwrap();
+        let id = test_backend::connect(&mut gilrs, "Test");
+        let south = gilrs.gamepad(id).button_code(Button::South).unwrap();
+        let east = gilrs.gamepad(id).button_code(Button::East).unwrap();
+
+        let mut data = MappingData::new();
+        data.insert_btn(south, Button::South);
+        gilrs.set_mapping(id.0, &data, None).unwrap();
+        assert_eq!(mapping_changed(&mut gilrs), 1);
+
+        let mut remap = CodeRemap::new();
+        remap.swap(south, east);
+        gilrs.set_code_remap(id, remap.clone());
+        assert_eq!(mapping_changed(&mut gilrs), 1);
+        gilrs.set_code_remap(id, remap);
+        assert_eq!(mapping_changed(&mut gilrs), 0);
+
+        gilrs.set_lever_mode(id, LeverMode::DPad);
+        assert_eq!(mapping_changed(&mut gilrs), 1);
+        gilrs.set_lever_mode(id, LeverMode::DPad);
+        assert_eq!(mapping_changed(&mut gilrs), 0);
+
+        gilrs.remap_button(id, east, Button::North).unwrap();
+        assert_eq!(mapping_changed(&mut gilrs), 1);
+    }
 }
@@ -0,0 +1,478 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Recognition of stick motions.
+//!
+//! This module is only available with `gestures` feature. [`GestureRecognizer`] watches
+//! `AxisChanged` events of left and right stick and reports when one of configured
+//! [`Gesture`]s was performed. Supported patterns are sequences of directions (for example
+//! quarter-circle or double-tap) that have to be completed within time window and flicks, which
+//! are fast moves from neutral position to edge of stick's range.
+//!
+//! # Example
+//!
+//! ```
+//! use gilrs::gestures::{Direction, Gesture, GestureRecognizer, Stick};
+//! use gilrs::Gilrs;
+//! use std::time::Duration;
+//!
+//! let mut gilrs = Gilrs::new().unwrap();
+//! let mut gestures = GestureRecognizer::new(vec![
+//!     Gesture::quarter_circle("hadouken", Stick::Left, Direction::Right, Duration::from_millis(300)),
+//!     Gesture::double_tap("dash", Stick::Left, Direction::Right, Duration::from_millis(250)),
+//!     Gesture::flick("flick", Stick::Right, None, 8.0),
+//! ]);
+//!
+//! while let Some(event) = gilrs.next_event() {
+//!     for gesture in gestures.handle_event(&event) {
+//!         println!("{} performed {}", gesture.id, gesture.name);
+//!     }
+//! }
+//! ```
+
+use crate::ev::{Axis, Event, EventType};
+use crate::gamepad::GamepadId;
+
+use fnv::FnvHashMap;
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+const HISTORY_LEN: usize = 16;
+const FLICK_EDGE: f32 = 0.9;
+
+/// Analog stick watched by gesture.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+impl Stick {
+    fn from_axis(axis: Axis) -> Option<(Self, bool)> {
+        Some(match axis {
+            Axis::LeftStickX => (Stick::Left, true),
+            Axis::LeftStickY => (Stick::Left, false),
+            Axis::RightStickX => (Stick::Right, true),
+            Axis::RightStickY => (Stick::Right, false),
+            _ => return None,
+        })
+    }
+}
+
+/// One of eight directions in which stick can be pushed, or neutral position.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Direction {
+    Neutral,
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// Returns direction of stick at position `(x, y)`. If distance from center is lower than
+    /// `deadzone`, `Neutral` is returned.
+    pub fn from_position(x: f32, y: f32, deadzone: f32) -> Self {
+        const DIRECTIONS: [Direction; 8] = [
+            Direction::Right,
+            Direction::UpRight,
+            Direction::Up,
+            Direction::UpLeft,
+            Direction::Left,
+            Direction::DownLeft,
+            Direction::Down,
+            Direction::DownRight,
+        ];
+
+        if (x * x + y * y).sqrt() < deadzone {
+            return Direction::Neutral;
+        }
+
+        let sector = (y.atan2(x) / std::f32::consts::FRAC_PI_4).round() as i32;
+        DIRECTIONS[sector.rem_euclid(8) as usize]
+    }
+}
+
+/// Stick motion that is recognized by [`GestureRecognizer`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern {
+    /// Stick has to go through all directions in `sequence`, without any other directions in
+    /// between, and whole sequence has to be completed within `window`.
+    Motion {
+        sequence: Vec<Direction>,
+        window: Duration,
+    },
+    /// Stick has to move from neutral position to edge of its range with speed of at least
+    /// `min_speed` (in units of axis value per second). If `direction` is not `None`, stick has to
+    /// end in that direction.
+    Flick {
+        direction: Option<Direction>,
+        min_speed: f32,
+    },
+}
+
+/// Named stick motion.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gesture {
+    pub name: String,
+    pub stick: Stick,
+    pub pattern: Pattern,
+}
+
+impl Gesture {
+    /// Creates gesture that is recognized when stick goes through all directions in `sequence`
+    /// within `window`.
+    pub fn motion(name: &str, stick: Stick, sequence: &[Direction], window: Duration) -> Self {
+        Gesture {
+            name: name.to_owned(),
+            stick,
+            pattern: Pattern::Motion {
+                sequence: sequence.to_vec(),
+                window,
+            },
+        }
+    }
+
+    /// Creates quarter-circle motion from down to `forward`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `forward` is not `Direction::Left` or `Direction::Right`.
+    pub fn quarter_circle(name: &str, stick: Stick, forward: Direction, window: Duration) -> Self {
+        let diagonal = match forward {
+            Direction::Left => Direction::DownLeft,
+            Direction::Right => Direction::DownRight,
+            _ => panic!("Quarter-circle has to end in left or right direction"),
+        };
+
+        Self::motion(name, stick, &[Direction::Down, diagonal, forward], window)
+    }
+
+    /// Creates gesture that is recognized when stick is pushed twice in `direction` within
+    /// `window`, with return to neutral position in between.
+    pub fn double_tap(name: &str, stick: Stick, direction: Direction, window: Duration) -> Self {
+        Self::motion(
+            name,
+            stick,
+            &[direction, Direction::Neutral, direction],
+            window,
+        )
+    }
+
+    /// Creates gesture that is recognized when stick is quickly moved from neutral position to
+    /// edge of its range.
+    pub fn flick(name: &str, stick: Stick, direction: Option<Direction>, min_speed: f32) -> Self {
+        Gesture {
+            name: name.to_owned(),
+            stick,
+            pattern: Pattern::Flick {
+                direction,
+                min_speed,
+            },
+        }
+    }
+}
+
+/// Gesture performed by a gamepad.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GestureEvent {
+    /// Id of gamepad that performed gesture.
+    pub id: GamepadId,
+    /// Name of recognized gesture.
+    pub name: String,
+    /// Index of recognized gesture in list passed to [`GestureRecognizer::new()`].
+    pub index: usize,
+    /// Time of event that completed gesture.
+    pub time: SystemTime,
+}
+
+#[derive(Clone, Debug)]
+struct StickState {
+    x: f32,
+    y: f32,
+    history: VecDeque<(Direction, SystemTime)>,
+    // Last position inside dead zone, used to compute speed of flicks.
+    neutral: Option<(f32, SystemTime)>,
+}
+
+impl StickState {
+    fn new() -> Self {
+        StickState {
+            x: 0.0,
+            y: 0.0,
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            neutral: None,
+        }
+    }
+
+    fn direction(&self) -> Direction {
+        self.history
+            .back()
+            .map(|&(dir, _)| dir)
+            .unwrap_or(Direction::Neutral)
+    }
+
+    fn matches(&self, sequence: &[Direction], window: Duration) -> bool {
+        if sequence.is_empty() || self.history.len() < sequence.len() {
+            return false;
+        }
+
+        let start = self.history.len() - sequence.len();
+        if !self
+            .history
+            .range(start..)
+            .map(|&(dir, _)| dir)
+            .eq(sequence.iter().copied())
+        {
+            return false;
+        }
+
+        let first = self.history[start].1;
+        let last = self.history[self.history.len() - 1].1;
+        last.duration_since(first).unwrap_or_default() <= window
+    }
+}
+
+/// Recognizes [`Gesture`]s in stream of events.
+///
+/// See [module level documentation](self) for example.
+#[derive(Clone, Debug)]
+pub struct GestureRecognizer {
+    gestures: Vec<Gesture>,
+    deadzone: f32,
+    sticks: FnvHashMap<(GamepadId, Stick), StickState>,
+}
+
+impl GestureRecognizer {
+    /// Creates new `GestureRecognizer` for `gestures`. Dead zone used to detect neutral position
+    /// is set to 0.5.
+    pub fn new(gestures: Vec<Gesture>) -> Self {
+        GestureRecognizer {
+            gestures,
+            deadzone: 0.5,
+            sticks: FnvHashMap::default(),
+        }
+    }
+
+    /// Sets distance from center below which stick is in neutral position.
+    pub fn with_deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = deadzone;
+        self
+    }
+
+    /// Returns recognized gestures.
+    pub fn gestures(&self) -> &[Gesture] {
+        &self.gestures
+    }
+
+    /// Processes `event` and returns gestures that it completed.
+    ///
+    /// Events should be passed after filters that modify axis values, like `deadzone`.
+    pub fn handle_event(&mut self, event: &Event) -> Vec<GestureEvent> {
+        let (stick, is_x, value) = match event.event {
+            EventType::AxisChanged(axis, value, _) => match Stick::from_axis(axis) {
+                Some((stick, is_x)) => (stick, is_x, value),
+                None => return Vec::new(),
+            },
+            EventType::Disconnected => {
+                self.sticks.retain(|&(id, _), _| id != event.id);
+                return Vec::new();
+            }
+            _ => return Vec::new(),
+        };
+
+        let state = self
+            .sticks
+            .entry((event.id, stick))
+            .or_insert_with(StickState::new);
+
+        let prev_magnitude = (state.x * state.x + state.y * state.y).sqrt();
+        if is_x {
+            state.x = value;
+        } else {
+            state.y = value;
+        }
+        let magnitude = (state.x * state.x + state.y * state.y).sqrt();
+
+        let direction = Direction::from_position(state.x, state.y, self.deadzone);
+        let direction_changed = direction != state.direction();
+        if direction_changed {
+            if state.history.len() == HISTORY_LEN {
+                state.history.pop_front();
+            }
+            state.history.push_back((direction, event.time));
+        }
+
+        let flick_speed = if magnitude < self.deadzone {
+            state.neutral = Some((magnitude, event.time));
+            None
+        } else if prev_magnitude < FLICK_EDGE && magnitude >= FLICK_EDGE {
+            state.neutral.take().map(|(start, time)| {
+                let dt = event
+                    .time
+                    .duration_since(time)
+                    .unwrap_or_default()
+                    .as_secs_f32();
+                (magnitude - start) / dt
+            })
+        } else {
+            None
+        };
+
+        let mut recognized = Vec::new();
+        for (index, gesture) in self.gestures.iter().enumerate() {
+            if gesture.stick != stick {
+                continue;
+            }
+
+            let matched = match gesture.pattern {
+                Pattern::Motion {
+                    ref sequence,
+                    window,
+                } => direction_changed && state.matches(sequence, window),
+                Pattern::Flick {
+                    direction: dir,
+                    min_speed,
+                } => {
+                    matches!(flick_speed, Some(speed) if speed >= min_speed)
+                        && dir.map_or(true, |dir| dir == direction)
+                }
+            };
+
+            if matched {
+                recognized.push(GestureEvent {
+                    id: event.id,
+                    name: gesture.name.clone(),
+                    index,
+                    time: event.time,
+                });
+            }
+        }
+
+        recognized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, Gesture, GestureRecognizer, Stick};
+    use crate::ev::{Axis, Code, Event, EventType};
+    use crate::gamepad::GamepadId;
+    use gilrs_core::native_ev_codes as necs;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn axis(axis: Axis, value: f32, ms: u64) -> Event {
+        Event {
+            id: GamepadId(0),
+            event: EventType::AxisChanged(axis, value, Code(necs::AXIS_LSTICKX)),
+            time: at(ms),
+        }
+    }
+
+    fn at(ms: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(ms)
+    }
+
+    fn names(rec: &mut GestureRecognizer, events: &[Event]) -> Vec<String> {
+        events
+            .iter()
+            .flat_map(|ev| rec.handle_event(ev))
+            .map(|g| g.name)
+            .collect()
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Direction::from_position(0.1, 0.2, 0.5), Direction::Neutral);
+        assert_eq!(Direction::from_position(0.0, 1.0, 0.5), Direction::Up);
+        assert_eq!(
+            Direction::from_position(0.7, -0.7, 0.5),
+            Direction::DownRight
+        );
+        assert_eq!(Direction::from_position(-1.0, 0.1, 0.5), Direction::Left);
+        assert_eq!(Direction::from_position(0.9, -0.2, 0.5), Direction::Right);
+    }
+
+    #[test]
+    fn quarter_circle() {
+        let window = Duration::from_millis(200);
+        let mut rec = GestureRecognizer::new(vec![Gesture::quarter_circle(
+            "qcf",
+            Stick::Left,
+            Direction::Right,
+            window,
+        )]);
+
+        let qcf = |start| {
+            [
+                axis(Axis::LeftStickY, -1.0, start),
+                axis(Axis::LeftStickX, 0.9, start + 50),
+                axis(Axis::LeftStickY, 0.0, start + 100),
+                axis(Axis::LeftStickX, 0.0, start + 150),
+            ]
+        };
+
+        assert_eq!(names(&mut rec, &qcf(0)), ["qcf"]);
+
+        // Too slow
+        let mut slow = qcf(1000);
+        slow[2].time = at(1300);
+        slow[3].time = at(1350);
+        assert!(names(&mut rec, &slow).is_empty());
+
+        // Wrong stick
+        let right: Vec<_> = qcf(2000)
+            .iter()
+            .map(|ev| match ev.event {
+                EventType::AxisChanged(Axis::LeftStickX, v, c) => Event {
+                    event: EventType::AxisChanged(Axis::RightStickX, v, c),
+                    ..*ev
+                },
+                EventType::AxisChanged(Axis::LeftStickY, v, c) => Event {
+                    event: EventType::AxisChanged(Axis::RightStickY, v, c),
+                    ..*ev
+                },
+                _ => *ev,
+            })
+            .collect();
+        assert!(names(&mut rec, &right).is_empty());
+    }
+
+    #[test]
+    fn double_tap_and_flick() {
+        let mut rec = GestureRecognizer::new(vec![
+            Gesture::double_tap(
+                "dash",
+                Stick::Left,
+                Direction::Left,
+                Duration::from_millis(200),
+            ),
+            Gesture::flick("flick", Stick::Left, Some(Direction::Left), 5.0),
+        ]);
+
+        let events = [
+            axis(Axis::LeftStickX, -1.0, 0),
+            axis(Axis::LeftStickX, 0.0, 50),
+            axis(Axis::LeftStickX, -1.0, 100),
+        ];
+        // There is no neutral position before first push, so it is not a flick.
+        assert_eq!(names(&mut rec, &events), ["dash", "flick"]);
+
+        // Slow push is not a flick.
+        let events = [
+            axis(Axis::LeftStickX, 0.0, 1000),
+            axis(Axis::LeftStickX, -0.6, 1500),
+            axis(Axis::LeftStickX, -1.0, 2000),
+        ];
+        assert!(names(&mut rec, &events).is_empty());
+    }
+}
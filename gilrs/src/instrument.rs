@@ -0,0 +1,360 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Support for instrument controllers like guitars, drum kits and DJ turntables.
+//!
+//! Operating systems report instrument controllers as gamepads, so their frets, pads and knobs
+//! are mapped to standard buttons and axes. [`InstrumentMapper`] translates events of gamepads
+//! that have [`Layout`] assigned into [`InstrumentEvent`]s that use instrument [`Element`]s and
+//! carry velocity of hits.
+//!
+//! Predefined layouts follow conventions of XInput instruments. There is no predefined layout for
+//! turntables, because their reports differ between models, use [`Layout::with_element()`] to
+//! describe them.
+//!
+//! # Example
+//!
+//! ```
+//! use gilrs::instrument::{Element, InstrumentEventKind, InstrumentMapper, Layout};
+//! use gilrs::Gilrs;
+//!
+//! let mut gilrs = Gilrs::new().unwrap();
+//! let mut instruments = InstrumentMapper::new();
+//!
+//! for (id, _) in gilrs.gamepads() {
+//!     instruments.set_layout(id, Some(Layout::guitar()));
+//! }
+//!
+//! while let Some(event) = gilrs.next_event() {
+//!     if let Some(ev) = instruments.handle_event(&event) {
+//!         if let (Element::Fret(fret), InstrumentEventKind::Pressed { .. }) = (ev.element, ev.kind) {
+//!             println!("{} pressed fret {}", ev.id, fret);
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::ev::{Axis, AxisOrBtn, Button, Event, EventType};
+use crate::gamepad::GamepadId;
+
+use fnv::FnvHashMap;
+
+use std::time::SystemTime;
+
+/// Element of instrument controller.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Element {
+    /// Fret button of guitar, numbered from 0 (green) to 4 (orange).
+    Fret(u8),
+    /// Lower (solo) fret button of guitar, numbered like `Fret`.
+    SoloFret(u8),
+    /// Strum bar of guitar moved up.
+    StrumUp,
+    /// Strum bar of guitar moved down.
+    StrumDown,
+    /// Whammy bar of guitar.
+    Whammy,
+    /// Tilt sensor of guitar.
+    Tilt,
+    /// Drum pad, numbered from left.
+    Pad(u8),
+    /// Cymbal of drum kit, numbered from left.
+    Cymbal(u8),
+    /// Kick pedal of drum kit.
+    Kick,
+    /// Turntable platter, numbered from left.
+    Platter(u8),
+    /// Crossfader of turntable.
+    Crossfader,
+    /// Effects knob of turntable.
+    EffectsKnob,
+}
+
+/// Maps buttons and axes of gamepad to instrument elements.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Layout {
+    elements: Vec<(AxisOrBtn, Element)>,
+    velocity: Vec<(Element, Axis)>,
+}
+
+impl Layout {
+    /// Creates empty layout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates layout of guitar: `South`, `East`, `North`, `West` and `LeftTrigger` are frets from
+    /// 0 to 4, d-pad up and down is strum bar, `RightStickX` is whammy bar and `RightStickY` is
+    /// tilt sensor.
+    pub fn guitar() -> Self {
+        Layout::new()
+            .with_element(AxisOrBtn::Btn(Button::South), Element::Fret(0))
+            .with_element(AxisOrBtn::Btn(Button::East), Element::Fret(1))
+            .with_element(AxisOrBtn::Btn(Button::North), Element::Fret(2))
+            .with_element(AxisOrBtn::Btn(Button::West), Element::Fret(3))
+            .with_element(AxisOrBtn::Btn(Button::LeftTrigger), Element::Fret(4))
+            .with_element(AxisOrBtn::Btn(Button::DPadUp), Element::StrumUp)
+            .with_element(AxisOrBtn::Btn(Button::DPadDown), Element::StrumDown)
+            .with_element(AxisOrBtn::Axis(Axis::RightStickX), Element::Whammy)
+            .with_element(AxisOrBtn::Axis(Axis::RightStickY), Element::Tilt)
+    }
+
+    /// Creates layout of drum kit: `East`, `North`, `West` and `South` are pads from 0 to 3 and
+    /// `LeftTrigger` is kick pedal. Velocity is not reported unless it is set with
+    /// [`with_velocity()`](Self::with_velocity).
+    pub fn drums() -> Self {
+        Layout::new()
+            .with_element(AxisOrBtn::Btn(Button::East), Element::Pad(0))
+            .with_element(AxisOrBtn::Btn(Button::North), Element::Pad(1))
+            .with_element(AxisOrBtn::Btn(Button::West), Element::Pad(2))
+            .with_element(AxisOrBtn::Btn(Button::South), Element::Pad(3))
+            .with_element(AxisOrBtn::Btn(Button::LeftTrigger), Element::Kick)
+    }
+
+    /// Maps `source` to `element`, replacing previous mapping of `source`.
+    pub fn with_element(mut self, source: AxisOrBtn, element: Element) -> Self {
+        self.elements.retain(|&(s, _)| s != source);
+        self.elements.push((source, element));
+        self
+    }
+
+    /// Uses absolute value of `axis` as velocity of hits of `element`.
+    pub fn with_velocity(mut self, element: Element, axis: Axis) -> Self {
+        self.velocity.retain(|&(e, _)| e != element);
+        self.velocity.push((element, axis));
+        self
+    }
+
+    /// Returns element mapped to `source`.
+    pub fn element(&self, source: AxisOrBtn) -> Option<Element> {
+        self.elements
+            .iter()
+            .find(|&&(s, _)| s == source)
+            .map(|&(_, e)| e)
+    }
+
+    fn velocity_axis(&self, element: Element) -> Option<Axis> {
+        self.velocity
+            .iter()
+            .find(|&&(e, _)| e == element)
+            .map(|&(_, a)| a)
+    }
+}
+
+/// What happened to instrument element.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InstrumentEventKind {
+    /// Element was pressed or hit. Velocity is in range from 0.0 to 1.0, or 1.0 if it isn't
+    /// reported by instrument.
+    Pressed { velocity: f32 },
+    /// Element that was pressed or hit is no longer held.
+    Released,
+    /// Value of analog element changed.
+    Changed(f32),
+}
+
+/// Event of instrument element.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InstrumentEvent {
+    /// Id of gamepad that is the instrument.
+    pub id: GamepadId,
+    pub element: Element,
+    pub kind: InstrumentEventKind,
+    /// Time of event that caused this one.
+    pub time: SystemTime,
+}
+
+/// Translates events of instrument controllers to [`InstrumentEvent`]s.
+///
+/// See [module level documentation](self) for example.
+#[derive(Clone, Debug, Default)]
+pub struct InstrumentMapper {
+    layouts: FnvHashMap<GamepadId, Layout>,
+    // Last values of axes used as velocity.
+    axes: FnvHashMap<(GamepadId, Axis), f32>,
+}
+
+impl InstrumentMapper {
+    /// Creates `InstrumentMapper` without any layout, so no events are translated.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets layout of gamepad with given id, or removes it if `layout` is `None`. Layout is kept
+    /// when gamepad disconnects.
+    pub fn set_layout(&mut self, id: GamepadId, layout: Option<Layout>) {
+        match layout {
+            Some(layout) => self.layouts.insert(id, layout),
+            None => self.layouts.remove(&id),
+        };
+    }
+
+    /// Returns layout of gamepad with given id.
+    pub fn layout(&self, id: GamepadId) -> Option<&Layout> {
+        self.layouts.get(&id)
+    }
+
+    /// Processes `event` and returns instrument event that it caused, if any.
+    ///
+    /// Velocity of hit is the last value of its axis reported before button was pressed, so
+    /// events should be passed in order in which they were received.
+    pub fn handle_event(&mut self, event: &Event) -> Option<InstrumentEvent> {
+        let layout = self.layouts.get(&event.id)?;
+
+        let (element, kind) = match event.event {
+            EventType::ButtonPressed(btn, _) => {
+                let element = layout.element(AxisOrBtn::Btn(btn))?;
+                let velocity = layout
+                    .velocity_axis(element)
+                    .and_then(|axis| self.axes.get(&(event.id, axis)))
+                    .map_or(1.0, |val| val.abs().min(1.0));
+
+                (element, InstrumentEventKind::Pressed { velocity })
+            }
+            EventType::ButtonReleased(btn, _) => (
+                layout.element(AxisOrBtn::Btn(btn))?,
+                InstrumentEventKind::Released,
+            ),
+            EventType::AxisChanged(axis, val, _) => {
+                if layout.velocity.iter().any(|&(_, a)| a == axis) {
+                    self.axes.insert((event.id, axis), val);
+                }
+
+                (
+                    layout.element(AxisOrBtn::Axis(axis))?,
+                    InstrumentEventKind::Changed(val),
+                )
+            }
+            EventType::Disconnected => {
+                self.axes.retain(|&(id, _), _| id != event.id);
+                return None;
+            }
+            _ => return None,
+        };
+
+        Some(InstrumentEvent {
+            id: event.id,
+            element,
+            kind,
+            time: event.time,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Element, InstrumentEventKind, InstrumentMapper, Layout};
+    use crate::ev::{Axis, Button, Code, Event, EventType};
+    use crate::gamepad::GamepadId;
+    use gilrs_core::native_ev_codes as necs;
+
+    fn kinds(
+        mapper: &mut InstrumentMapper,
+        id: GamepadId,
+        event: EventType,
+    ) -> Option<(Element, InstrumentEventKind)> {
+        mapper
+            .handle_event(&Event::new(id, event))
+            .map(|ev| (ev.element, ev.kind))
+    }
+
+    #[test]
+    fn guitar() {
+        let (guitar, gamepad) = (GamepadId(0), GamepadId(1));
+        let mut mapper = InstrumentMapper::new();
+        mapper.set_layout(guitar, Some(Layout::guitar()));
+
+        let south = Code(necs::BTN_SOUTH);
+        assert_eq!(
+            kinds(
+                &mut mapper,
+                guitar,
+                EventType::ButtonPressed(Button::South, south)
+            ),
+            Some((
+                Element::Fret(0),
+                InstrumentEventKind::Pressed { velocity: 1.0 }
+            ))
+        );
+        assert_eq!(
+            kinds(
+                &mut mapper,
+                guitar,
+                EventType::ButtonReleased(Button::South, south)
+            ),
+            Some((Element::Fret(0), InstrumentEventKind::Released))
+        );
+        let rx = Code(necs::AXIS_RSTICKX);
+        assert_eq!(
+            kinds(
+                &mut mapper,
+                guitar,
+                EventType::AxisChanged(Axis::RightStickX, 0.5, rx)
+            ),
+            Some((Element::Whammy, InstrumentEventKind::Changed(0.5)))
+        );
+        // Unmapped elements and gamepads without layout are ignored.
+        let start = Code(necs::BTN_START);
+        assert!(kinds(
+            &mut mapper,
+            guitar,
+            EventType::ButtonPressed(Button::Start, start)
+        )
+        .is_none());
+        assert!(kinds(
+            &mut mapper,
+            gamepad,
+            EventType::ButtonPressed(Button::South, south)
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn drum_velocity() {
+        let id = GamepadId(0);
+        let mut mapper = InstrumentMapper::new();
+        mapper.set_layout(
+            id,
+            Some(Layout::drums().with_velocity(Element::Pad(0), Axis::LeftStickX)),
+        );
+
+        let lx = Code(necs::AXIS_LSTICKX);
+        let east = Code(necs::BTN_EAST);
+        assert!(kinds(
+            &mut mapper,
+            id,
+            EventType::AxisChanged(Axis::LeftStickX, -0.25, lx)
+        )
+        .is_none());
+        assert_eq!(
+            kinds(
+                &mut mapper,
+                id,
+                EventType::ButtonPressed(Button::East, east)
+            ),
+            Some((
+                Element::Pad(0),
+                InstrumentEventKind::Pressed { velocity: 0.25 }
+            ))
+        );
+
+        // Velocity is forgotten when gamepad disconnects.
+        mapper.handle_event(&Event::new(id, EventType::Disconnected));
+        assert_eq!(
+            kinds(
+                &mut mapper,
+                id,
+                EventType::ButtonPressed(Button::East, east)
+            ),
+            Some((
+                Element::Pad(0),
+                InstrumentEventKind::Pressed { velocity: 1.0 }
+            ))
+        );
+    }
+}
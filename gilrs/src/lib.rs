@@ -58,6 +58,7 @@
 //! |                  | Input | Hotplugging | Force feedback |
 //! |------------------|:-----:|:-----------:|:--------------:|
 //! | Linux/BSD (evdev)|   ✓   |      ✓      |        ✓       |
+//! | Windows (WGI)    |   ✓   |      ✓      |        ✓       |
 //! | Windows (XInput) |   ✓   |      ✓      |        ✓       |
 //! | OS X             |   ✓   |      ✓      |        ✕       |
 //! | Wasm             |   ✓   |      ✓      |       n/a      |
@@ -77,13 +78,20 @@
 //! unified button layout for platforms that doesn't make any guarantees about it. The main source
 //! is [SDL_GameControllerDB](https://github.com/gabomdq/SDL_GameControllerDB), but library also
 //! support loading mappings from environment variable `SDL_GAMECONTROLLERCONFIG` (which Steam
-//! use).
+//! use) and from file named by `SDL_GAMECONTROLLERCONFIG_FILE`. If you want to override mappings
+//! only for gilrs-based games, use `GILRS_GAMECONTROLLERCONFIG`, which takes precedence over SDL
+//! variables.
 //!
 //! Cargo features
 //! --------------
 //!
 //! - `serde-serialize` - enable deriving of serde's `Serialize` and `Deserialize` for
 //!   various types.
+//! - `gestures` - enable [`gestures`] module that recognizes stick motions like quarter-circle.
+//! - `uinput` - enable `output` module that creates virtual gamepads (Linux only).
+//! - `tracing` - emit structured [`tracing`](https://docs.rs/tracing) events for gamepad
+//!   hotplug, mapping resolution, events dropped by filters and force feedback commands, and span
+//!   for force feedback thread. Without it, the same diagnostics are logged with `log` crate.
 //!
 //! Platform specific notes
 //! ======================
@@ -94,6 +102,7 @@
 //! With evdev, GilRs read (and write, in case of force feedback) directly from appropriate
 //! `/dev/input/event*` file. This mean that user have to have read and write access to this file.
 //! On most distros it shouldn't be a problem, but if it is, you will have to create udev rule.
+//! Gamepads that can't be opened because of that are listed by `Gilrs::inaccessible_devices()`.
 //! On FreeBSD generic HID gamepads use hgame(4) and special use Linux driver via `webcamd`.
 //!
 //! To build GilRs, you will need pkg-config and libudev .pc file. On some distributions this file
@@ -111,18 +120,52 @@
 #[macro_use]
 extern crate log;
 
+/// Emits diagnostic event with `key = value` fields. With `tracing` feature fields are recorded
+/// as structured data, otherwise they are appended to the `log` message.
+macro_rules! diag {
+    ($lvl:ident, $($field:ident = $val:expr),+; $($arg:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::$lvl!($($field = ?$val,)+ $($arg)+);
+        #[cfg(not(feature = "tracing"))]
+        log::$lvl!(
+            concat!("{}", $(" ", stringify!($field), "={:?}"),+),
+            format_args!($($arg)+),
+            $($val),+
+        );
+    }};
+}
+
 mod constants;
 mod gamepad;
 mod mapping;
+mod players;
+mod profiles;
+#[cfg(test)]
+mod test_backend;
 mod utils;
 
+pub mod calibration;
 pub mod ev;
 pub mod ff;
+#[cfg(feature = "gestures")]
+pub mod gestures;
+pub mod instrument;
+#[cfg(all(target_os = "linux", feature = "uinput"))]
+pub mod output;
 
 pub use crate::ev::filter::Filter;
-pub use crate::ev::{Axis, Button, Event, EventType};
+pub use crate::ev::{Axis, AxisDirection, Button, Event, EventType};
 pub use crate::gamepad::{
-    ConnectedGamepadsIterator, Error, Gamepad, GamepadId, Gilrs, GilrsBuilder, MappingSource,
-    PowerInfo,
+    ConnectedGamepadsIterator, ConnectionType, Error, Gamepad, GamepadId, Gilrs, GilrsBuilder,
+    Guid, HeadsetState, HidError, InaccessibleDevice, MappingSource, ParseGuidError, PowerInfo,
+    Stats, SubscriptionId, TriggerDeadzone, TriggerRange,
+};
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub use crate::mapping::fuzz_sdl_mapping;
+pub use crate::mapping::{
+    AxisRange, CodeRemap, LeverMode, MappingData as Mapping, MappingEntry, MappingError,
+    MappingLayer,
 };
-pub use crate::mapping::{MappingData as Mapping, MappingError};
+pub use crate::players::{PlayerAssigner, PlayerEvent};
+pub use crate::profiles::{Profile, ProfileError, ProfileId, Profiles};
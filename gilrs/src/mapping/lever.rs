@@ -0,0 +1,242 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ev::{Axis, Button, Code, EventType};
+use gilrs_core::{native_ev_codes as nec, EvCode};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+/// How lever of arcade stick or hitbox is reported.
+///
+/// Depending on model (and often on position of a switch on the device), lever is reported either
+/// as d-pad or as left stick, while most games expect only one of them. With modes other than
+/// `Mapped`, `Gilrs` additionally reports lever as the other element, so game receives events it
+/// expects regardless of the device. Original events are still reported.
+///
+/// Mode is set per gamepad with [`Gilrs::set_lever_mode()`](crate::Gilrs::set_lever_mode) and is
+/// kept when gamepad reconnects. Emulated elements that are not in gamepad's mapping use standard
+/// `Code`s, so [`Gamepad::is_pressed()`](crate::Gamepad::is_pressed) and
+/// [`Gamepad::value()`](crate::Gamepad::value) work for them too.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum LeverMode {
+    /// Lever is reported only as element it is mapped to.
+    #[default]
+    Mapped,
+    /// Left stick is also reported as d-pad buttons.
+    DPad,
+    /// D-pad is also reported as left stick.
+    LeftStick,
+    /// Left stick is also reported as d-pad and d-pad as left stick.
+    Both,
+}
+
+impl LeverMode {
+    fn emulates_dpad(self) -> bool {
+        matches!(self, LeverMode::DPad | LeverMode::Both)
+    }
+
+    fn emulates_stick(self) -> bool {
+        matches!(self, LeverMode::LeftStick | LeverMode::Both)
+    }
+}
+
+/// Element reported by `Lever`, with `Code` to use if gamepad's mapping doesn't have it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum LeverOutput {
+    Button(Button, bool, Code),
+    Axis(Axis, f32, Code),
+}
+
+// D-pad buttons with their standard codes, indexed by `UP`, `DOWN`, `LEFT` and `RIGHT`.
+const DPAD: [(Button, EvCode); 4] = [
+    (Button::DPadUp, nec::BTN_DPAD_UP),
+    (Button::DPadDown, nec::BTN_DPAD_DOWN),
+    (Button::DPadLeft, nec::BTN_DPAD_LEFT),
+    (Button::DPadRight, nec::BTN_DPAD_RIGHT),
+];
+const UP: usize = 0;
+const DOWN: usize = 1;
+const LEFT: usize = 2;
+const RIGHT: usize = 3;
+
+/// Per gamepad state of lever emulation.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Lever {
+    pub mode: LeverMode,
+    // Pressed d-pad buttons reported by gamepad.
+    dpad: [bool; 4],
+    // Position of d-pad reported as left stick.
+    stick: [f32; 2],
+    // Pressed d-pad buttons emulated from left stick.
+    emulated_dpad: [bool; 4],
+}
+
+impl Lever {
+    pub fn new(mode: LeverMode) -> Self {
+        Lever {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// Updates lever with mapped event reported by gamepad and appends elements that should be
+    /// additionally reported to `out`. `pressed` and `released` are thresholds used to convert
+    /// stick to d-pad buttons.
+    pub fn handle(
+        &mut self,
+        event: &EventType,
+        pressed: f32,
+        released: f32,
+        out: &mut Vec<LeverOutput>,
+    ) {
+        match *event {
+            EventType::ButtonPressed(btn, _) | EventType::ButtonReleased(btn, _)
+                if self.mode.emulates_stick() =>
+            {
+                if let Some(idx) = DPAD.iter().position(|&(b, _)| b == btn) {
+                    self.dpad[idx] = matches!(event, EventType::ButtonPressed(..));
+                    let x = self.dpad[RIGHT] as i8 - self.dpad[LEFT] as i8;
+                    let y = self.dpad[UP] as i8 - self.dpad[DOWN] as i8;
+                    self.set_stick(0, x as f32, out);
+                    self.set_stick(1, y as f32, out);
+                }
+            }
+            EventType::AxisChanged(Axis::DPadX, val, _) if self.mode.emulates_stick() => {
+                self.set_stick(0, val, out)
+            }
+            EventType::AxisChanged(Axis::DPadY, val, _) if self.mode.emulates_stick() => {
+                self.set_stick(1, val, out)
+            }
+            EventType::AxisChanged(Axis::LeftStickX, val, _) if self.mode.emulates_dpad() => {
+                self.set_dpad(LEFT, -val, pressed, released, out);
+                self.set_dpad(RIGHT, val, pressed, released, out);
+            }
+            EventType::AxisChanged(Axis::LeftStickY, val, _) if self.mode.emulates_dpad() => {
+                self.set_dpad(DOWN, -val, pressed, released, out);
+                self.set_dpad(UP, val, pressed, released, out);
+            }
+            _ => (),
+        }
+    }
+
+    fn set_stick(&mut self, idx: usize, val: f32, out: &mut Vec<LeverOutput>) {
+        if self.stick[idx] == val {
+            return;
+        }
+        self.stick[idx] = val;
+
+        let (axis, code) = if idx == 0 {
+            (Axis::LeftStickX, nec::AXIS_LSTICKX)
+        } else {
+            (Axis::LeftStickY, nec::AXIS_LSTICKY)
+        };
+        out.push(LeverOutput::Axis(axis, val, Code(code)));
+    }
+
+    fn set_dpad(
+        &mut self,
+        idx: usize,
+        val: f32,
+        pressed: f32,
+        released: f32,
+        out: &mut Vec<LeverOutput>,
+    ) {
+        let was_pressed = self.emulated_dpad[idx];
+        let is_pressed = if was_pressed {
+            val > released
+        } else {
+            val >= pressed
+        };
+
+        if is_pressed != was_pressed {
+            self.emulated_dpad[idx] = is_pressed;
+            let (btn, code) = DPAD[idx];
+            out.push(LeverOutput::Button(btn, is_pressed, Code(code)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lever, LeverMode, LeverOutput};
+    use crate::ev::{Axis, Button, Code, EventType};
+    use gilrs_core::native_ev_codes as nec;
+
+    fn handle(lever: &mut Lever, event: EventType) -> Vec<LeverOutput> {
+        let mut out = Vec::new();
+        lever.handle(&event, 0.75, 0.65, &mut out);
+        out
+    }
+
+    #[test]
+    fn dpad_to_stick() {
+        let up = Code(nec::BTN_DPAD_UP);
+        let mut lever = Lever::new(LeverMode::LeftStick);
+
+        assert_eq!(
+            handle(&mut lever, EventType::ButtonPressed(Button::DPadUp, up)),
+            vec![LeverOutput::Axis(
+                Axis::LeftStickY,
+                1.0,
+                Code(nec::AXIS_LSTICKY)
+            )]
+        );
+        assert_eq!(
+            handle(&mut lever, EventType::ButtonReleased(Button::DPadUp, up)),
+            vec![LeverOutput::Axis(
+                Axis::LeftStickY,
+                0.0,
+                Code(nec::AXIS_LSTICKY)
+            )]
+        );
+        let x = Code(nec::AXIS_DPADX);
+        assert_eq!(
+            handle(&mut lever, EventType::AxisChanged(Axis::DPadX, -1.0, x)),
+            vec![LeverOutput::Axis(
+                Axis::LeftStickX,
+                -1.0,
+                Code(nec::AXIS_LSTICKX)
+            )]
+        );
+
+        // Stick is not reported as d-pad in this mode.
+        let stick_x = Code(nec::AXIS_LSTICKX);
+        assert!(handle(
+            &mut lever,
+            EventType::AxisChanged(Axis::LeftStickX, 1.0, stick_x)
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn stick_to_dpad() {
+        let x = Code(nec::AXIS_LSTICKX);
+        let left = Code(nec::BTN_DPAD_LEFT);
+        let right = Code(nec::BTN_DPAD_RIGHT);
+        let mut lever = Lever::new(LeverMode::Both);
+
+        assert!(handle(&mut lever, EventType::AxisChanged(Axis::LeftStickX, 0.5, x)).is_empty());
+        assert_eq!(
+            handle(&mut lever, EventType::AxisChanged(Axis::LeftStickX, 0.8, x)),
+            vec![LeverOutput::Button(Button::DPadRight, true, right)]
+        );
+        // Hysteresis keeps button pressed.
+        assert!(handle(&mut lever, EventType::AxisChanged(Axis::LeftStickX, 0.7, x)).is_empty());
+        assert_eq!(
+            handle(
+                &mut lever,
+                EventType::AxisChanged(Axis::LeftStickX, -1.0, x)
+            ),
+            vec![
+                LeverOutput::Button(Button::DPadLeft, true, left),
+                LeverOutput::Button(Button::DPadRight, false, right),
+            ]
+        );
+    }
+}
@@ -6,9 +6,17 @@
 // copied, modified, or distributed except according to those terms.
 #![cfg_attr(target_os = "windows", allow(dead_code))]
 
+mod lever;
 mod parser;
+mod remap;
 
+pub use self::lever::LeverMode;
+pub(crate) use self::lever::{Lever, LeverOutput};
+pub use self::remap::CodeRemap;
+
+use crate::constants::{AXIS_DPADY, BTN_DPAD_RIGHT};
 use crate::ev::{self, Axis, AxisOrBtn, Button};
+use crate::gamepad::RawGamepad;
 use crate::utils::PATH_SEPARATOR;
 use gilrs_core::native_ev_codes as nec;
 use gilrs_core::EvCode;
@@ -16,12 +24,18 @@ use gilrs_core::EvCode;
 use std::collections::HashMap;
 use std::env;
 use std::error::Error;
+use std::ffi::OsString;
 use std::fmt::{Display, Formatter, Result as FmtResult, Write as _};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 use uuid::Uuid;
 use vec_map::VecMap;
 
+pub use self::parser::AxisRange;
+
 use self::parser::{Error as ParserError, ErrorKind as ParserErrorKind, Parser, Token};
 
 /// Platform name used by SDL mappings
@@ -45,22 +59,37 @@ const SDL_PLATFORM_NAME: &str = "Unknown";
 /// This struct is internal, `MappingData` is exported in public interface as `Mapping`.
 pub struct Mapping {
     mappings: FnvHashMap<EvCode, AxisOrBtn>,
+    // Axes marked with `~` in SDL mapping.
+    inverted: FnvHashSet<EvCode>,
+    // Input and output halves of entries like `+leftx:-a0`. Entries that are missing use full
+    // range.
+    ranges: FnvHashMap<EvCode, (AxisRange, AxisRange)>,
+    // Directions of first hat (SDL bitmask) that d-pad codes are mapped from.
+    hats: FnvHashMap<EvCode, u8>,
     name: String,
     default: bool,
     hats_mapped: u8,
+    // Reverse of `mappings`, indexed by `Button` and `Axis`. Updated by `index_rev()`.
+    buttons_rev: [Option<EvCode>; BTN_DPAD_RIGHT as usize + 1],
+    axes_rev: [Option<EvCode>; AXIS_DPADY as usize + 1],
 }
 
 impl Mapping {
     pub fn new() -> Self {
         Mapping {
             mappings: FnvHashMap::default(),
+            inverted: FnvHashSet::default(),
+            ranges: FnvHashMap::default(),
+            hats: FnvHashMap::default(),
             name: String::new(),
             default: false,
             hats_mapped: 0,
+            buttons_rev: Default::default(),
+            axes_rev: Default::default(),
         }
     }
 
-    pub fn default(gamepad: &gilrs_core::Gamepad) -> Self {
+    pub fn default(gamepad: &RawGamepad) -> Self {
         use self::Axis as Ax;
         use self::AxisOrBtn::*;
 
@@ -162,12 +191,20 @@ impl Mapping {
             }
         }
 
-        Mapping {
+        let mut mapping = Mapping {
             mappings,
+            inverted: FnvHashSet::default(),
+            ranges: FnvHashMap::default(),
+            hats: FnvHashMap::default(),
             name: String::new(),
             default: true,
             hats_mapped: 0,
-        }
+            buttons_rev: Default::default(),
+            axes_rev: Default::default(),
+        };
+        mapping.index_rev();
+
+        mapping
     }
 
     pub fn name(&self) -> &str {
@@ -196,7 +233,8 @@ impl Mapping {
                     ident,
                     ev_code,
                     mapped_btn,
-                    buttons,
+                    (buttons, axes),
+                    data.inverted.contains(&ev_code),
                     &mut sdl_mappings,
                     &mut mappings,
                 )
@@ -234,8 +272,9 @@ impl Mapping {
                 Self::add_axis(
                     ident,
                     ev_code,
-                    mapped_axis,
+                    AxisOrBtn::Axis(mapped_axis),
                     axes,
+                    data.inverted.contains(&ev_code),
                     &mut sdl_mappings,
                     &mut mappings,
                 )
@@ -255,16 +294,105 @@ impl Mapping {
             }
         }
 
-        let mapping = Mapping {
+        // Only axes can be inverted.
+        let inverted = data
+            .inverted
+            .iter()
+            .copied()
+            .filter(|code| mappings.contains_key(code) && axes.contains(code))
+            .collect();
+
+        let mut mapping = Mapping {
             mappings,
+            inverted,
+            ranges: FnvHashMap::default(),
+            hats: FnvHashMap::default(),
             name: name.to_owned(),
             default: false,
             hats_mapped: 0,
+            buttons_rev: Default::default(),
+            axes_rev: Default::default(),
         };
+        mapping.index_rev();
+        mapping.check_required()?;
 
         Ok((mapping, sdl_mappings))
     }
 
+    /// Parses mapping set with `Gilrs::set_sdl_mapping()`. Unlike `parse_sdl_mapping()`, which
+    /// skips entries that can't be used with gamepad, every invalid entry is reported as error.
+    pub fn from_sdl(line: &str, buttons: &[EvCode], axes: &[EvCode]) -> Result<Self, MappingError> {
+        let mut parser = Parser::new(line);
+        let mut targets = FnvHashSet::default();
+
+        loop {
+            let pos = parser.position();
+            let (to, output, valid) = match parser.next_token() {
+                None => break,
+                Some(Err(e)) if e.kind() == &ParserErrorKind::EmptyValue => continue,
+                Some(Err(e)) => return Err(MappingError::InvalidSdlMapping(e.position)),
+                Some(Ok(Token::AxisMapping {
+                    from, to, output, ..
+                })) => (to, output, usize::from(from) < axes.len()),
+                Some(Ok(Token::ButtonMapping { from, to, output })) => {
+                    (to, output, usize::from(from) < buttons.len())
+                }
+                Some(Ok(Token::HatMapping {
+                    hat,
+                    direction,
+                    to,
+                    output,
+                })) => (
+                    to,
+                    output,
+                    hat == 0 && matches!(direction, 0 | 1 | 2 | 4 | 8),
+                ),
+                Some(Ok(_)) => continue,
+            };
+
+            if !valid {
+                return Err(MappingError::InvalidSdlMapping(pos));
+            }
+
+            // Halves of axis can be mapped from different elements.
+            if matches!(output, AxisRange::Full)
+                && to != AxisOrBtn::Btn(Button::Unknown)
+                && !targets.insert(to)
+            {
+                return Err(MappingError::DuplicatedElement(to));
+            }
+        }
+
+        let mapping = Self::parse_sdl_mapping(line, buttons, axes).map_err(|e| {
+            MappingError::InvalidSdlMapping(match e {
+                ParseSdlMappingError::UnknownHatDirection(pos) => pos,
+                ParseSdlMappingError::ParseError(e) => e.position,
+            })
+        })?;
+        mapping.check_required()?;
+
+        Ok(mapping)
+    }
+
+    // Sticks have to be mapped with both axes.
+    fn check_required(&self) -> Result<(), MappingError> {
+        let sticks = [
+            (Axis::LeftStickX, Axis::LeftStickY),
+            (Axis::RightStickX, Axis::RightStickY),
+        ];
+
+        for (x, y) in sticks {
+            let (x, y) = (AxisOrBtn::Axis(x), AxisOrBtn::Axis(y));
+            match (self.map_rev(&x), self.map_rev(&y)) {
+                (Some(_), None) => return Err(MappingError::MissingElement(y)),
+                (None, Some(_)) => return Err(MappingError::MissingElement(x)),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn parse_sdl_mapping(
         line: &str,
         buttons: &[EvCode],
@@ -273,8 +401,13 @@ impl Mapping {
         let mut mapping = Mapping::new();
         let mut parser = Parser::new(line);
 
-        let mut uuid: Option<Uuid> = None;
-        while let Some(token) = parser.next_token() {
+        let mut uuid = Uuid::nil();
+        loop {
+            let pos = parser.position();
+            let token = match parser.next_token() {
+                Some(token) => token,
+                None => break,
+            };
             if let Err(ref e) = token {
                 if e.kind() == &ParserErrorKind::EmptyValue {
                     continue;
@@ -289,33 +422,45 @@ impl Mapping {
                         warn!("Mappings for different platform – {}", platform);
                     }
                 }
-                Token::Uuid(v) => uuid = Some(v),
-
+                Token::Uuid(v) => uuid = v,
                 Token::Name(name) => mapping.name = name.to_owned(),
-                Token::AxisMapping { from, to, .. } => {
+                // Mapping is used regardless of device name and hints.
+                Token::Crc(_) | Token::Hint(_) | Token::Field { .. } => (),
+                Token::AxisMapping {
+                    from,
+                    to,
+                    input,
+                    output,
+                    inverted,
+                } => {
                     let axis = axes.get(from as usize).cloned();
                     if let Some(axis) = axis {
                         mapping.mappings.insert(axis, to);
+                        if inverted {
+                            mapping.inverted.insert(axis);
+                        }
+                        if input != AxisRange::Full || output != AxisRange::Full {
+                            mapping.ranges.insert(axis, (input, output));
+                        }
                     } else {
                         warn!(
                             "SDL-mapping {} {}: Unknown axis a{}",
-                            uuid.unwrap(),
-                            mapping.name,
-                            from
+                            uuid, mapping.name, from
                         )
                     }
                 }
-                Token::ButtonMapping { from, to, .. } => {
+                Token::ButtonMapping { from, to, output } => {
                     let btn = buttons.get(from as usize).cloned();
 
                     if let Some(btn) = btn {
                         mapping.mappings.insert(btn, to);
+                        if output != AxisRange::Full {
+                            mapping.ranges.insert(btn, (AxisRange::Full, output));
+                        }
                     } else {
                         warn!(
                             "SDL-mapping {} {}: Unknown button b{}",
-                            uuid.unwrap(),
-                            mapping.name,
-                            from
+                            uuid, mapping.name, from
                         )
                     }
                 }
@@ -340,7 +485,7 @@ impl Mapping {
                             2 => (nec::AXIS_DPADX, nec::BTN_DPAD_RIGHT),
                             8 => (nec::AXIS_DPADX, nec::BTN_DPAD_LEFT),
                             0 => continue, // FIXME: I have no idea what 0 means here
-                            _ => return Err(ParseSdlMappingError::UnknownHatDirection),
+                            _ => return Err(ParseSdlMappingError::UnknownHatDirection(pos)),
                         };
 
                         if to.is_button() {
@@ -349,17 +494,21 @@ impl Mapping {
                                     mapping
                                         .mappings
                                         .insert(from_axis, AxisOrBtn::Axis(Axis::DPadX));
+                                    *mapping.hats.entry(from_axis).or_default() |= direction as u8;
                                 }
                                 AxisOrBtn::Btn(Button::DPadUp | Button::DPadDown) => {
                                     mapping
                                         .mappings
                                         .insert(from_axis, AxisOrBtn::Axis(Axis::DPadY));
+                                    *mapping.hats.entry(from_axis).or_default() |= direction as u8;
                                 }
                                 _ => (),
                             }
                             mapping.mappings.insert(from_btn, to);
+                            *mapping.hats.entry(from_btn).or_default() |= direction as u8;
                         } else {
                             mapping.mappings.insert(from_axis, to);
+                            *mapping.hats.entry(from_axis).or_default() |= direction as u8;
                         }
 
                         mapping.hats_mapped |= direction as u8;
@@ -368,31 +517,46 @@ impl Mapping {
             }
         }
 
+        mapping.index_rev();
         Ok(mapping)
     }
 
+    // Buttons can be also mapped from axes, like analog triggers.
     fn add_button(
         ident: &str,
         ev_code: EvCode,
         mapped_btn: Button,
-        buttons: &[EvCode],
+        (buttons, axes): (&[EvCode], &[EvCode]),
+        inverted: bool,
         sdl_mappings: &mut String,
         mappings: &mut FnvHashMap<EvCode, AxisOrBtn>,
     ) -> Result<(), MappingError> {
-        let n_btn = buttons
-            .iter()
-            .position(|&x| x == ev_code)
-            .ok_or(MappingError::InvalidCode(ev::Code(ev_code)))?;
-        let _ = write!(sdl_mappings, "{}:b{},", ident, n_btn);
-        mappings.insert(ev_code, AxisOrBtn::Btn(mapped_btn));
-        Ok(())
+        if let Some(n_btn) = buttons.iter().position(|&x| x == ev_code) {
+            if mappings.contains_key(&ev_code) {
+                return Err(MappingError::DuplicatedCode(ev::Code(ev_code)));
+            }
+            let _ = write!(sdl_mappings, "{}:b{},", ident, n_btn);
+            mappings.insert(ev_code, AxisOrBtn::Btn(mapped_btn));
+            Ok(())
+        } else {
+            Self::add_axis(
+                ident,
+                ev_code,
+                AxisOrBtn::Btn(mapped_btn),
+                axes,
+                inverted,
+                sdl_mappings,
+                mappings,
+            )
+        }
     }
 
     fn add_axis(
         ident: &str,
         ev_code: EvCode,
-        mapped_axis: Axis,
+        mapped_axis: AxisOrBtn,
         axes: &[EvCode],
+        inverted: bool,
         sdl_mappings: &mut String,
         mappings: &mut FnvHashMap<EvCode, AxisOrBtn>,
     ) -> Result<(), MappingError> {
@@ -400,8 +564,12 @@ impl Mapping {
             .iter()
             .position(|&x| x == ev_code)
             .ok_or(MappingError::InvalidCode(ev::Code(ev_code)))?;
-        let _ = write!(sdl_mappings, "{}:a{},", ident, n_axis);
-        mappings.insert(ev_code, AxisOrBtn::Axis(mapped_axis));
+        if mappings.contains_key(&ev_code) {
+            return Err(MappingError::DuplicatedCode(ev::Code(ev_code)));
+        }
+        let suffix = if inverted { "~" } else { "" };
+        let _ = write!(sdl_mappings, "{}:a{}{},", ident, n_axis, suffix);
+        mappings.insert(ev_code, mapped_axis);
         Ok(())
     }
 
@@ -413,8 +581,100 @@ impl Mapping {
         self.mappings.get(code).cloned()
     }
 
+    /// Returns all entries sorted by source code. `buttons` and `axes` are elements of gamepad,
+    /// their indices are used in `MappingEntry::sdl_element`.
+    pub fn entries(&self, buttons: &[EvCode], axes: &[EvCode]) -> Vec<MappingEntry> {
+        let mut entries: Vec<_> = self
+            .mappings
+            .iter()
+            .map(|(&code, &target)| {
+                let inverted = self.inverted.contains(&code);
+                let (input, output) = self
+                    .ranges
+                    .get(&code)
+                    .copied()
+                    .unwrap_or((AxisRange::Full, AxisRange::Full));
+                let hat = self.hats.get(&code).copied();
+
+                let sdl_element = match hat {
+                    // D-pad axis is mapped from two directions, which SDL can't express.
+                    Some(dir) if dir.count_ones() == 1 => Some(format!("h0.{}", dir)),
+                    Some(_) => None,
+                    None => match buttons.iter().position(|&c| c == code) {
+                        Some(i) => Some(format!("b{}", i)),
+                        None => axes.iter().position(|&c| c == code).map(|i| {
+                            let prefix = match input {
+                                AxisRange::LowerHalf => "-",
+                                AxisRange::UpperHalf => "+",
+                                AxisRange::Full => "",
+                            };
+                            let suffix = if inverted { "~" } else { "" };
+                            format!("{}a{}{}", prefix, i, suffix)
+                        }),
+                    },
+                };
+
+                MappingEntry {
+                    source: ev::Code(code),
+                    target,
+                    inverted,
+                    input,
+                    output,
+                    hat,
+                    sdl_element,
+                }
+            })
+            .collect();
+        entries.sort_by_key(|e| e.source.0);
+
+        entries
+    }
+
+    /// Returns `true` if values of axis have to be negated to match gilrs' axis orientation. If
+    /// axis is mapped to button, button is pressed when axis is at its minimum.
+    pub fn is_inverted(&self, code: &EvCode) -> bool {
+        self.inverted.contains(code)
+    }
+
     pub fn map_rev(&self, el: &AxisOrBtn) -> Option<EvCode> {
-        self.mappings.iter().find(|x| x.1 == el).map(|x| *x.0)
+        match *el {
+            AxisOrBtn::Btn(btn) => self.buttons_rev.get(btn as usize).copied().flatten(),
+            AxisOrBtn::Axis(axis) => self.axes_rev.get(axis as usize).copied().flatten(),
+        }
+    }
+
+    /// Maps `code` to `el` for each of `overrides`, in order. Element is no longer reported for
+    /// code it was previously mapped to and values of overridden axes are not inverted.
+    pub fn apply_overrides(&mut self, overrides: &[(ev::Code, AxisOrBtn)]) {
+        if overrides.is_empty() {
+            return;
+        }
+
+        for &(ev::Code(code), el) in overrides {
+            self.mappings.retain(|_, &mut old| old != el);
+            self.mappings.insert(code, el);
+            self.inverted.remove(&code);
+            self.ranges.remove(&code);
+            self.hats.remove(&code);
+        }
+        self.index_rev();
+    }
+
+    /// Rebuilds reverse mappings used by `map_rev()`. Has to be called after `mappings` change.
+    fn index_rev(&mut self) {
+        self.buttons_rev = Default::default();
+        self.axes_rev = Default::default();
+
+        for (&code, &el) in &self.mappings {
+            let slot = match el {
+                AxisOrBtn::Btn(btn) => self.buttons_rev.get_mut(btn as usize),
+                AxisOrBtn::Axis(axis) => self.axes_rev.get_mut(axis as usize),
+            };
+
+            if let Some(slot @ None) = slot {
+                *slot = Some(code);
+            }
+        }
     }
 
     pub fn is_default(&self) -> bool {
@@ -430,7 +690,8 @@ impl Mapping {
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum ParseSdlMappingError {
-    UnknownHatDirection,
+    // Offset of entry with unknown direction.
+    UnknownHatDirection(usize),
     ParseError(ParserError),
 }
 
@@ -453,17 +714,39 @@ impl Error for ParseSdlMappingError {
 impl Display for ParseSdlMappingError {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
         match self {
-            ParseSdlMappingError::UnknownHatDirection => {
-                fmt.write_str("hat direction wasn't 1, 2, 4 or 8")
+            ParseSdlMappingError::UnknownHatDirection(pos) => {
+                write!(fmt, "hat direction wasn't 1, 2, 4 or 8 at {}", pos)
             }
-            ParseSdlMappingError::ParseError(_) => fmt.write_str("parsing error"),
+            ParseSdlMappingError::ParseError(err) => write!(fmt, "parsing error: {}", err),
         }
     }
 }
 
+/// Source of SDL mapping, ordered from lowest to highest precedence.
+///
+/// Mapping from a layer is never replaced by a mapping for the same gamepad from a lower layer,
+/// regardless of order in which they were added.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum MappingLayer {
+    /// Mappings included in gilrs, from
+    /// [SDL_GameControllerDB](https://github.com/gabomdq/SDL_GameControllerDB).
+    Included,
+    /// Mappings from `SDL_GAMECONTROLLERCONFIG`, `GILRS_GAMECONTROLLERCONFIG` environment
+    /// variables and file named by `SDL_GAMECONTROLLERCONFIG_FILE`.
+    Environment,
+    /// Mappings added with
+    /// [`GilrsBuilder::add_mappings()`](struct.GilrsBuilder.html#method.add_mappings), for example
+    /// loaded by the application from user's config file, or with
+    /// [`GilrsBuilder::add_mappings_file()`](struct.GilrsBuilder.html#method.add_mappings_file).
+    User,
+    /// Mapping set with [`Gilrs::set_mapping()`](struct.Gilrs.html#method.set_mapping).
+    Runtime,
+}
+
 #[derive(Debug)]
 pub struct MappingDb {
-    mappings: HashMap<Uuid, String>,
+    mappings: HashMap<Uuid, (String, MappingLayer)>,
 }
 
 impl MappingDb {
@@ -474,20 +757,44 @@ impl MappingDb {
     }
 
     pub fn add_included_mappings(&mut self) {
-        self.insert(include_str!(concat!(
-            env!("OUT_DIR"),
-            PATH_SEPARATOR!(),
-            "gamecontrollerdb.txt"
-        )));
+        self.insert(
+            include_str!(concat!(
+                env!("OUT_DIR"),
+                PATH_SEPARATOR!(),
+                "gamecontrollerdb.txt"
+            )),
+            MappingLayer::Included,
+        );
+    }
+
+    /// Adds mappings from, in order, file named by `SDL_GAMECONTROLLERCONFIG_FILE`,
+    /// `SDL_GAMECONTROLLERCONFIG` and `GILRS_GAMECONTROLLERCONFIG` environment variables. Later
+    /// sources override earlier ones.
+    ///
+    /// Values of variables are read with `var`, usually `std::env::var_os`.
+    pub fn add_env_mappings<F>(&mut self, var: F)
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        if let Some(path) = var("SDL_GAMECONTROLLERCONFIG_FILE") {
+            self.insert_file(Path::new(&path), MappingLayer::Environment);
+        }
+
+        for name in ["SDL_GAMECONTROLLERCONFIG", "GILRS_GAMECONTROLLERCONFIG"] {
+            if let Some(mappings) = var(name).and_then(|v| v.into_string().ok()) {
+                self.insert(&mappings, MappingLayer::Environment);
+            }
+        }
     }
 
-    pub fn add_env_mappings(&mut self) {
-        if let Ok(mapping) = env::var("SDL_GAMECONTROLLERCONFIG") {
-            self.insert(&mapping);
+    pub fn insert_file(&mut self, path: &Path, layer: MappingLayer) {
+        match fs::read_to_string(path) {
+            Ok(mappings) => self.insert(&mappings, layer),
+            Err(e) => warn!("Failed to read mappings from {:?}: {}", path, e),
         }
     }
 
-    pub fn insert(&mut self, s: &str) {
+    pub fn insert(&mut self, s: &str, layer: MappingLayer) {
         for mapping in s.lines() {
             let pat = "platform:";
             if let Some(offset) = mapping.find(pat).map(|o| o + pat.len()) {
@@ -499,16 +806,31 @@ impl MappingDb {
                 }
             }
 
-            mapping
-                .split(',')
-                .next()
-                .and_then(|s| Uuid::parse_str(s).ok())
-                .and_then(|uuid| self.mappings.insert(uuid, mapping.to_owned()));
+            let uuid = match mapping.split(',').next().map(Uuid::parse_str) {
+                Some(Ok(uuid)) => uuid,
+                _ => continue,
+            };
+
+            match self.mappings.get(&uuid) {
+                Some((_, old_layer)) if *old_layer > layer => {
+                    trace!(
+                        "Ignoring {:?} mapping for {}, {:?} mapping takes precedence",
+                        layer,
+                        uuid,
+                        old_layer
+                    );
+                }
+                _ => {
+                    self.mappings.insert(uuid, (mapping.to_owned(), layer));
+                }
+            }
         }
     }
 
-    pub fn get(&self, uuid: Uuid) -> Option<&str> {
-        self.mappings.get(&uuid).map(String::as_ref)
+    pub fn get(&self, uuid: Uuid) -> Option<(&str, MappingLayer)> {
+        self.mappings
+            .get(&uuid)
+            .map(|(mapping, layer)| (mapping.as_str(), *layer))
     }
 
     pub fn len(&self) -> usize {
@@ -516,6 +838,114 @@ impl MappingDb {
     }
 }
 
+/// Mappings added with `GilrsBuilder::add_mappings()` or `GilrsBuilder::add_mappings_file()`.
+#[derive(Debug, Clone)]
+pub enum UserMappings {
+    Str(String),
+    File(PathBuf),
+}
+
+/// Sources of mappings, kept to rebuild `MappingDb` when watched files change.
+#[derive(Debug, Clone)]
+pub struct MappingSources {
+    pub included: bool,
+    pub env: bool,
+    pub user: Vec<UserMappings>,
+}
+
+impl MappingSources {
+    pub fn build(&self) -> MappingDb {
+        let mut db = MappingDb::new();
+        if self.included {
+            db.add_included_mappings();
+        }
+
+        if self.env {
+            db.add_env_mappings(|name| env::var_os(name));
+        }
+
+        for mappings in &self.user {
+            match mappings {
+                UserMappings::Str(s) => db.insert(s, MappingLayer::User),
+                UserMappings::File(path) => db.insert_file(path, MappingLayer::User),
+            }
+        }
+
+        db
+    }
+
+    /// Returns files that mappings are read from.
+    fn files(&self) -> Vec<PathBuf> {
+        let env_file = env::var_os("SDL_GAMECONTROLLERCONFIG_FILE")
+            .filter(|_| self.env)
+            .map(PathBuf::from);
+        let user_files = self.user.iter().filter_map(|mappings| match mappings {
+            UserMappings::File(path) => Some(path.clone()),
+            UserMappings::Str(_) => None,
+        });
+
+        env_file.into_iter().chain(user_files).collect()
+    }
+}
+
+/// Watches mapping files for changes by comparing their modification time.
+#[derive(Debug)]
+pub struct MappingWatch {
+    sources: MappingSources,
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    last_check: SystemTime,
+}
+
+impl MappingWatch {
+    // Checking metadata is cheap, but there is no need to do it on every event.
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new(sources: MappingSources, now: SystemTime) -> Self {
+        let files = sources
+            .files()
+            .into_iter()
+            .map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            })
+            .collect();
+
+        MappingWatch {
+            sources,
+            files,
+            last_check: now,
+        }
+    }
+
+    /// Returns rebuilt mapping database if any of watched files changed since last check.
+    pub fn poll(&mut self, now: SystemTime) -> Option<MappingDb> {
+        if now.duration_since(self.last_check).unwrap_or_default() < Self::INTERVAL {
+            return None;
+        }
+        self.last_check = now;
+
+        let mut changed = false;
+        for (path, last_modified) in &mut self.files {
+            let modified = modified(path);
+            if modified != *last_modified {
+                debug!("Mapping file {:?} changed", path);
+                *last_modified = modified;
+                changed = true;
+            }
+        }
+
+        if changed {
+            Some(self.sources.build())
+        } else {
+            None
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
 /// Stores data used to map gamepad buttons and axes.
 ///
 /// After you add all mappings, use
@@ -528,6 +958,7 @@ impl MappingDb {
 pub struct MappingData {
     buttons: VecMap<EvCode>,
     axes: VecMap<EvCode>,
+    inverted: FnvHashSet<EvCode>,
 }
 
 impl MappingData {
@@ -536,6 +967,7 @@ impl MappingData {
         MappingData {
             buttons: VecMap::with_capacity(18),
             axes: VecMap::with_capacity(11),
+            inverted: FnvHashSet::default(),
         }
     }
 
@@ -568,6 +1000,93 @@ impl MappingData {
     pub fn remove_axis(&mut self, idx: Axis) -> Option<ev::Code> {
         self.axes.remove(idx as usize).map(ev::Code)
     }
+
+    /// Sets whether values of axis `code` are negated (`~` in SDL mapping). Axes mapped to buttons,
+    /// like analog triggers, can be inverted too. Inversion of buttons is ignored.
+    pub fn set_inverted(&mut self, code: ev::Code, inverted: bool) {
+        if inverted {
+            self.inverted.insert(code.0);
+        } else {
+            self.inverted.remove(&code.0);
+        }
+    }
+
+    /// Returns `true` if values of axis `code` are negated.
+    pub fn is_inverted(&self, code: ev::Code) -> bool {
+        self.inverted.contains(&code.0)
+    }
+}
+
+impl MappingData {
+    /// Creates `MappingData` from entries returned by
+    /// [`Gamepad::mapping_entries()`](crate::Gamepad::mapping_entries), so current mapping of
+    /// gamepad can be edited and applied with [`Gilrs::set_mapping()`](crate::Gilrs::set_mapping).
+    ///
+    /// Entries that can't be represented by `MappingData` are skipped: mappings to
+    /// `Axis::DPadX`, `Axis::DPadY` and unknown elements, entries mapped from hat or from half of
+    /// axis or to half of axis, and axes whose source isn't axis of gamepad.
+    pub fn from_entries<'a, I: IntoIterator<Item = &'a MappingEntry>>(entries: I) -> Self {
+        let mut data = MappingData::new();
+        for entry in entries {
+            if entry.hat.is_some()
+                || entry.input != AxisRange::Full
+                || entry.output != AxisRange::Full
+            {
+                continue;
+            }
+
+            let kind = entry.sdl_element.as_deref().and_then(|e| e.chars().next());
+            match (entry.target, kind) {
+                (AxisOrBtn::Btn(Button::Unknown), _) => continue,
+                (AxisOrBtn::Btn(btn), Some('a' | 'b')) => {
+                    data.insert_btn(entry.source, btn);
+                }
+                (AxisOrBtn::Axis(Axis::DPadX | Axis::DPadY | Axis::Unknown), _) => continue,
+                (AxisOrBtn::Axis(axis), Some('a')) => {
+                    data.insert_axis(entry.source, axis);
+                }
+                _ => continue,
+            }
+            data.set_inverted(entry.source, entry.inverted);
+        }
+
+        data
+    }
+}
+
+/// Single entry of gamepad's mapping. See
+/// [`Gamepad::mapping_entries()`](crate::Gamepad::mapping_entries).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MappingEntry {
+    /// Element reported by device.
+    pub source: ev::Code,
+    /// Button or axis to which `source` is mapped.
+    pub target: AxisOrBtn,
+    /// Whether values of axis are negated (`~` suffix in SDL mapping).
+    pub inverted: bool,
+    /// Part of `source` axis that is used, for example `LowerHalf` for `-a2` in SDL mapping.
+    pub input: AxisRange,
+    /// Part of `target` axis that is set, for example `UpperHalf` for `+leftx` in SDL mapping.
+    pub output: AxisRange,
+    /// Directions of first hat that `source` is mapped from as SDL bitmask (1 – up, 2 – right,
+    /// 4 – down, 8 – left), or `None` if it isn't mapped from hat.
+    pub hat: Option<u8>,
+    /// Element in SDL mapping format, like `b3`, `-a2`, `a1~` or `h0.4`, or `None` if `source`
+    /// isn't one of gamepad's buttons or axes or is mapped from more than one hat direction. Can
+    /// be used to compare mapping with entries of SDL game controller database.
+    pub sdl_element: Option<String>,
+}
+
+/// Runs all SDL mapping parsers on `line`. Entry point of fuzz target in `fuzz/`.
+#[cfg(fuzzing)]
+pub fn fuzz_sdl_mapping(line: &str) {
+    let buttons = [nec::BTN_SOUTH, nec::BTN_EAST, nec::BTN_NORTH, nec::BTN_WEST];
+    let axes = [nec::AXIS_LSTICKX, nec::AXIS_LSTICKY, nec::AXIS_DPADX];
+
+    let _ = Mapping::parse_sdl_mapping(line, &buttons, &axes);
+    let _ = Mapping::from_sdl(line, &buttons, &axes);
+    MappingDb::new().insert(line, MappingLayer::User);
 }
 
 /// The error type for functions related to gamepad mapping.
@@ -583,16 +1102,27 @@ pub enum MappingError {
     /// Gamepad is not connected.
     NotConnected,
     /// Same gamepad element is referenced by axis and button.
+    #[deprecated(note = "`DuplicatedCode` is returned instead")]
     DuplicatedEntry,
     /// `Mapping` with `Button::Unknown` or `Axis::Unknown`.
     UnknownElement,
     /// `Mapping` have button or axis that are not present in SDL2.
     NotSdl2Compatible,
+    /// Same `Code` is mapped to more than one button or axis.
+    DuplicatedCode(ev::Code),
+    /// SDL mapping maps more than one element to same button or axis.
+    DuplicatedElement(AxisOrBtn),
+    /// Only one axis of stick is mapped, mapping has to contain the other one too.
+    MissingElement(AxisOrBtn),
+    /// SDL mapping is invalid at given byte offset: it can't be parsed, references button or axis
+    /// index that gamepad doesn't have or uses hat other than first one.
+    InvalidSdlMapping(usize),
 }
 
 impl Error for MappingError {}
 
 impl Display for MappingError {
+    #[allow(deprecated)]
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         let sbuf;
         let s = match self {
@@ -610,6 +1140,22 @@ impl Display for MappingError {
             }
             MappingError::UnknownElement => "Button::Unknown and Axis::Unknown are not allowed",
             MappingError::NotSdl2Compatible => "one of buttons or axes is not compatible with SDL2",
+            MappingError::DuplicatedCode(code) => {
+                sbuf = format!("{} is mapped to more than one element", code);
+                sbuf.as_ref()
+            }
+            MappingError::DuplicatedElement(el) => {
+                sbuf = format!("more than one element is mapped to {:?}", el);
+                sbuf.as_ref()
+            }
+            MappingError::MissingElement(el) => {
+                sbuf = format!("stick is mapped without {:?}", el);
+                sbuf.as_ref()
+            }
+            MappingError::InvalidSdlMapping(pos) => {
+                sbuf = format!("SDL mapping is invalid at offset {}", pos);
+                sbuf.as_ref()
+            }
         };
 
         f.write_str(s)
@@ -668,6 +1214,113 @@ mod tests {
         Mapping::parse_sdl_mapping(TEST_STR, &BUTTONS, &AXES).unwrap();
     }
 
+    #[test]
+    fn inverted_axis() {
+        let line = "03000000260900008888000000010001,Inverted,leftx:a0,lefty:a1~,";
+        let mapping = Mapping::parse_sdl_mapping(line, &BUTTONS, &AXES).unwrap();
+
+        assert!(!mapping.is_inverted(&AXES[0]));
+        assert!(mapping.is_inverted(&AXES[1]));
+    }
+
+    #[test]
+    fn map_rev() {
+        let line = "03000000260900008888000000010001,Reverse,a:b1,leftx:a0,";
+        let mapping = Mapping::parse_sdl_mapping(line, &BUTTONS, &AXES).unwrap();
+
+        assert_eq!(
+            mapping.map_rev(&AxisOrBtn::Btn(Button::South)),
+            Some(BUTTONS[1])
+        );
+        assert_eq!(
+            mapping.map_rev(&AxisOrBtn::Axis(Axis::LeftStickX)),
+            Some(AXES[0])
+        );
+        assert_eq!(mapping.map_rev(&AxisOrBtn::Btn(Button::East)), None);
+        assert_eq!(mapping.map_rev(&AxisOrBtn::Axis(Axis::Unknown)), None);
+    }
+
+    #[test]
+    fn apply_overrides() {
+        let line = "03000000260900008888000000010001,Overrides,a:b0,b:b1,leftx:a0,lefty:a1~,";
+        let mut mapping = Mapping::parse_sdl_mapping(line, &BUTTONS, &AXES).unwrap();
+
+        mapping.apply_overrides(&[
+            (ev::Code(BUTTONS[1]), AxisOrBtn::Btn(Button::South)),
+            (ev::Code(AXES[1]), AxisOrBtn::Axis(Axis::RightStickY)),
+        ]);
+        assert_eq!(mapping.map(&BUTTONS[0]), None);
+        assert_eq!(
+            mapping.map(&BUTTONS[1]),
+            Some(AxisOrBtn::Btn(Button::South))
+        );
+        assert_eq!(
+            mapping.map_rev(&AxisOrBtn::Btn(Button::South)),
+            Some(BUTTONS[1])
+        );
+        assert_eq!(mapping.map_rev(&AxisOrBtn::Btn(Button::East)), None);
+        assert_eq!(
+            mapping.map_rev(&AxisOrBtn::Axis(Axis::RightStickY)),
+            Some(AXES[1])
+        );
+        assert_eq!(mapping.map_rev(&AxisOrBtn::Axis(Axis::LeftStickY)), None);
+        assert!(!mapping.is_inverted(&AXES[1]));
+    }
+
+    #[test]
+    fn entries() {
+        let line = "03000000260900008888000000010001,Entries,a:b1,leftx:a0,lefty:a1~,\
+                    lefttrigger:a4,+rightx:-a3,dpup:h0.1,dpdown:h0.4,";
+        let mapping = Mapping::parse_sdl_mapping(line, &BUTTONS, &AXES).unwrap();
+
+        let entries = mapping.entries(&BUTTONS, &AXES);
+        let sdl: Vec<_> = entries
+            .iter()
+            .map(|e| (e.target, e.sdl_element.as_deref(), e.hat))
+            .collect();
+        let btn = AxisOrBtn::Btn;
+        let axis = AxisOrBtn::Axis;
+        assert_eq!(
+            sdl,
+            [
+                (btn(Button::South), Some("b1"), None),
+                (btn(Button::DPadUp), Some("h0.1"), Some(1)),
+                (btn(Button::DPadDown), Some("h0.4"), Some(4)),
+                (axis(Axis::LeftStickX), Some("a0"), None),
+                (axis(Axis::LeftStickY), Some("a1~"), None),
+                (axis(Axis::RightStickX), Some("-a3"), None),
+                (btn(Button::LeftTrigger2), Some("a4"), None),
+                (axis(Axis::DPadY), None, Some(5)),
+            ]
+        );
+        let right_x = &entries[5];
+        assert_eq!(right_x.input, AxisRange::LowerHalf);
+        assert_eq!(right_x.output, AxisRange::UpperHalf);
+        assert!(entries[4].inverted);
+
+        // Half axes and hats can't be represented by `MappingData`.
+        let data = MappingData::from_entries(&entries);
+        assert_eq!(data.button(Button::South), Some(ev::Code(BUTTONS[1])));
+        assert_eq!(data.axis(Axis::LeftStickY), Some(ev::Code(AXES[1])));
+        assert!(data.is_inverted(ev::Code(AXES[1])));
+        assert_eq!(data.button(Button::LeftTrigger2), Some(ev::Code(AXES[4])));
+        assert_eq!(data.axis(Axis::RightStickX), None);
+        assert_eq!(data.axis(Axis::DPadY), None);
+        assert_eq!(data.button(Button::DPadUp), None);
+
+        let (mapping, sdl) =
+            Mapping::from_data(&data, &BUTTONS, &AXES, "Entries", Uuid::nil()).unwrap();
+        assert_eq!(
+            sdl,
+            "00000000000000000000000000000000,Entries,a:b1,lefttrigger:a4,leftx:a0,lefty:a1~,"
+        );
+        assert!(mapping.is_inverted(&AXES[1]));
+        assert_eq!(
+            mapping.map(&AXES[4]),
+            Some(AxisOrBtn::Btn(Button::LeftTrigger2))
+        );
+    }
+
     #[test]
     fn from_data() {
         let uuid = Uuid::nil();
@@ -713,6 +1366,65 @@ mod tests {
         assert_eq!(Err(MappingError::UnknownElement), incorrect_mappings);
     }
 
+    #[test]
+    fn from_data_validation() {
+        let uuid = Uuid::nil();
+        let name = "Best Gamepad";
+        let axes = AXES.iter().cloned().map(ev::Code).collect::<Vec<_>>();
+
+        let mut data = MappingData::new();
+        data.insert_axis(axes[0], Axis::LeftStickX);
+        assert_eq!(
+            Mapping::from_data(&data, &BUTTONS, &AXES, name, uuid).map(|_| ()),
+            Err(MappingError::MissingElement(AxisOrBtn::Axis(
+                Axis::LeftStickY
+            )))
+        );
+
+        data.insert_axis(axes[0], Axis::LeftStickY);
+        assert_eq!(
+            Mapping::from_data(&data, &BUTTONS, &AXES, name, uuid).map(|_| ()),
+            Err(MappingError::DuplicatedCode(axes[0]))
+        );
+    }
+
+    #[test]
+    fn from_sdl() {
+        let mapping = Mapping::from_sdl(TEST_STR, &BUTTONS, &AXES).unwrap();
+        assert_eq!(
+            mapping,
+            Mapping::parse_sdl_mapping(TEST_STR, &BUTTONS, &AXES).unwrap()
+        );
+
+        let line = "03000000260900008888000000010001,Gamepad,a:b0,b:b99,";
+        assert_eq!(
+            Mapping::from_sdl(line, &BUTTONS, &AXES),
+            Err(MappingError::InvalidSdlMapping(46))
+        );
+
+        let line = "03000000260900008888000000010001,Gamepad,a:b0,a:b1,";
+        assert_eq!(
+            Mapping::from_sdl(line, &BUTTONS, &AXES),
+            Err(MappingError::DuplicatedElement(AxisOrBtn::Btn(
+                Button::South
+            )))
+        );
+
+        let line = "03000000260900008888000000010001,Gamepad,a:b0,rightx:a2,";
+        assert_eq!(
+            Mapping::from_sdl(line, &BUTTONS, &AXES),
+            Err(MappingError::MissingElement(AxisOrBtn::Axis(
+                Axis::RightStickY
+            )))
+        );
+
+        let line = "03000000260900008888000000010001,Gamepad,a:x0,";
+        assert_eq!(
+            Mapping::from_sdl(line, &BUTTONS, &AXES),
+            Err(MappingError::InvalidSdlMapping(43))
+        );
+    }
+
     #[test]
     fn with_mappings() {
         let mappings = format!(
@@ -721,11 +1433,97 @@ mod tests {
         );
         let mut db = MappingDb::new();
         db.add_included_mappings();
-        db.insert(&mappings);
+        db.insert(&mappings, MappingLayer::User);
 
         assert_eq!(
-            Some(TEST_STR),
+            Some((TEST_STR, MappingLayer::User)),
             db.get(Uuid::parse_str("03000000260900008888000000010001").unwrap())
         );
     }
+
+    #[test]
+    fn env_mappings() {
+        let uuid = Uuid::parse_str("03000000260900008888000000010001").unwrap();
+        let gilrs = TEST_STR.replace("GameCube", "Gilrs");
+        let sdl = format!("invalid\n{}", TEST_STR);
+        let file = TEST_STR.replace("GameCube", "File");
+        let path = env::temp_dir().join(format!("gilrs-env-{}.txt", std::process::id()));
+        fs::write(&path, &file).unwrap();
+
+        fn vars<'a>(
+            file: &'a Path,
+            sdl: Option<&'a str>,
+            gilrs: Option<&'a str>,
+        ) -> impl Fn(&str) -> Option<OsString> + 'a {
+            move |name| match name {
+                "SDL_GAMECONTROLLERCONFIG_FILE" => Some(file.into()),
+                "SDL_GAMECONTROLLERCONFIG" => sdl.map(OsString::from),
+                "GILRS_GAMECONTROLLERCONFIG" => gilrs.map(OsString::from),
+                _ => None,
+            }
+        }
+
+        let mut db = MappingDb::new();
+        db.add_env_mappings(vars(&path, None, None));
+        assert_eq!(
+            Some((file.as_str(), MappingLayer::Environment)),
+            db.get(uuid)
+        );
+
+        let mut db = MappingDb::new();
+        db.add_env_mappings(vars(&path, Some(&sdl), None));
+        assert_eq!(Some((TEST_STR, MappingLayer::Environment)), db.get(uuid));
+
+        let mut db = MappingDb::new();
+        db.add_env_mappings(vars(&path, Some(&sdl), Some(&gilrs)));
+        assert_eq!(
+            Some((gilrs.as_str(), MappingLayer::Environment)),
+            db.get(uuid)
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn mapping_layers() {
+        let uuid = Uuid::parse_str("03000000260900008888000000010001").unwrap();
+        let user = TEST_STR.replace("GameCube", "User");
+        let env = TEST_STR.replace("GameCube", "Env");
+
+        let mut db = MappingDb::new();
+        db.insert(&user, MappingLayer::User);
+        db.insert(&env, MappingLayer::Environment);
+        db.insert(TEST_STR, MappingLayer::Included);
+        assert_eq!(Some((user.as_str(), MappingLayer::User)), db.get(uuid));
+
+        db.insert(&env, MappingLayer::User);
+        assert_eq!(Some((env.as_str(), MappingLayer::User)), db.get(uuid));
+    }
+
+    #[test]
+    fn watch_file() {
+        let uuid = Uuid::parse_str("03000000260900008888000000010001").unwrap();
+        let path = env::temp_dir().join(format!("gilrs-watch-{}.txt", std::process::id()));
+        fs::write(&path, TEST_STR).unwrap();
+
+        let sources = MappingSources {
+            included: false,
+            env: false,
+            user: vec![UserMappings::File(path.clone())],
+        };
+        let now = SystemTime::UNIX_EPOCH;
+        let mut watch = MappingWatch::new(sources, now);
+        assert!(watch.poll(now + MappingWatch::INTERVAL).is_none());
+
+        let changed = TEST_STR.replace("GameCube", "Changed");
+        fs::write(&path, &changed).unwrap();
+        // Make sure that modification time differs even on file systems with coarse timestamps.
+        watch.files[0].1 = None;
+        // Too early.
+        assert!(watch.poll(now + MappingWatch::INTERVAL).is_none());
+
+        let db = watch.poll(now + MappingWatch::INTERVAL * 2).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Some((changed.as_str(), MappingLayer::User)), db.get(uuid));
+    }
 }
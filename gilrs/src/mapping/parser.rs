@@ -94,6 +94,11 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Returns position of the next token.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
     pub fn next_token(&mut self) -> Option<Result<Token<'_>, Error>> {
         if self.pos >= self.data.len() {
             None
@@ -148,130 +153,102 @@ impl<'a> Parser<'a> {
         let pos = self.pos;
         self.pos = next_comma + 1;
 
-        let mut split = pair.split(':');
-        let key = split
-            .next()
-            .ok_or_else(|| Error::new(ErrorKind::InvalidKeyValPair, pos))?;
-        let value = split
-            .next()
+        // Hints can contain colons, so only first one separates key from value.
+        let (key, value) = pair
+            .split_once(':')
             .ok_or_else(|| Error::new(ErrorKind::InvalidKeyValPair, pos))?;
-
-        if split.next().is_some() {
-            return Err(Error::new(ErrorKind::InvalidKeyValPair, pos));
-        }
+        let value_pos = pos + key.len() + 1;
 
         if value.is_empty() {
-            return Err(Error::new(ErrorKind::EmptyValue, pos));
+            return Err(Error::new(ErrorKind::EmptyValue, value_pos));
         }
 
-        if key == "platform" {
-            return Ok(Token::Platform(value));
+        match key {
+            "platform" => return Ok(Token::Platform(value)),
+            "hint" => return Ok(Token::Hint(value)),
+            "crc" => {
+                return u16::from_str_radix(value, 16)
+                    .map(Token::Crc)
+                    .map_err(|_| Error::new(ErrorKind::InvalidValue, value_pos))
+            }
+            "sdk>=" | "sdk<=" | "face" | "type" => return Ok(Token::Field { key, value }),
+            _ => (),
         }
 
-        let mut input = AxisRange::Full;
-        let mut output = AxisRange::Full;
-        let mut inverted = false;
-        let mut is_axis = false;
+        if value.contains(':') {
+            return Err(Error::new(ErrorKind::InvalidKeyValPair, pos));
+        }
 
-        let key = match key.get(0..1) {
-            Some("+") => {
-                output = AxisRange::UpperHalf;
-                &key[1..]
-            }
-            Some("-") => {
-                output = AxisRange::LowerHalf;
-                &key[1..]
-            }
-            _ => key,
+        let (output, key) = match key.as_bytes().first() {
+            Some(b'+') => (AxisRange::UpperHalf, &key[1..]),
+            Some(b'-') => (AxisRange::LowerHalf, &key[1..]),
+            _ => (AxisRange::Full, key),
         };
 
-        let from = match value.get(0..1) {
-            Some("+") if value.get(1..2) == Some("a") => {
-                is_axis = true;
-                input = AxisRange::UpperHalf;
-
-                if value.get((value.len() - 1)..) == Some("~") {
-                    inverted = true;
-
-                    &value[2..(value.len() - 1)]
-                } else {
-                    &value[2..]
-                }
-            }
-            Some("-") if value.get(1..2) == Some("a") => {
-                is_axis = true;
-                input = AxisRange::LowerHalf;
-
-                if value.get((value.len() - 1)..) == Some("~") {
-                    inverted = true;
+        let (input, value) = match value.as_bytes().first() {
+            Some(b'+') => (Some(AxisRange::UpperHalf), &value[1..]),
+            Some(b'-') => (Some(AxisRange::LowerHalf), &value[1..]),
+            _ => (None, value),
+        };
+        // Position of element kind (`a`, `b` or `h`) in value.
+        let kind_pos = value_pos + input.is_some() as usize;
+
+        let idx = AXES_SDL.binary_search(&key);
+
+        match value.as_bytes().first() {
+            Some(b'a') => {
+                let (from, inverted) = match value[1..].strip_suffix('~') {
+                    Some(from) => (from, true),
+                    None => (&value[1..], false),
+                };
+                let from = from
+                    .parse()
+                    .map_err(|_| Error::new(ErrorKind::InvalidValue, kind_pos + 1))?;
+                let idx = idx.map_err(|_| Error::new(ErrorKind::UnknownAxis, pos))?;
 
-                    &value[2..(value.len() - 1)]
-                } else {
-                    &value[2..]
-                }
+                Ok(Token::AxisMapping {
+                    from,
+                    to: AXES[idx],
+                    input: input.unwrap_or(AxisRange::Full),
+                    output,
+                    inverted,
+                })
             }
-            Some("a") => {
-                is_axis = true;
-
-                if value.get((value.len() - 1)..) == Some("~") {
-                    inverted = true;
+            Some(b'b') if input.is_none() => {
+                let from = value[1..]
+                    .parse()
+                    .map_err(|_| Error::new(ErrorKind::InvalidValue, kind_pos + 1))?;
+                let idx = idx.map_err(|_| Error::new(ErrorKind::UnknownButton, pos))?;
 
-                    &value[1..(value.len() - 1)]
-                } else {
-                    &value[1..]
-                }
+                Ok(Token::ButtonMapping {
+                    from,
+                    to: AXES[idx],
+                    output,
+                })
             }
-            Some("b") => &value[1..],
-            Some("h") => {
-                let dot_idx = value
-                    .find('.')
-                    .ok_or_else(|| Error::new(ErrorKind::InvalidValue, pos))?;
-                let hat = value[1..dot_idx]
+            Some(b'h') if input.is_none() => {
+                let (hat, direction) = value[1..]
+                    .split_once('.')
+                    .ok_or_else(|| Error::new(ErrorKind::InvalidValue, kind_pos))?;
+                let hat = hat
                     .parse()
-                    .map_err(|_| Error::new(ErrorKind::InvalidValue, pos + 1))?;
-                let direction = value
-                    .get((dot_idx + 1)..)
-                    .and_then(|s| s.parse().ok())
-                    .ok_or_else(|| Error::new(ErrorKind::InvalidValue, pos + dot_idx + 1))?;
-
-                let idx = AXES_SDL
-                    .binary_search(&key)
-                    .map_err(|_| Error::new(ErrorKind::UnknownButton, pos))?;
-
-                return Ok(Token::HatMapping {
+                    .map_err(|_| Error::new(ErrorKind::InvalidValue, kind_pos + 1))?;
+                let direction = direction.parse().map_err(|_| {
+                    Error::new(
+                        ErrorKind::InvalidValue,
+                        kind_pos + value.len() - direction.len(),
+                    )
+                })?;
+                let idx = idx.map_err(|_| Error::new(ErrorKind::UnknownButton, pos))?;
+
+                Ok(Token::HatMapping {
                     hat,
                     direction,
                     to: AXES[idx],
                     output,
-                });
+                })
             }
-            _ => return Err(Error::new(ErrorKind::InvalidValue, pos)),
-        }
-        .parse::<u16>()
-        .map_err(|_| Error::new(ErrorKind::InvalidValue, pos))?;
-
-        if is_axis {
-            let idx = AXES_SDL
-                .binary_search(&key)
-                .map_err(|_| Error::new(ErrorKind::UnknownAxis, pos))?;
-
-            Ok(Token::AxisMapping {
-                from,
-                to: AXES[idx],
-                input,
-                output,
-                inverted,
-            })
-        } else {
-            let idx = AXES_SDL
-                .binary_search(&key)
-                .map_err(|_| Error::new(ErrorKind::UnknownButton, pos))?;
-
-            Ok(Token::ButtonMapping {
-                from,
-                to: AXES[idx],
-                output,
-            })
+            _ => Err(Error::new(ErrorKind::InvalidValue, kind_pos)),
         }
     }
 
@@ -288,7 +265,18 @@ pub enum Token<'a> {
     Uuid(Uuid),
     Platform(&'a str),
     Name(&'a str),
-    #[allow(dead_code)]
+    // CRC of device name, used by SDL to tell apart devices with the same GUID.
+    Crc(#[allow(dead_code)] u16),
+    // SDL hint that must be set for mapping to be used, for example
+    // `!SDL_GAMECONTROLLER_USE_BUTTON_LABELS:=1`.
+    Hint(#[allow(dead_code)] &'a str),
+    // Other fields that don't affect mapping, like `sdk>=` or `face`.
+    Field {
+        #[allow(dead_code)]
+        key: &'a str,
+        #[allow(dead_code)]
+        value: &'a str,
+    },
     AxisMapping {
         from: u16,
         to: AxisOrBtn,
@@ -299,7 +287,6 @@ pub enum Token<'a> {
     ButtonMapping {
         from: u16,
         to: AxisOrBtn,
-        #[allow(dead_code)]
         output: AxisRange,
     },
     // This is just SDL representation, we will convert this to axis mapping later
@@ -313,11 +300,16 @@ pub enum Token<'a> {
     },
 }
 
+/// Part of axis used by entry of SDL mapping, see
+/// [`MappingEntry`](crate::MappingEntry).
 #[repr(u8)]
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum AxisRange {
+    /// Negative half of axis (`-` prefix).
     LowerHalf,
+    /// Positive half of axis (`+` prefix).
     UpperHalf,
+    /// Whole axis.
     Full,
 }
 
@@ -379,7 +371,7 @@ impl Display for Error {
 
 #[cfg(test)]
 mod tests {
-    use crate::mapping::parser::{ErrorKind, Parser};
+    use crate::mapping::parser::{AxisRange, ErrorKind, Parser, Token};
     use crate::utils::PATH_SEPARATOR;
 
     #[test]
@@ -418,4 +410,100 @@ mod tests {
         }
         assert_eq!(errors, 0);
     }
+
+    #[test]
+    fn fields_and_ranges() {
+        let line = "03000000260900008888000000010001,Pad,crc:1a2b,\
+                    hint:!SDL_GAMECONTROLLER_USE_BUTTON_LABELS:=1,sdk>=:29,+leftx:+a2~,";
+        let mut parser = Parser::new(line);
+
+        assert!(matches!(parser.next_token(), Some(Ok(Token::Uuid(_)))));
+        assert!(matches!(parser.next_token(), Some(Ok(Token::Name("Pad")))));
+        assert!(matches!(parser.next_token(), Some(Ok(Token::Crc(0x1a2b)))));
+        assert!(matches!(
+            parser.next_token(),
+            Some(Ok(Token::Hint("!SDL_GAMECONTROLLER_USE_BUTTON_LABELS:=1")))
+        ));
+        assert!(matches!(
+            parser.next_token(),
+            Some(Ok(Token::Field {
+                key: "sdk>=",
+                value: "29"
+            }))
+        ));
+        assert!(matches!(
+            parser.next_token(),
+            Some(Ok(Token::AxisMapping {
+                from: 2,
+                input: AxisRange::UpperHalf,
+                output: AxisRange::UpperHalf,
+                inverted: true,
+                ..
+            }))
+        ));
+        assert!(parser.next_token().is_none());
+    }
+
+    #[test]
+    fn error_offsets() {
+        let prefix = "03000000260900008888000000010001,Pad,";
+        let cases = [
+            ("a:b", ErrorKind::InvalidValue, 3),
+            ("a:x1", ErrorKind::InvalidValue, 2),
+            ("dpup:h0.x", ErrorKind::InvalidValue, 8),
+            ("dpup:h.", ErrorKind::InvalidValue, 6),
+            ("leftx:+b1", ErrorKind::InvalidValue, 7),
+            ("crc:zz", ErrorKind::InvalidValue, 4),
+            ("foo:b1", ErrorKind::UnknownButton, 0),
+            ("a:b1:b2", ErrorKind::InvalidKeyValPair, 0),
+            ("a", ErrorKind::InvalidKeyValPair, 0),
+        ];
+
+        for (entry, kind, offset) in cases {
+            let line = format!("{}{}", prefix, entry);
+            let mut parser = Parser::new(&line);
+            parser.next_token();
+            parser.next_token();
+
+            match parser.next_token() {
+                Some(Err(e)) => {
+                    assert_eq!(
+                        (e.kind(), e.position),
+                        (&kind, prefix.len() + offset),
+                        "{}",
+                        entry
+                    )
+                }
+                other => panic!("{}: expected error, got {:?}", entry, other),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_input() {
+        let lines = [
+            "",
+            ",",
+            "xinput",
+            "xinput,",
+            "xinput,,,,",
+            "xinput,Pad,:",
+            "xinput,Pad,+:-",
+            "xinput,Pad,a:+",
+            "xinput,Pad,a:a~",
+            "xinput,Pad,a:-a~",
+            "xinput,Pad,a:h",
+            "xinput,Pad,a:h.",
+            "xinput,Pad,é:bé",
+            "xinput,Pad,+é:aé~",
+            "xinput,Pad,a:b99999999999",
+        ];
+
+        for line in lines {
+            let mut parser = Parser::new(line);
+            for _ in 0..10 {
+                let _ = parser.next_token();
+            }
+        }
+    }
 }
@@ -0,0 +1,135 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ev::Code;
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+/// Table that replaces raw `Code`s reported by device before they are mapped to buttons and axes.
+///
+/// This can be used to work around firmware quirks, like swapped axes or buttons, without changing
+/// mapping. Remap table is set per gamepad with
+/// [`Gilrs::set_code_remap()`](crate::Gilrs::set_code_remap) and is applied before SDL-style
+/// mapping, so `Code` of all events, gamepad state and mapping use remapped codes. Axis values are
+/// still normalized using range of the original axis.
+///
+/// Like `Code`, `CodeRemap` is platform specific. With `serde-serialize` feature it can be
+/// stored together with other per device data.
+///
+/// ```
+/// use gilrs::{Axis, CodeRemap, Gilrs};
+///
+/// let mut gilrs = Gilrs::new().unwrap();
+/// let ids: Vec<_> = gilrs.gamepads().map(|(id, _)| id).collect();
+///
+/// for id in ids {
+///     let gamepad = gilrs.gamepad(id);
+///     let left = gamepad.axis_code(Axis::LeftStickX);
+///     let right = gamepad.axis_code(Axis::RightStickX);
+///
+///     // Firmware of this gamepad reports X axes of sticks swapped.
+///     if let (Some(left), Some(right)) = (left, right) {
+///         let mut remap = CodeRemap::new();
+///         remap.swap(left, right);
+///         gilrs.set_code_remap(id, remap);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct CodeRemap {
+    // Usually there are only a few entries, so linear search is fast enough.
+    entries: Vec<(Code, Code)>,
+}
+
+impl CodeRemap {
+    /// Creates empty remap table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes events with code `from` be reported with code `to`. Returns code that `from` was
+    /// previously remapped to.
+    pub fn insert(&mut self, from: Code, to: Code) -> Option<Code> {
+        match self.entries.iter_mut().find(|(f, _)| *f == from) {
+            Some((_, t)) => Some(std::mem::replace(t, to)),
+            None => {
+                self.entries.push((from, to));
+                None
+            }
+        }
+    }
+
+    /// Swaps codes `a` and `b`.
+    pub fn swap(&mut self, a: Code, b: Code) {
+        self.insert(a, b);
+        self.insert(b, a);
+    }
+
+    /// Removes remapping of `from` and returns code that it was remapped to.
+    pub fn remove(&mut self, from: Code) -> Option<Code> {
+        let idx = self.entries.iter().position(|(f, _)| *f == from)?;
+        Some(self.entries.remove(idx).1)
+    }
+
+    /// Returns code that events with `code` are reported with.
+    pub fn get(&self, code: Code) -> Code {
+        self.entries
+            .iter()
+            .find(|(f, _)| *f == code)
+            .map_or(code, |&(_, t)| t)
+    }
+
+    // Returns code that device reports for remapped `code`.
+    pub(crate) fn original(&self, code: Code) -> Code {
+        self.entries
+            .iter()
+            .find(|(_, t)| *t == code)
+            .map_or(code, |&(f, _)| f)
+    }
+
+    /// Returns `true` if table doesn't remap any code.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns iterator over `(from, to)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (Code, Code)> + '_ {
+        self.entries.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CodeRemap;
+    use crate::ev::Code;
+    use gilrs_core::native_ev_codes as nec;
+
+    #[test]
+    fn swap_and_remove() {
+        let (a, b, c) = (
+            Code(nec::AXIS_LSTICKX),
+            Code(nec::AXIS_RSTICKX),
+            Code(nec::BTN_SOUTH),
+        );
+
+        let mut remap = CodeRemap::new();
+        assert!(remap.is_empty());
+        remap.swap(a, b);
+        assert_eq!(remap.get(a), b);
+        assert_eq!(remap.get(b), a);
+        assert_eq!(remap.get(c), c);
+
+        assert_eq!(remap.insert(a, c), Some(b));
+        assert_eq!(remap.get(a), c);
+        assert_eq!(remap.remove(a), Some(c));
+        assert_eq!(remap.remove(a), None);
+        assert_eq!(remap.get(a), a);
+        assert_eq!(remap.iter().collect::<Vec<_>>(), vec![(b, a)]);
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Virtual gamepads that other applications can read (Linux only, requires `uinput` feature).
+//!
+//! This can be used to write remappers that read events from real gamepad, modify them (for
+//! example with filters) and forward them to [`VirtualGamepad`].
+//!
+//! Only Linux uinput is supported. There is no Windows implementation, creating virtual gamepads
+//! there requires ViGEmBus driver, which gilrs doesn't use.
+//!
+//! ```no_run
+//! use gilrs::output::VirtualGamepad;
+//! use gilrs::Gilrs;
+//!
+//! let mut gilrs = Gilrs::new().unwrap();
+//! let mut output = VirtualGamepad::new("Remapped gamepad").unwrap();
+//!
+//! loop {
+//!     while let Some(event) = gilrs.next_event_blocking(None) {
+//!         output.send(&event).unwrap();
+//!     }
+//! }
+//! ```
+
+use std::io::Result as IoResult;
+
+use gilrs_core::{native_ev_codes as nec, AxisInfo, EvCode, VirtualDevice};
+
+use crate::ev::{Axis, Button, Event, EventType};
+
+const AXIS_MAX: i32 = i16::MAX as i32;
+
+const BUTTONS: [(Button, EvCode); 19] = [
+    (Button::South, nec::BTN_SOUTH),
+    (Button::East, nec::BTN_EAST),
+    (Button::North, nec::BTN_NORTH),
+    (Button::West, nec::BTN_WEST),
+    (Button::C, nec::BTN_C),
+    (Button::Z, nec::BTN_Z),
+    (Button::LeftTrigger, nec::BTN_LT),
+    (Button::RightTrigger, nec::BTN_RT),
+    (Button::LeftTrigger2, nec::BTN_LT2),
+    (Button::RightTrigger2, nec::BTN_RT2),
+    (Button::Select, nec::BTN_SELECT),
+    (Button::Start, nec::BTN_START),
+    (Button::Mode, nec::BTN_MODE),
+    (Button::LeftThumb, nec::BTN_LTHUMB),
+    (Button::RightThumb, nec::BTN_RTHUMB),
+    (Button::DPadUp, nec::BTN_DPAD_UP),
+    (Button::DPadDown, nec::BTN_DPAD_DOWN),
+    (Button::DPadLeft, nec::BTN_DPAD_LEFT),
+    (Button::DPadRight, nec::BTN_DPAD_RIGHT),
+];
+
+const AXES: [(Axis, EvCode); 6] = [
+    (Axis::LeftStickX, nec::AXIS_LSTICKX),
+    (Axis::LeftStickY, nec::AXIS_LSTICKY),
+    (Axis::LeftZ, nec::AXIS_LEFTZ),
+    (Axis::RightStickX, nec::AXIS_RSTICKX),
+    (Axis::RightStickY, nec::AXIS_RSTICKY),
+    (Axis::RightZ, nec::AXIS_RIGHTZ),
+];
+
+/// Virtual gamepad with all buttons and axes of standard layout (except `Axis::DPadX` and
+/// `Axis::DPadY`, d-pad is reported with buttons).
+///
+/// Axes use range from -32767 to 32767 and, like on real gamepads, Y axes point downwards.
+#[derive(Debug)]
+pub struct VirtualGamepad {
+    device: VirtualDevice,
+}
+
+impl VirtualGamepad {
+    /// Creates virtual gamepad named `name` with vendor and product ID set to 0.
+    pub fn new(name: &str) -> IoResult<Self> {
+        Self::with_ids(name, 0, 0)
+    }
+
+    /// Creates virtual gamepad named `name` with given vendor and product ID. Other applications
+    /// may use them to select mapping.
+    pub fn with_ids(name: &str, vendor_id: u16, product_id: u16) -> IoResult<Self> {
+        let buttons: Vec<_> = BUTTONS.iter().map(|&(_, code)| code).collect();
+        let info = AxisInfo {
+            min: -AXIS_MAX,
+            max: AXIS_MAX,
+            deadzone: None,
+        };
+        let axes: Vec<_> = AXES.iter().map(|&(_, code)| (code, info)).collect();
+
+        VirtualDevice::new(name, vendor_id, product_id, &buttons, &axes)
+            .map(|device| VirtualGamepad { device })
+    }
+
+    /// Presses or releases button. Does nothing for `Button::Unknown`.
+    pub fn set_button(&mut self, btn: Button, pressed: bool) -> IoResult<()> {
+        match button_code(btn) {
+            Some(code) => {
+                self.device.emit(code, pressed as i32)?;
+                self.device.sync()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Sets value of axis. `value` is clamped to range from -1.0 to 1.0. Does nothing for
+    /// `Axis::Unknown`, `Axis::DPadX` and `Axis::DPadY`.
+    pub fn set_axis(&mut self, axis: Axis, value: f32) -> IoResult<()> {
+        match axis_code(axis) {
+            Some(code) => {
+                self.device.emit(code, axis_value(axis, value))?;
+                self.device.sync()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Forwards `ButtonPressed`, `ButtonReleased` and `AxisChanged` events. Other events are
+    /// ignored, as are events with unknown buttons or axes.
+    pub fn send(&mut self, event: &Event) -> IoResult<()> {
+        match event.event {
+            EventType::ButtonPressed(btn, _) => self.set_button(btn, true),
+            EventType::ButtonReleased(btn, _) => self.set_button(btn, false),
+            EventType::AxisChanged(axis, value, _) => self.set_axis(axis, value),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn button_code(btn: Button) -> Option<EvCode> {
+    BUTTONS
+        .iter()
+        .find(|&&(b, _)| b == btn)
+        .map(|&(_, code)| code)
+}
+
+fn axis_code(axis: Axis) -> Option<EvCode> {
+    AXES.iter()
+        .find(|&&(a, _)| a == axis)
+        .map(|&(_, code)| code)
+}
+
+fn axis_value(axis: Axis, value: f32) -> i32 {
+    let value = value.clamp(-1.0, 1.0);
+    let value = match axis {
+        Axis::LeftStickY | Axis::RightStickY if gilrs_core::IS_Y_AXIS_REVERSED => -value,
+        _ => value,
+    };
+
+    (value * AXIS_MAX as f32).round() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{axis_code, axis_value, button_code, AXIS_MAX};
+    use crate::ev::{Axis, Button};
+
+    #[test]
+    fn codes_and_values() {
+        assert!(button_code(Button::South).is_some());
+        assert!(button_code(Button::Unknown).is_none());
+        assert!(axis_code(Axis::DPadX).is_none());
+
+        assert_eq!(axis_value(Axis::LeftStickX, 1.0), AXIS_MAX);
+        assert_eq!(axis_value(Axis::LeftStickX, -2.0), -AXIS_MAX);
+        assert_eq!(axis_value(Axis::LeftStickY, 1.0), -AXIS_MAX);
+        assert_eq!(axis_value(Axis::RightZ, 0.0), 0);
+    }
+}
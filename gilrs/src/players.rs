@@ -0,0 +1,363 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ev::{Button, Event, EventType};
+use crate::gamepad::{GamepadId, Gilrs};
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use uuid::Uuid;
+
+/// Event emitted by [`PlayerAssigner`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PlayerEvent {
+    /// Gamepad pressed join button and claimed player slot.
+    Joined { player: usize, id: GamepadId },
+    /// Player left, either by pressing leave button or by calling
+    /// [`PlayerAssigner::leave()`]. Slot is free again.
+    Left { player: usize, id: GamepadId },
+    /// Player's gamepad was disconnected. Slot stays reserved until gamepad reconnects or player
+    /// leaves.
+    Disconnected { player: usize, id: GamepadId },
+    /// Player's gamepad was connected again. `id` may be different from the one used before
+    /// disconnect.
+    Reconnected { player: usize, id: GamepadId },
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Slot {
+    // `None` if slot was loaded from store and its gamepad didn't connect yet.
+    id: Option<GamepadId>,
+    uuid: [u8; 16],
+    connected: bool,
+}
+
+/// Assigns gamepads to player slots.
+///
+/// Gamepad claims first free slot when it presses join button (`Start` by default). Each gamepad
+/// can control at most one player. When gamepad disconnects, its slot stays reserved and is given
+/// back to it, or to other gamepad with the same UUID, when it connects again.
+///
+/// Assignments can be also kept across application runs with
+/// [`with_store()`](PlayerAssigner::with_store), so returning players get their slots back.
+///
+/// ```
+/// use gilrs::{Gilrs, PlayerAssigner, PlayerEvent};
+///
+/// let mut gilrs = Gilrs::new().unwrap();
+/// let mut players = PlayerAssigner::new(4);
+///
+/// while let Some(event) = gilrs.next_event() {
+///     match players.handle_event(&event, &gilrs) {
+///         Some(PlayerEvent::Joined { player, id }) => println!("{} is player {}", id, player),
+///         Some(PlayerEvent::Left { player, .. }) => println!("Player {} left", player),
+///         _ => (),
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PlayerAssigner {
+    join_button: Button,
+    leave_button: Option<Button>,
+    slots: Vec<Option<Slot>>,
+    store: Option<PathBuf>,
+}
+
+impl PlayerAssigner {
+    /// Creates new `PlayerAssigner` with `max_players` slots.
+    pub fn new(max_players: usize) -> Self {
+        PlayerAssigner {
+            join_button: Button::Start,
+            leave_button: None,
+            slots: vec![None; max_players],
+            store: None,
+        }
+    }
+
+    /// Sets button that has to be pressed to join. Default is `Button::Start`.
+    pub fn with_join_button(mut self, button: Button) -> Self {
+        self.join_button = button;
+        self
+    }
+
+    /// Sets button that makes player leave. By default there is no leave button.
+    pub fn with_leave_button(mut self, button: Option<Button>) -> Self {
+        self.leave_button = button;
+        self
+    }
+
+    /// Keeps slot assignments in file at `path`, so they survive application restart.
+    ///
+    /// Assignments saved in the file are loaded immediately and their slots are reserved, as if
+    /// their gamepads were disconnected. When gamepad with matching UUID connects, it gets its slot
+    /// back. Gamepads that were already connected when `Gilrs` was created don't emit
+    /// `EventType::Connected`, use [`sync()`](PlayerAssigner::sync) to claim their slots. The file
+    /// is updated every time player joins or leaves.
+    ///
+    /// Gamepads are recognized only by UUID, so two gamepads of the same model may swap their
+    /// slots. Errors while reading or writing the file are logged and otherwise ignored; missing
+    /// file is not an error.
+    pub fn with_store<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        let path = path.into();
+        match fs::read_to_string(&path) {
+            Ok(s) => self.load(&s),
+            Err(e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => warn!("Failed to read player assignments from {:?}: {}", path, e),
+        }
+        self.store = Some(path);
+        self
+    }
+
+    /// Gives reserved slots back to connected gamepads with matching UUID, for example after
+    /// assignments were loaded with [`with_store()`](PlayerAssigner::with_store).
+    pub fn sync(&mut self, gilrs: &Gilrs) -> Vec<PlayerEvent> {
+        gilrs
+            .gamepads()
+            .filter_map(|(id, gamepad)| self.handle(id, EventType::Connected, Some(gamepad.uuid())))
+            .collect()
+    }
+
+    /// Updates assignments according to `event`. `gilrs` is used to get UUID of gamepads.
+    pub fn handle_event(&mut self, event: &Event, gilrs: &Gilrs) -> Option<PlayerEvent> {
+        let uuid = gilrs
+            .connected_gamepad(event.id)
+            .map(|gamepad| gamepad.uuid());
+
+        self.handle(event.id, event.event, uuid)
+    }
+
+    fn handle(
+        &mut self,
+        id: GamepadId,
+        event: EventType,
+        uuid: Option<[u8; 16]>,
+    ) -> Option<PlayerEvent> {
+        match event {
+            EventType::ButtonPressed(btn, _) if btn == self.join_button => {
+                if self.player(id).is_some() {
+                    return None;
+                }
+
+                let player = self.slots.iter().position(Option::is_none)?;
+                self.slots[player] = Some(Slot {
+                    id: Some(id),
+                    uuid: uuid.unwrap_or_default(),
+                    connected: true,
+                });
+                self.save();
+
+                Some(PlayerEvent::Joined { player, id })
+            }
+            EventType::ButtonPressed(btn, _) if Some(btn) == self.leave_button => {
+                let player = self.player(id)?;
+                self.leave(player)
+            }
+            EventType::Disconnected => {
+                let player = self.player(id)?;
+                if let Some(slot) = &mut self.slots[player] {
+                    slot.connected = false;
+                }
+
+                Some(PlayerEvent::Disconnected { player, id })
+            }
+            EventType::Connected => {
+                // Prefer slot that was used by the same gamepad id, then any slot reserved for
+                // gamepad with the same UUID.
+                let player = self
+                    .slots
+                    .iter()
+                    .position(|s| matches!(s, Some(s) if !s.connected && s.id == Some(id)))
+                    .or_else(|| {
+                        let uuid = uuid?;
+                        self.slots.iter().position(|s| {
+                            matches!(s, Some(s) if !s.connected && s.uuid == uuid)
+                                && self.player(id).is_none()
+                        })
+                    })?;
+
+                if let Some(slot) = &mut self.slots[player] {
+                    slot.id = Some(id);
+                    slot.connected = true;
+                }
+
+                Some(PlayerEvent::Reconnected { player, id })
+            }
+            _ => None,
+        }
+    }
+
+    /// Frees slot of `player`. Returns `None` if no gamepad was assigned to it.
+    pub fn leave(&mut self, player: usize) -> Option<PlayerEvent> {
+        let slot = self.slots.get_mut(player)?.take()?;
+        self.save();
+
+        Some(PlayerEvent::Left {
+            player,
+            id: slot.id?,
+        })
+    }
+
+    /// Returns player controlled by gamepad with given id.
+    pub fn player(&self, id: GamepadId) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|s| matches!(s, Some(s) if s.id == Some(id) && s.connected))
+    }
+
+    /// Returns id of gamepad assigned to `player`, even if it's currently disconnected.
+    pub fn gamepad(&self, player: usize) -> Option<GamepadId> {
+        self.slots.get(player).copied().flatten().and_then(|s| s.id)
+    }
+
+    /// Returns `true` if `player` has gamepad assigned and it is connected.
+    pub fn is_connected(&self, player: usize) -> bool {
+        matches!(self.slots.get(player), Some(Some(s)) if s.connected)
+    }
+
+    /// Returns maximum number of players.
+    pub fn max_players(&self) -> usize {
+        self.slots.len()
+    }
+
+    // Each line of store contains player number and UUID of its gamepad.
+    fn to_store_string(&self) -> String {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(player, slot)| {
+                let uuid = Uuid::from_bytes(slot.as_ref()?.uuid);
+                Some(format!("{} {}\n", player, uuid.simple()))
+            })
+            .collect()
+    }
+
+    fn load(&mut self, s: &str) {
+        for line in s.lines().filter(|l| !l.trim().is_empty()) {
+            let parsed = line.split_once(' ').and_then(|(player, uuid)| {
+                let player = player.parse::<usize>().ok()?;
+                let uuid = Uuid::parse_str(uuid.trim()).ok()?;
+                Some((player, uuid))
+            });
+
+            match parsed {
+                Some((player, uuid)) if player < self.slots.len() => {
+                    self.slots[player] = Some(Slot {
+                        id: None,
+                        uuid: *uuid.as_bytes(),
+                        connected: false,
+                    });
+                }
+                _ => warn!("Invalid player assignment: {:?}", line),
+            }
+        }
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.store {
+            if let Err(e) = fs::write(path, self.to_store_string()) {
+                warn!("Failed to save player assignments to {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PlayerAssigner, PlayerEvent};
+    use crate::ev::{Button, Code, EventType};
+    use crate::gamepad::GamepadId;
+    use gilrs_core::native_ev_codes as necs;
+
+    use std::{env, fs};
+
+    fn press(btn: Button) -> EventType {
+        EventType::ButtonPressed(btn, Code(necs::BTN_START))
+    }
+
+    #[test]
+    fn join_leave_reconnect() {
+        let (a, b, c) = (GamepadId(0), GamepadId(1), GamepadId(2));
+        let mut players = PlayerAssigner::new(2).with_leave_button(Some(Button::Select));
+
+        assert_eq!(
+            players.handle(a, press(Button::Start), Some([1; 16])),
+            Some(PlayerEvent::Joined { player: 0, id: a })
+        );
+        // Same gamepad can't take second slot.
+        assert_eq!(players.handle(a, press(Button::Start), Some([1; 16])), None);
+        assert_eq!(players.handle(b, press(Button::South), Some([2; 16])), None);
+        assert_eq!(
+            players.handle(b, press(Button::Start), Some([2; 16])),
+            Some(PlayerEvent::Joined { player: 1, id: b })
+        );
+        assert_eq!(players.handle(c, press(Button::Start), Some([3; 16])), None);
+
+        assert_eq!(
+            players.handle(a, EventType::Disconnected, None),
+            Some(PlayerEvent::Disconnected { player: 0, id: a })
+        );
+        assert_eq!(players.player(a), None);
+        assert_eq!(players.gamepad(0), Some(a));
+        // Slot is still reserved.
+        assert_eq!(players.handle(c, press(Button::Start), Some([3; 16])), None);
+        assert_eq!(players.handle(c, EventType::Connected, Some([3; 16])), None);
+        assert_eq!(
+            players.handle(c, EventType::Connected, Some([1; 16])),
+            Some(PlayerEvent::Reconnected { player: 0, id: c })
+        );
+        assert_eq!(players.player(c), Some(0));
+
+        assert_eq!(
+            players.handle(b, press(Button::Select), Some([2; 16])),
+            Some(PlayerEvent::Left { player: 1, id: b })
+        );
+        assert!(!players.is_connected(1));
+    }
+
+    #[test]
+    fn store() {
+        let (a, b, c) = (GamepadId(0), GamepadId(1), GamepadId(2));
+        let path = env::temp_dir().join(format!("gilrs-players-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut players = PlayerAssigner::new(3).with_store(&path);
+        players.handle(a, press(Button::Start), Some([1; 16]));
+        players.handle(b, press(Button::Start), Some([2; 16]));
+        players.handle(c, press(Button::Start), Some([3; 16]));
+        players.leave(1);
+
+        let mut saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(saved.lines().count(), 2);
+        // Invalid lines are skipped.
+        saved.push_str("9 01010101010101010101010101010101\nfoo\n");
+        fs::write(&path, saved).unwrap();
+
+        let mut players = PlayerAssigner::new(3).with_store(&path);
+        assert_eq!(players.gamepad(0), None);
+        assert!(!players.is_connected(0));
+        // Slots are reserved for returning players.
+        assert_eq!(
+            players.handle(a, press(Button::Start), Some([4; 16])),
+            Some(PlayerEvent::Joined { player: 1, id: a })
+        );
+        assert_eq!(
+            players.handle(b, EventType::Connected, Some([3; 16])),
+            Some(PlayerEvent::Reconnected { player: 2, id: b })
+        );
+        assert_eq!(players.handle(c, press(Button::Start), Some([5; 16])), None);
+        // Leaving slot whose gamepad didn't connect yet frees it.
+        assert_eq!(players.leave(0), None);
+        assert_eq!(
+            players.handle(c, press(Button::Start), Some([5; 16])),
+            Some(PlayerEvent::Joined { player: 0, id: c })
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
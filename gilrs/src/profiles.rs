@@ -0,0 +1,405 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::ev::filter::FilterFn;
+use crate::ev::{Event, EventType};
+use crate::gamepad::{GamepadId, Gilrs};
+use crate::mapping::{MappingData, MappingError};
+use crate::utils;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+
+#[cfg(feature = "serde-serialize")]
+use serde::{Deserialize, Serialize};
+
+/// Identifies profile added to [`Profiles`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct ProfileId(usize);
+
+impl From<ProfileId> for usize {
+    fn from(x: ProfileId) -> usize {
+        x.0
+    }
+}
+
+impl Display for ProfileId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// Named set of filters and optional mapping, for example "menu" or "vehicle".
+pub struct Profile {
+    name: String,
+    mapping: Option<MappingData>,
+    filters: Vec<Box<dyn FilterFn>>,
+}
+
+impl Profile {
+    /// Creates empty profile. Events of gamepads that use it pass unchanged.
+    pub fn new(name: &str) -> Self {
+        Profile {
+            name: name.to_owned(),
+            mapping: None,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Sets mapping that is applied with [`Gilrs::set_mapping()`] when gamepad switches to this
+    /// profile. Switching to profile without mapping keeps mapping that gamepad currently uses.
+    pub fn with_mapping(mut self, mapping: MappingData) -> Self {
+        self.mapping = Some(mapping);
+        self
+    }
+
+    /// Appends filter. Filters are applied in the same order as they were added.
+    pub fn with_filter<F: FilterFn + 'static>(mut self, filter: F) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// Returns name of the profile.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns mapping set with [`with_mapping()`](Self::with_mapping).
+    pub fn mapping(&self) -> Option<&MappingData> {
+        self.mapping.as_ref()
+    }
+
+    fn apply(&self, mut ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        for filter in &self.filters {
+            ev = filter.filter(ev, gilrs);
+        }
+
+        ev
+    }
+}
+
+impl Debug for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Profile")
+            .field("name", &self.name)
+            .field("mapping", &self.mapping)
+            .field(
+                "filters",
+                &self
+                    .filters
+                    .iter()
+                    .map(|f| f.describe())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Per gamepad profiles that can be switched at runtime.
+///
+/// Each gamepad uses at most one profile at a time, the default one unless it was switched with
+/// [`switch()`](Self::switch). `Profiles` implements [`FilterFn`], so filters of active profile
+/// are applied by passing events through it. Switching profile inserts
+/// `EventType::ProfileChanged` to the event queue of `Gilrs`.
+///
+/// When `None` is filtered (see [`ev::filter`](crate::ev::filter) module documentation), it is
+/// passed to filters of every profile used by any gamepad and first generated event is returned.
+///
+/// ```
+/// use gilrs::ev::filter::{Jitter, Repeat};
+/// use gilrs::{Filter, Gilrs, Profile, Profiles};
+///
+/// let mut gilrs = Gilrs::new().unwrap();
+/// let mut profiles = Profiles::new();
+/// let menu = profiles.add(Profile::new("menu").with_filter(Repeat::new()));
+/// profiles.add(Profile::new("on-foot").with_filter(Jitter::new()));
+/// profiles.set_default(Some(menu));
+///
+/// while let Some(event) = gilrs.next_event().filter_ev(&profiles, &mut gilrs) {
+///     gilrs.update(&event);
+///     // Start game.
+///     # if false {
+///     profiles.switch(&mut gilrs, event.id, "on-foot").unwrap();
+///     # }
+/// #   break;
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+    active: HashMap<GamepadId, ProfileId>,
+    default: Option<ProfileId>,
+}
+
+impl Profiles {
+    /// Creates `Profiles` without any profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds profile and returns its id. Profile with the same name is replaced.
+    pub fn add(&mut self, profile: Profile) -> ProfileId {
+        match self.find(profile.name()) {
+            Some(id) => {
+                self.profiles[id.0] = profile;
+                id
+            }
+            None => {
+                self.profiles.push(profile);
+                ProfileId(self.profiles.len() - 1)
+            }
+        }
+    }
+
+    /// Returns id of profile named `name`.
+    pub fn find(&self, name: &str) -> Option<ProfileId> {
+        self.profiles
+            .iter()
+            .position(|p| p.name == name)
+            .map(ProfileId)
+    }
+
+    /// Returns profile with given id.
+    pub fn get(&self, id: ProfileId) -> Option<&Profile> {
+        self.profiles.get(id.0)
+    }
+
+    /// Sets profile used by gamepads that weren't switched to other profile. `None` (default)
+    /// means that events of such gamepads pass unchanged.
+    pub fn set_default(&mut self, profile: Option<ProfileId>) {
+        self.default = profile;
+    }
+
+    /// Returns profile currently used by gamepad.
+    pub fn active(&self, id: GamepadId) -> Option<ProfileId> {
+        self.active.get(&id).copied().or(self.default)
+    }
+
+    /// Switches gamepad to profile named `name` and applies its mapping, if any.
+    /// `EventType::ProfileChanged` is inserted if profile is different from the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProfileError::NotFound` if there is no profile named `name` and
+    /// `ProfileError::Mapping` if mapping could not be set. Gamepad keeps its profile in both
+    /// cases.
+    pub fn switch(
+        &mut self,
+        gilrs: &mut Gilrs,
+        id: GamepadId,
+        name: &str,
+    ) -> Result<ProfileId, ProfileError> {
+        let profile_id = self.find(name).ok_or(ProfileError::NotFound)?;
+        let profile = &self.profiles[profile_id.0];
+
+        if let Some(ref mapping) = profile.mapping {
+            gilrs.set_mapping(id.into(), mapping, profile.name.as_str())?;
+        }
+
+        if self.active(id) != Some(profile_id) {
+            self.active.insert(id, profile_id);
+            gilrs.insert_event(Event {
+                id,
+                event: EventType::ProfileChanged(profile_id),
+                time: utils::time_now(),
+            });
+        }
+
+        Ok(profile_id)
+    }
+
+    /// Switches all connected gamepads to profile named `name` and makes it the default one.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`switch()`](Self::switch). Gamepads are switched in order, so some of them may
+    /// use new profile when error is returned.
+    pub fn switch_all(&mut self, gilrs: &mut Gilrs, name: &str) -> Result<(), ProfileError> {
+        let ids: Vec<_> = gilrs.gamepads().map(|(id, _)| id).collect();
+        for id in ids {
+            self.switch(gilrs, id, name)?;
+        }
+        self.default = self.find(name);
+
+        Ok(())
+    }
+}
+
+impl FilterFn for Profiles {
+    fn describe(&self) -> String {
+        let profiles = self
+            .profiles
+            .iter()
+            .map(|p| {
+                let filters = p.filters.iter().map(|f| f.describe()).collect::<Vec<_>>();
+                format!("{}: [{}]", p.name, filters.join(", "))
+            })
+            .collect::<Vec<_>>();
+
+        format!("Profiles {{ {} }}", profiles.join(", "))
+    }
+
+    fn filter(&self, ev: Option<Event>, gilrs: &mut Gilrs) -> Option<Event> {
+        match ev {
+            Some(e) => match self.active(e.id).and_then(|p| self.get(p)) {
+                Some(profile) => profile.apply(ev, gilrs),
+                None => ev,
+            },
+            None => {
+                let mut used: Vec<_> = self.active.values().copied().chain(self.default).collect();
+                used.sort_by_key(|p| p.0);
+                used.dedup();
+
+                used.into_iter()
+                    .filter_map(|p| self.get(p))
+                    .find_map(|profile| profile.apply(None, gilrs))
+            }
+        }
+    }
+}
+
+/// Error returned by [`Profiles::switch()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProfileError {
+    /// There is no profile with given name.
+    NotFound,
+    /// Mapping of the profile could not be set.
+    Mapping(MappingError),
+}
+
+impl Error for ProfileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProfileError::Mapping(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::NotFound => f.write_str("profile not found"),
+            ProfileError::Mapping(_) => f.write_str("failed to set mapping of the profile"),
+        }
+    }
+}
+
+impl From<MappingError> for ProfileError {
+    fn from(e: MappingError) -> Self {
+        ProfileError::Mapping(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Profile, ProfileError, ProfileId, Profiles};
+    use crate::ev::filter::{Filter, FilterFn, Jitter, Repeat};
+    use crate::ev::{Button, Code, Event, EventType};
+    use crate::gamepad::{GamepadId, Gilrs, GilrsBuilder};
+    use crate::mapping::MappingData;
+    use crate::test_backend;
+    use gilrs_core::native_ev_codes as nec;
+
+    #[test]
+    fn add_and_default() {
+        let mut profiles = Profiles::new();
+        let menu = profiles.add(Profile::new("menu"));
+        let vehicle = profiles.add(Profile::new("vehicle"));
+        assert_eq!(menu, ProfileId(0));
+        assert_eq!(vehicle, ProfileId(1));
+        // Replaced, not added.
+        assert_eq!(profiles.add(Profile::new("menu")), menu);
+        assert_eq!(profiles.find("vehicle"), Some(vehicle));
+        assert_eq!(profiles.find("on-foot"), None);
+
+        let id = GamepadId(0);
+        assert_eq!(profiles.active(id), None);
+        profiles.set_default(Some(menu));
+        assert_eq!(profiles.active(id), Some(menu));
+        profiles.active.insert(id, vehicle);
+        assert_eq!(profiles.active(id), Some(vehicle));
+        assert_eq!(profiles.active(GamepadId(1)), Some(menu));
+    }
+
+    #[test]
+    fn describe() {
+        let mut profiles = Profiles::new();
+        profiles.add(Profile::new("menu").with_filter(Repeat::new()));
+        profiles.add(Profile::new("vehicle").with_filter(Jitter::new()));
+
+        assert_eq!(
+            profiles.describe(),
+            "Profiles { menu: [Repeat { after: 500ms, every: 30ms }], \
+             vehicle: [Jitter { threshold: 0.01 }] }"
+        );
+    }
+
+    #[test]
+    fn switch_and_filter() {
+        let mut gilrs = GilrsBuilder::new()
+            .with_default_filters(false)
+            .set_update_state(false)
+            .build()
+            .unwrap();
+        let first = test_backend::connect(&mut gilrs, "First");
+        let second = test_backend::connect(&mut gilrs, "Second");
+        let drop = |ev: Option<Event>, _: &mut Gilrs| {
+            ev.map(|e| Event {
+                event: EventType::Dropped,
+                ..e
+            })
+        };
+        let mut mapping = MappingData::new();
+        mapping.insert_btn(Code(nec::BTN_SOUTH), Button::East);
+
+        let mut profiles = Profiles::new();
+        let menu = profiles.add(Profile::new("menu").with_filter(drop));
+        let vehicle = profiles.add(Profile::new("vehicle").with_mapping(mapping));
+        let events = |gilrs: &mut Gilrs| {
+            let mut events = Vec::new();
+            while let Some(ev) = gilrs.next_event() {
+                events.push((ev.id, ev.event));
+            }
+            events
+        };
+
+        assert_eq!(profiles.switch(&mut gilrs, first, "menu"), Ok(menu));
+        assert_eq!(
+            events(&mut gilrs),
+            [(first, EventType::ProfileChanged(menu))]
+        );
+        // Switching to the same profile doesn't emit event.
+        assert_eq!(profiles.switch(&mut gilrs, first, "menu"), Ok(menu));
+        assert!(events(&mut gilrs).is_empty());
+        assert_eq!(
+            profiles.switch(&mut gilrs, first, "on-foot"),
+            Err(ProfileError::NotFound)
+        );
+        assert_eq!(profiles.active(first), Some(menu));
+
+        // Only gamepad that uses profile is filtered.
+        let ev = Event::new(first, EventType::Connected);
+        assert_eq!(
+            Some(ev).filter_ev(&profiles, &mut gilrs).map(|e| e.event),
+            Some(EventType::Dropped)
+        );
+        let ev = Event::new(second, EventType::Connected);
+        assert_eq!(Some(ev).filter_ev(&profiles, &mut gilrs), Some(ev));
+
+        assert_eq!(profiles.switch(&mut gilrs, second, "vehicle"), Ok(vehicle));
+        assert!(events(&mut gilrs).contains(&(second, EventType::ProfileChanged(vehicle))));
+        assert_eq!(gilrs.gamepad(second).map_name(), Some("vehicle"));
+        assert_eq!(
+            gilrs.gamepad(second).button_code(Button::East),
+            Some(Code(nec::BTN_SOUTH))
+        );
+    }
+}
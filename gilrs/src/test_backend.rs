@@ -0,0 +1,330 @@
+// Copyright 2016-2018 Mateusz Sieczko and other GilRs Developers
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Backend used instead of `gilrs_core::Gilrs` in unit tests.
+//!
+//! It has the same interface as `gilrs_core`, but gamepads and their events are created by tests,
+//! so filters, mappings and gamepad state can be tested through `Gilrs` without real devices.
+#![allow(dead_code)]
+
+use gilrs_core::{
+    native_ev_codes as nec, AxisInfo, ConnectionType, EvCode, Event, EventType, FfDevice,
+    HeadsetState, HidError, InaccessibleDevice, PowerInfo,
+};
+
+use crate::GamepadId;
+
+use std::collections::VecDeque;
+use std::error;
+use std::fmt::{self, Display};
+use std::time::{Duration, SystemTime};
+
+use fnv::FnvHashMap;
+
+#[derive(Debug)]
+pub enum Error {
+    NotImplemented(Gilrs),
+    Other(Box<dyn error::Error + Send + Sync + 'static>),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("test backend error")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Gilrs {
+    gamepads: Vec<Gamepad>,
+    events: VecDeque<Event>,
+    scanned: bool,
+    poll_interval: Option<Duration>,
+}
+
+impl Gilrs {
+    pub fn new() -> Result<Self, Error> {
+        Ok(Gilrs {
+            scanned: true,
+            ..Default::default()
+        })
+    }
+
+    pub fn new_deferred() -> Self {
+        Gilrs::default()
+    }
+
+    pub fn scan_devices(&mut self) -> Result<(), Box<dyn error::Error + Send + Sync + 'static>> {
+        self.scanned = true;
+        Ok(())
+    }
+
+    pub fn is_scanned(&self) -> bool {
+        self.scanned
+    }
+
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    pub fn next_event_blocking(&mut self, _timeout: Option<Duration>) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    pub fn gamepad(&self, id: usize) -> Option<&Gamepad> {
+        self.gamepads.get(id).filter(|_| self.scanned)
+    }
+
+    pub fn inaccessible_devices(&self) -> &[InaccessibleDevice] {
+        &[]
+    }
+
+    pub fn last_gamepad_hint(&self) -> usize {
+        if self.scanned {
+            self.gamepads.len()
+        } else {
+            0
+        }
+    }
+
+    pub fn poll_interval(&self) -> Option<Duration> {
+        self.poll_interval
+    }
+
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = Some(interval);
+    }
+
+    /// Connects `gamepad` as new device and queues `Connected` event. Returns its id.
+    ///
+    /// If devices weren't scanned yet, no event is queued and gamepad is found by
+    /// `scan_devices()`, like gamepad that was plugged in before application started.
+    pub fn connect(&mut self, gamepad: Gamepad) -> usize {
+        self.gamepads.push(gamepad);
+        let id = self.gamepads.len() - 1;
+        if self.scanned {
+            self.events.push_back(Event::new(id, EventType::Connected));
+        }
+
+        id
+    }
+
+    /// Connects gamepad `id` again and queues `Connected` event.
+    pub fn reconnect(&mut self, id: usize) {
+        self.gamepads[id].connected = true;
+        self.events.push_back(Event::new(id, EventType::Connected));
+    }
+
+    /// Disconnects gamepad `id` and queues `Disconnected` event.
+    pub fn disconnect(&mut self, id: usize) {
+        self.gamepads[id].connected = false;
+        self.gamepads[id].values.clear();
+        self.events
+            .push_back(Event::new(id, EventType::Disconnected));
+    }
+
+    /// Changes connection type of gamepad `id` without queueing event. It's reported to `Gilrs`
+    /// when gamepad reconnects.
+    pub fn set_connection_type(&mut self, id: usize, connection_type: ConnectionType) {
+        self.gamepads[id].connection_type = connection_type;
+    }
+
+    /// Changes value reported by `query_value()` without queueing event, like when event is lost.
+    pub fn set_value(&mut self, id: usize, nec: EvCode, value: i32) {
+        self.gamepads[id].values.insert(nec, value);
+    }
+
+    /// Queues event of gamepad `id` with given time.
+    pub fn push(&mut self, id: usize, event: EventType, time: SystemTime) {
+        match event {
+            EventType::ButtonPressed(nec) => {
+                self.gamepads[id].values.insert(nec, 1);
+            }
+            EventType::ButtonReleased(nec) => {
+                self.gamepads[id].values.insert(nec, 0);
+            }
+            EventType::AxisValueChanged(val, nec) => {
+                self.gamepads[id].values.insert(nec, val);
+            }
+            _ => (),
+        }
+
+        let mut ev = Event::new(id, event);
+        ev.time = time;
+        self.events.push_back(ev);
+    }
+}
+
+#[derive(Debug)]
+pub struct Gamepad {
+    name: String,
+    uuid: [u8; 16],
+    buttons: Vec<EvCode>,
+    axes: Vec<EvCode>,
+    axes_info: FnvHashMap<EvCode, AxisInfo>,
+    values: FnvHashMap<EvCode, i32>,
+    connected: bool,
+    connection_type: ConnectionType,
+}
+
+impl Gamepad {
+    /// Creates gamepad with all standard buttons, sticks in range `-32768..=32767` and analog
+    /// triggers (`AXIS_LT2`, `AXIS_RT2`) in range `0..=255`.
+    pub fn new(name: &str) -> Self {
+        let buttons = vec![
+            nec::BTN_SOUTH,
+            nec::BTN_EAST,
+            nec::BTN_NORTH,
+            nec::BTN_WEST,
+            nec::BTN_LT,
+            nec::BTN_RT,
+            nec::BTN_SELECT,
+            nec::BTN_START,
+            nec::BTN_MODE,
+            nec::BTN_LTHUMB,
+            nec::BTN_RTHUMB,
+            nec::BTN_DPAD_UP,
+            nec::BTN_DPAD_DOWN,
+            nec::BTN_DPAD_LEFT,
+            nec::BTN_DPAD_RIGHT,
+        ];
+        let stick = AxisInfo {
+            min: -32768,
+            max: 32767,
+            deadzone: None,
+        };
+        let trigger = AxisInfo {
+            min: 0,
+            max: 255,
+            deadzone: None,
+        };
+        let axes_info: FnvHashMap<_, _> = [
+            (nec::AXIS_LSTICKX, stick),
+            (nec::AXIS_LSTICKY, stick),
+            (nec::AXIS_RSTICKX, stick),
+            (nec::AXIS_RSTICKY, stick),
+            (nec::AXIS_LT2, trigger),
+            (nec::AXIS_RT2, trigger),
+        ]
+        .into_iter()
+        .collect();
+        let mut axes: Vec<_> = axes_info.keys().copied().collect();
+        axes.sort();
+
+        let mut uuid = [0; 16];
+        for (i, b) in name.bytes().enumerate() {
+            uuid[i % 16] ^= b;
+        }
+
+        Gamepad {
+            name: name.to_owned(),
+            uuid,
+            buttons,
+            axes,
+            axes_info,
+            values: FnvHashMap::default(),
+            connected: true,
+            connection_type: ConnectionType::Unknown,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn uuid(&self) -> [u8; 16] {
+        self.uuid
+    }
+
+    pub fn vendor_id(&self) -> Option<u16> {
+        None
+    }
+
+    pub fn product_id(&self) -> Option<u16> {
+        None
+    }
+
+    pub fn xinput_user_index(&self) -> Option<u32> {
+        None
+    }
+
+    pub fn power_info(&self) -> PowerInfo {
+        PowerInfo::Unknown
+    }
+
+    pub fn headset_state(&self) -> HeadsetState {
+        HeadsetState::Unknown
+    }
+
+    pub fn connection_type(&self) -> ConnectionType {
+        self.connection_type
+    }
+
+    pub fn device_path(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn send_output_report(&self, _data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn send_feature_report(&self, _data: &[u8]) -> Result<(), HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_feature_report(&self, _buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn get_input_report(&self, _buf: &mut [u8]) -> Result<usize, HidError> {
+        Err(HidError::NotSupported)
+    }
+
+    pub fn is_ff_supported(&self) -> bool {
+        false
+    }
+
+    pub fn motor_count(&self) -> usize {
+        0
+    }
+
+    pub fn ff_device(&self) -> Option<FfDevice> {
+        None
+    }
+
+    pub fn buttons(&self) -> &[EvCode] {
+        &self.buttons
+    }
+
+    pub fn axes(&self) -> &[EvCode] {
+        &self.axes
+    }
+
+    pub fn axis_info(&self, nec: EvCode) -> Option<&AxisInfo> {
+        self.axes_info.get(&nec)
+    }
+
+    pub fn query_value(&self, nec: EvCode) -> Option<i32> {
+        self.connected
+            .then(|| self.values.get(&nec).copied().unwrap_or(0))
+    }
+
+    pub fn code_name(&self, _nec: EvCode) -> Option<String> {
+        None
+    }
+}
+
+/// Connects gamepad named `name` to `gilrs` and handles its `Connected` event.
+pub fn connect(gilrs: &mut crate::Gilrs, name: &str) -> GamepadId {
+    let id = gilrs.test_backend().connect(Gamepad::new(name));
+    while gilrs.next_event().is_some() {}
+
+    GamepadId(id)
+}
@@ -20,6 +20,34 @@ pub fn clamp(x: f32, min: f32, max: f32) -> f32 {
     x.clamp(min, max)
 }
 
+/// Vendor names that some drivers prepend to product name.
+const VENDOR_PREFIXES: &[&str] = &[
+    "Sony Interactive Entertainment",
+    "Sony Computer Entertainment",
+    "Nintendo Co., Ltd.",
+    "Microsoft Corporation",
+    "Valve Software",
+];
+
+/// Cleans up product name reported by OS: removes control and replacement characters, collapses
+/// whitespace and strips vendor name if there is something left after it.
+pub fn normalize_product_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .filter(|&c| c != char::REPLACEMENT_CHARACTER)
+        .collect::<String>();
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    for prefix in VENDOR_PREFIXES {
+        if let Some(rest) = name.strip_prefix(prefix).and_then(|s| s.strip_prefix(' ')) {
+            return rest.to_owned();
+        }
+    }
+
+    name
+}
+
 #[cfg(path_separator = "backslash")]
 macro_rules! PATH_SEPARATOR {
     () => {
@@ -46,4 +74,24 @@ mod tests {
         assert_eq!(clamp(0.5, 0.0, 1.0), 0.5);
         assert_eq!(clamp(2.0, 0.0, 1.0), 1.0);
     }
+
+    #[test]
+    fn t_normalize_product_name() {
+        assert_eq!(
+            normalize_product_name("Sony Interactive Entertainment Wireless Controller"),
+            "Wireless Controller"
+        );
+        assert_eq!(
+            normalize_product_name("  Xbox\tWireless \u{FFFD}Controller\0 "),
+            "Xbox Wireless Controller"
+        );
+        assert_eq!(
+            normalize_product_name("Sony Interactive Entertainment"),
+            "Sony Interactive Entertainment"
+        );
+        assert_eq!(
+            normalize_product_name("Valve SoftwareX Pad"),
+            "Valve SoftwareX Pad"
+        );
+    }
 }